@@ -1,23 +1,47 @@
 use crate::{
-    audit::AuditLog,
-    dag::dag::{DagScheduler, RequestDag},
+    audit::{AuditBackend, AuditCheckpoint, AuditEntry, AuditLog, AuditReport, AuditedNode},
+    audit_crypt::AuditKeyring,
+    audit_segment::AuditRotation,
+    credential::AuditCredential,
+    dag::{
+        dag::{DagScheduler, NodeProof, RequestDag},
+        executor::{
+            DagRun, DagRunError, DagTask, TaskError, TaskFailure, TaskGraph, TaskInputs,
+            TaskOutput, TaskPolicy,
+        },
+    },
+    egress::{EgressError, EgressGate, EgressKind},
     model::engine::HybridModelEngine,
+    node_key::{NodeKey, PublishedKey},
     payment::{bitcoin::LightningGateway, PaymentError, PaymentGateway},
-    policy::{PolicyError, SafetyPolicy},
-    verification::axiom_checker::{
-        parse_axiom_set, C0Signature, DeterministicVerifier, VerificationError, Verifier,
+    policy::{
+        Caller, OutputVerdict, PiiScrub, PolicyError, PolicyExplanation, PolicyLoadOptions,
+        PolicyRequest, SafetyDecision, SafetyPolicy,
+    },
+    transparency::{ConsistencyProof, InclusionProof, SignedTreeHead},
+    triage::{TriageOutcome, TriageReport},
+    verification::{
+        axiom_checker::{
+            parse_axiom_set, C0Signature, DeterministicVerifier, VerificationError, Verifier,
+        },
+        axiom_source::{AxiomResolver, AxiomSourceError, ResolvedAxioms},
     },
 };
 use axum::{
-    extract::State,
-    http::StatusCode,
-    response::{IntoResponse, Response},
-    routing::post,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{get, post},
     Json, Router,
 };
+use futures::Stream;
 use serde::{Deserialize, Serialize};
-use serde_json::json;
-use std::sync::Arc;
+use serde_json::{json, Value};
+use std::{any::Any, collections::BTreeMap, future::Future, sync::Arc, time::Duration};
+use tracing::{debug, warn};
 use uuid::Uuid;
 
 #[derive(Clone)]
@@ -28,7 +52,12 @@ pub struct AppState {
     pub payment: Arc<LightningGateway>,
     pub dag: Arc<DagScheduler>,
     pub audit: Arc<AuditLog>,
+    pub node_key: Arc<NodeKey>,
+    pub egress: Arc<EgressGate>,
+    pub axioms: Arc<AxiomResolver>,
     pub require_payment: bool,
+    /// Token for `/api/v1/admin/*` routes; admin routes are disabled when unset.
+    pub admin_token: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -38,6 +67,9 @@ pub struct CreativeRequest {
     pub temperature: Option<f32>,
     pub top_k: Option<u32>,
     pub lightning_invoice: Option<String>,
+    /// Any other fields, screened by the identity gate.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -50,11 +82,16 @@ pub struct CreativeResponse {
 #[derive(Debug, Deserialize)]
 pub struct VerifiedRequest {
     pub prompt: String,
+    /// Inline axiom set JSON, an `https://` URL, or a `sha256:` content hash.
     pub axiom_set: String,
+    /// Expected SHA-256 of a remote axiom set; required for URLs.
+    pub axiom_hash: Option<String>,
     pub max_steps: Option<u32>,
     pub lightning_invoice: Option<String>,
     pub allow_network: Option<bool>,
     pub free_local: Option<bool>,
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
 }
 
 #[derive(Debug, Serialize)]
@@ -66,6 +103,23 @@ pub struct VerifiedResponse {
     pub merkle_root: String,
 }
 
+/// Returned instead of output when triage scores the prompt below the threshold.
+#[derive(Debug, Serialize)]
+pub struct ClarificationResponse {
+    pub request_id: Uuid,
+    pub status: String,
+    pub clarify: String,
+    pub triage: TriageReport,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PolicyExplainRequest {
+    pub prompt: String,
+    pub mode: Option<String>,
+    pub axiom_set: Option<String>,
+    pub media: Option<Vec<String>>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ErrorResponse {
     pub error: String,
@@ -95,6 +149,114 @@ impl IntoResponse for ApiError {
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct AuditVerifyQuery {
+    pub from_index: Option<usize>,
+    pub from_hash: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditTailQuery {
+    pub mode: Option<String>,
+    /// Hash of the last entry already seen; later entries are replayed first.
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InclusionQuery {
+    pub tree_size: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConsistencyQuery {
+    pub first: usize,
+    pub second: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditRedactRequest {
+    pub request_id: Uuid,
+    pub node_id: String,
+    pub reason: String,
+}
+
+/// A DAG node's Merkle proof with the root recorded in its audit entry. The entry hash
+/// links the root to the log's own inclusion proofs.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NodeProofResponse {
+    pub request_id: Uuid,
+    pub entry_hash: String,
+    pub merkle_root: String,
+    pub proof: NodeProof,
+}
+
+/// An inclusion proof with the signed tree head it verifies against.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InclusionResponse {
+    pub proof: InclusionProof,
+    pub tree_head: SignedTreeHead,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConsistencyResponse {
+    pub proof: ConsistencyProof,
+    pub first: SignedTreeHead,
+    pub second: SignedTreeHead,
+}
+
+/// API key header that authenticates callers and selects their policy profile.
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+fn authenticate(policy: &SafetyPolicy, headers: &HeaderMap) -> Result<Caller, ApiError> {
+    let api_key = headers.get(API_KEY_HEADER).and_then(|v| v.to_str().ok());
+    policy.authenticate(api_key).map_err(policy_error_to_api)
+}
+
+/// Field names of a request body at any depth, for the identity gate.
+fn field_names(fields: &BTreeMap<String, Value>) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut pending: Vec<&Value> = Vec::new();
+    for (name, value) in fields {
+        names.push(name.clone());
+        pending.push(value);
+    }
+    while let Some(value) = pending.pop() {
+        match value {
+            Value::Object(map) => {
+                for (name, value) in map {
+                    names.push(name.clone());
+                    pending.push(value);
+                }
+            }
+            Value::Array(items) => pending.extend(items),
+            _ => {}
+        }
+    }
+    names
+}
+
+/// Header carrying the admin token.
+pub const ADMIN_TOKEN_HEADER: &str = "x-admin-token";
+
+fn authorize_admin(app: &AppState, headers: &HeaderMap) -> Result<(), ApiError> {
+    let expected = app
+        .admin_token
+        .as_deref()
+        .ok_or_else(|| ApiError::new(StatusCode::FORBIDDEN, "admin API disabled"))?;
+    let presented = headers
+        .get(ADMIN_TOKEN_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    // blake3::Hash equality is constant-time.
+    if blake3::hash(presented.as_bytes()) != blake3::hash(expected.as_bytes()) {
+        return Err(ApiError::new(
+            StatusCode::UNAUTHORIZED,
+            "missing or invalid admin token",
+        ));
+    }
+    Ok(())
+}
+
 fn env_flag(key: &str, default: bool) -> bool {
     std::env::var(key)
         .ok()
@@ -102,24 +264,81 @@ fn env_flag(key: &str, default: bool) -> bool {
         .unwrap_or(default)
 }
 
+fn env_u64(key: &str) -> Option<u64> {
+    std::env::var(key).ok().and_then(|v| v.parse().ok())
+}
+
 pub fn build_router(state: AppState) -> Router {
     Router::new()
         .route("/api/v1/creative", post(handle_creative))
         .route("/api/v1/verified", post(handle_verified))
+        .route("/api/v1/policy/explain", post(handle_policy_explain))
+        .route("/api/v1/node/key", get(handle_node_key))
+        .route("/api/v1/audit/tree-head", get(handle_tree_head))
+        .route(
+            "/api/v1/audit/proof/inclusion/:request_id",
+            get(handle_inclusion_proof),
+        )
+        .route(
+            "/api/v1/audit/proof/node/:request_id/:node_id",
+            get(handle_node_proof),
+        )
+        .route(
+            "/api/v1/audit/proof/consistency",
+            get(handle_consistency_proof),
+        )
+        .route("/api/v1/admin/audit/verify", get(handle_audit_verify))
+        .route("/api/v1/admin/audit/redact", post(handle_audit_redact))
+        .route("/api/v1/admin/audit/tail", get(handle_audit_tail))
+        .route(
+            "/api/v1/admin/audit/credential/:request_id",
+            get(handle_audit_credential),
+        )
+        .route("/api/v1/admin/audit/node/:node_id", get(handle_audit_node))
         .with_state(state)
 }
 
 pub fn build_state() -> anyhow::Result<AppState> {
-    let policy = Arc::new(SafetyPolicy::load_from_disk("policy/safety_config.json")?);
+    let policy_path = std::env::var("AXIOMHIVE_POLICY_PATH")
+        .unwrap_or_else(|_| "policy/safety_config.json".into());
+    let policy = Arc::new(SafetyPolicy::load(
+        policy_path,
+        &PolicyLoadOptions::from_env(),
+    )?);
     let require_payment = env_flag("AXIOMHIVE_REQUIRE_PAYMENT", true);
 
     let model = Arc::new(HybridModelEngine::default());
     let verifier = Arc::new(DeterministicVerifier::new(model.clone()));
     let payment = Arc::new(LightningGateway::new("axiomhive-edge"));
     let dag = Arc::new(DagScheduler::default());
-    let audit_path =
-        std::env::var("AXIOMHIVE_AUDIT_PATH").unwrap_or_else(|_| "data/audit.jsonl".into());
-    let audit = Arc::new(AuditLog::new(audit_path));
+    let audit_backend = AuditBackend::parse(
+        &std::env::var("AXIOMHIVE_AUDIT_BACKEND").unwrap_or_else(|_| "jsonl".into()),
+    )?;
+    let audit_path = std::env::var("AXIOMHIVE_AUDIT_PATH")
+        .unwrap_or_else(|_| audit_backend.default_path().into());
+    let node_key_path =
+        std::env::var("AXIOMHIVE_NODE_KEY_PATH").unwrap_or_else(|_| "data/node.key".into());
+    let node_key = Arc::new(NodeKey::load_or_create(node_key_path)?);
+    let rotation = AuditRotation {
+        max_bytes: env_u64("AXIOMHIVE_AUDIT_SEGMENT_BYTES"),
+        max_age: env_u64("AXIOMHIVE_AUDIT_SEGMENT_SECS").map(Duration::from_secs),
+        compress: env_flag("AXIOMHIVE_AUDIT_COMPRESS", false),
+        retain_segments: env_u64("AXIOMHIVE_AUDIT_RETAIN_SEGMENTS").map(|n| n as usize),
+        retain_age: env_u64("AXIOMHIVE_AUDIT_RETAIN_SECS").map(Duration::from_secs),
+    };
+    let mut audit =
+        AuditLog::new(audit_backend.open(audit_path, rotation)?).with_node_key(node_key.clone());
+    if let Some(keyring) = AuditKeyring::from_env()? {
+        audit = audit.with_keyring(Arc::new(keyring));
+    }
+    let audit = Arc::new(audit);
+    let egress = Arc::new(
+        EgressGate::new(policy.node_allows_network())
+            .with_audit(audit.clone(), policy.provenance()),
+    );
+    let axiom_cache =
+        std::env::var("AXIOMHIVE_AXIOM_CACHE").unwrap_or_else(|_| "data/axioms".into());
+    let axioms = Arc::new(AxiomResolver::new(axiom_cache, egress.clone()));
 
     Ok(AppState {
         model,
@@ -128,119 +347,618 @@ pub fn build_state() -> anyhow::Result<AppState> {
         payment,
         dag,
         audit,
+        node_key,
+        egress,
+        axioms,
         require_payment,
+        admin_token: std::env::var("AXIOMHIVE_ADMIN_TOKEN")
+            .ok()
+            .filter(|token| !token.is_empty()),
     })
 }
 
-pub async fn handle_creative(
-    State(app): State<AppState>,
-    Json(body): Json<CreativeRequest>,
-) -> Result<Json<CreativeResponse>, ApiError> {
-    if let Err(err) = app.policy.check_prompt(&body.prompt) {
-        return Err(policy_error_to_api(err));
+/// Per-attempt limit on generation and verification; a failed attempt is retried once.
+const MODEL_TIMEOUT: Duration = Duration::from_secs(120);
+/// Per-attempt limit on invoice validation.
+const PAYMENT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// State shared by the tasks of one request's pipeline.
+struct Pipeline<B> {
+    app: AppState,
+    caller: Caller,
+    body: B,
+}
+
+impl<B> Pipeline<B> {
+    fn policy(&self) -> &SafetyPolicy {
+        self.app.policy.for_caller(&self.caller)
     }
+}
 
-    let temperature = body.temperature.unwrap_or(0.9);
-    let top_k = body.top_k.unwrap_or(64);
-    let media = body.media.unwrap_or_default();
+/// A pipeline task: `step` is called with the request's state and the node's inputs.
+fn step<B, F, Fut>(pipeline: &Arc<Pipeline<B>>, step: F) -> impl DagTask<StepError>
+where
+    B: Send + Sync + 'static,
+    F: Fn(Arc<Pipeline<B>>, TaskInputs) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<TaskOutput, StepError>> + Send + 'static,
+{
+    let pipeline = pipeline.clone();
+    move |inputs| step(pipeline.clone(), inputs)
+}
 
-    app.payment
-        .validate_invoice(
-            body.lightning_invoice.as_deref(),
-            app.require_payment,
-            false,
+fn input<'a, T: Any>(inputs: &'a TaskInputs, node_type: &str) -> Result<&'a T, ApiError> {
+    inputs
+        .get(node_type)
+        .ok_or_else(|| internal_error(format!("{node_type} output missing")))
+}
+
+fn run_output<'a, T: Any>(run: &'a DagRun, node_type: &str) -> Result<&'a T, ApiError> {
+    run.value(node_type)
+        .ok_or_else(|| internal_error(format!("{node_type} output missing")))
+}
+
+/// Error of a pipeline step, with the payload its node records in the audit log.
+#[derive(Debug)]
+struct StepError {
+    error: ApiError,
+    record: Value,
+}
+
+impl StepError {
+    fn recording(error: ApiError, record: Value) -> Self {
+        Self { error, record }
+    }
+}
+
+/// Upstream failures (502, 503, 504) may pass on a second try; anything else a step
+/// returns would fail the same way again.
+impl TaskError for StepError {
+    fn is_transient(&self) -> bool {
+        matches!(
+            self.error.status,
+            StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
         )
+    }
+}
+
+impl From<ApiError> for StepError {
+    fn from(error: ApiError) -> Self {
+        let record = error_record(&error);
+        Self { error, record }
+    }
+}
+
+fn error_record(error: &ApiError) -> Value {
+    json!({ "error": error.message, "status": error.status.as_u16() })
+}
+
+/// Runs a request's pipeline. A failed run is audited before its error is returned: the
+/// nodes that completed, then the failed node with what it recorded, so denials are on
+/// the record too.
+async fn run_pipeline(
+    app: &AppState,
+    policy: &SafetyPolicy,
+    request_id: Uuid,
+    mode: &str,
+    graph: TaskGraph<StepError>,
+) -> Result<DagRun, ApiError> {
+    let DagRunError {
+        node,
+        attempts,
+        failure,
+        mut dag,
+        parents,
+    } = match app.dag.execute(graph).await {
+        Ok(run) => return Ok(run),
+        Err(err) => err,
+    };
+    let (error, record) = match failure {
+        TaskFailure::Failed(step) => (step.error, step.record),
+        TaskFailure::TimedOut(limit) => {
+            let error = ApiError::new(
+                StatusCode::GATEWAY_TIMEOUT,
+                format!(
+                    "{node} timed out after {}s ({attempts} attempts)",
+                    limit.as_secs()
+                ),
+            );
+            let record = error_record(&error);
+            (error, record)
+        }
+        TaskFailure::Panicked => {
+            let error = internal_error(format!("{node} panicked"));
+            let record = error_record(&error);
+            (error, record)
+        }
+    };
+    let parents: Vec<&str> = parents.iter().map(String::as_str).collect();
+    dag.add_node(&node, record, &parents)
+        .map_err(internal_error)?;
+    append_audit(app, policy, request_id, mode, &dag)?;
+    Err(error)
+}
+
+fn identity_task(
+    policy: &SafetyPolicy,
+    prompt: &str,
+    fields: &BTreeMap<String, Value>,
+    allow_transmute: bool,
+) -> Result<TaskOutput, StepError> {
+    let identity = policy.check_identity(prompt, &field_names(fields));
+    // A deny or escalate decision is recorded with the verdict, like the others.
+    let transmuted = identity
+        .enforce(allow_transmute)
+        .map_err(|err| StepError::recording(policy_error_to_api(err), json!(identity)))?
+        .map(str::to_string);
+    Ok(match transmuted {
+        Some(response) => TaskOutput::new(json!(identity), response).halt(),
+        None => TaskOutput::record(json!(identity)),
+    })
+}
+
+fn pii_task(policy: &SafetyPolicy, prompt: &str) -> Result<TaskOutput, StepError> {
+    let scrubbed = policy.scrub_pii(prompt).map_err(policy_error_to_api)?;
+    Ok(TaskOutput::new(
+        json!({
+            "detected": scrubbed.report.detected,
+            "actions": scrubbed.report.actions,
+        }),
+        scrubbed,
+    ))
+}
+
+/// Checks the scrubbed prompt as `pipeline`'s caller.
+fn policy_task<B>(pipeline: &Pipeline<B>, request: PolicyRequest) -> Result<TaskOutput, StepError> {
+    let policy = pipeline.policy();
+    let decision = policy
+        .ensure_media_allowed(request.media)
+        .and_then(|()| {
+            policy.check_prompt(PolicyRequest {
+                caller: pipeline.caller.client.as_deref(),
+                ..request
+            })
+        })
+        .map_err(|err| {
+            let error = policy_error_to_api(err);
+            let record = json!({
+                "prompt_len": request.prompt.len(),
+                "mode": request.mode,
+                "profile": pipeline.caller.profile,
+                "error": error.message,
+                "status": error.status.as_u16(),
+            });
+            StepError::recording(error, record)
+        })?;
+    Ok(TaskOutput::new(
+        json!({
+            "prompt_len": request.prompt.len(),
+            "mode": request.mode,
+            "action": decision.action,
+            "risk": decision.risk,
+            "rule": decision.rule_id,
+            "profile": pipeline.caller.profile,
+            "price_sats": pipeline.app.payment.quote_sats(policy.price_multiplier()),
+        }),
+        decision,
+    ))
+}
+
+/// Halts the run when triage asks for clarification.
+fn triage_task(triage: TriageReport) -> TaskOutput {
+    let ask = triage.outcome == TriageOutcome::Ask;
+    let output = TaskOutput::new(json!(triage), triage);
+    if ask {
+        output.halt()
+    } else {
+        output
+    }
+}
+
+async fn payment_task(
+    app: &AppState,
+    invoice: Option<&str>,
+    decision: &SafetyDecision,
+    free_local: bool,
+) -> Result<TaskOutput, StepError> {
+    let required = app.require_payment || decision.requires_payment;
+    let free_local = free_local && !decision.requires_payment;
+    app.payment
+        .validate_invoice(invoice, required, free_local)
         .await
         .map_err(payment_error_to_api)?;
+    Ok(TaskOutput::record(json!({
+        "required": required,
+        "invoice": invoice.is_some(),
+        "free_local": free_local,
+    })))
+}
 
-    let output = app
-        .model
-        .generate_creative(&body.prompt, &media, temperature, top_k)
+fn output_task(policy: &SafetyPolicy, output: &str, mode: &str) -> TaskOutput {
+    let screened = policy.check_output(output);
+    TaskOutput::new(
+        json!({
+            "action": screened.action,
+            "matched_terms": screened.matched_terms,
+            "redactions": screened.redactions,
+            "mode": mode,
+        }),
+        screened,
+    )
+}
+
+fn model_policy() -> TaskPolicy {
+    TaskPolicy::timeout(MODEL_TIMEOUT).with_retries(1, Duration::from_millis(250))
+}
+
+/// Runs a model call on the blocking pool. The engines compute synchronously, so awaiting
+/// them in place would hold a worker and leave the step's timeout unable to fire. A call
+/// that times out finishes in the background and its result is dropped.
+async fn run_blocking<T, F, Fut>(call: F) -> Result<T, ApiError>
+where
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = T>,
+    T: Send + 'static,
+{
+    let handle = tokio::runtime::Handle::current();
+    tokio::task::spawn_blocking(move || handle.block_on(call()))
         .await
-        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        .map_err(internal_error)
+}
 
+/// Logs the checkpoint root of an executed DAG.
+fn record_checkpoint(app: &AppState, request_id: Uuid, dag: &RequestDag) {
+    match app.dag.record_checkpoint(dag) {
+        Some(root) => debug!(%request_id, %root, "recorded DAG checkpoint"),
+        None => warn!(%request_id, "executed DAG is empty; no checkpoint recorded"),
+    }
+}
+
+pub async fn handle_creative(
+    State(app): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<CreativeRequest>,
+) -> Result<Response, ApiError> {
     let request_id = Uuid::new_v4();
-    let mut dag = RequestDag::default();
-    dag.add_node(
-        "policy_check",
-        json!({"prompt_len": body.prompt.len(), "mode": "creative"}),
-    );
-    dag.add_node(
-        "model_run",
-        json!({"temperature": temperature, "top_k": top_k, "media": media}),
-    );
+    let caller = authenticate(&app.policy, &headers)?;
+    let pipeline = Arc::new(Pipeline {
+        app: app.clone(),
+        caller,
+        body,
+    });
+    let mut graph = TaskGraph::default();
+    let identity_gate = graph
+        .add_task(
+            "identity_gate",
+            &[],
+            TaskPolicy::default(),
+            step(&pipeline, |p, _| async move {
+                identity_task(p.policy(), &p.body.prompt, &p.body.extra, true)
+            }),
+        )
+        .map_err(internal_error)?;
+    let pii_scan = graph
+        .add_task(
+            "pii_scan",
+            &[identity_gate],
+            TaskPolicy::default(),
+            step(&pipeline, |p, _| async move {
+                pii_task(p.policy(), &p.body.prompt)
+            }),
+        )
+        .map_err(internal_error)?;
+    let policy_check = graph
+        .add_task(
+            "policy_check",
+            &[pii_scan],
+            TaskPolicy::default(),
+            step(&pipeline, |p, inputs| async move {
+                let scrubbed = input::<PiiScrub>(&inputs, "pii_scan")?;
+                policy_task(
+                    &p,
+                    PolicyRequest {
+                        prompt: &scrubbed.text,
+                        mode: "creative",
+                        axiom_set: None,
+                        media: p.body.media.as_deref().unwrap_or_default(),
+                        ..PolicyRequest::default()
+                    },
+                )
+            }),
+        )
+        .map_err(internal_error)?;
+    let triage = graph
+        .add_task(
+            "triage",
+            &[pii_scan, policy_check],
+            TaskPolicy::default(),
+            step(&pipeline, |p, inputs| async move {
+                let scrubbed = input::<PiiScrub>(&inputs, "pii_scan")?;
+                Ok(triage_task(p.policy().triage(&scrubbed.text)))
+            }),
+        )
+        .map_err(internal_error)?;
+    let payment = graph
+        .add_task(
+            "payment",
+            &[policy_check, triage],
+            TaskPolicy::timeout(PAYMENT_TIMEOUT),
+            step(&pipeline, |p, inputs| async move {
+                let decision = input::<SafetyDecision>(&inputs, "policy_check")?;
+                payment_task(&p.app, p.body.lightning_invoice.as_deref(), decision, false).await
+            }),
+        )
+        .map_err(internal_error)?;
+    let model_run = graph
+        .add_task(
+            "model_run",
+            &[pii_scan, policy_check, payment],
+            model_policy(),
+            step(&pipeline, |p, inputs| async move {
+                let scrubbed = input::<PiiScrub>(&inputs, "pii_scan")?;
+                let media = p.body.media.clone().unwrap_or_default();
+                let temperature = p.body.temperature.unwrap_or(0.9);
+                let top_k = p.body.top_k.unwrap_or(64);
+                let (model, prompt, call_media) =
+                    (p.app.model.clone(), scrubbed.text.clone(), media.clone());
+                let output = run_blocking(move || async move {
+                    model
+                        .generate_creative(&prompt, &call_media, temperature, top_k)
+                        .await
+                })
+                .await?
+                .map_err(internal_error)?;
+                Ok(TaskOutput::new(
+                    json!({"temperature": temperature, "top_k": top_k, "media": media}),
+                    output,
+                ))
+            }),
+        )
+        .map_err(internal_error)?;
+    graph
+        .add_task(
+            "output_check",
+            &[model_run],
+            TaskPolicy::default(),
+            step(&pipeline, |p, inputs| async move {
+                let output = input::<String>(&inputs, "model_run")?;
+                Ok(output_task(p.policy(), output, "creative"))
+            }),
+        )
+        .map_err(internal_error)?;
 
-    let _merkle_root = app
-        .dag
-        .record_checkpoint(&dag)
-        .unwrap_or_else(|| "empty-dag".to_string());
-    app.audit
-        .append(request_id, "creative", &dag)
-        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let policy = pipeline.policy();
+    let run = run_pipeline(&app, policy, request_id, "creative", graph).await?;
+    if run.halted.is_some() {
+        append_audit(&app, policy, request_id, "creative", &run.dag)?;
+        // Only a transmuting identity gate leaves a response behind.
+        if let Some(response) = run.value::<String>("identity_gate") {
+            return Ok(Json(CreativeResponse {
+                request_id,
+                output: response.clone(),
+                mode: "creative".into(),
+            })
+            .into_response());
+        }
+        let triage = run_output::<TriageReport>(&run, "triage")?.clone();
+        return Ok(clarification(policy, request_id, triage));
+    }
+
+    record_checkpoint(&app, request_id, &run.dag);
+    append_audit(&app, policy, request_id, "creative", &run.dag)?;
+    let screened = run_output::<OutputVerdict>(&run, "output_check")?.clone();
+    let output = run_output::<PiiScrub>(&run, "pii_scan")?
+        .vault
+        .restore(&screened.release().map_err(policy_error_to_api)?);
 
     Ok(Json(CreativeResponse {
         request_id,
         output,
         mode: "creative".into(),
-    }))
+    })
+    .into_response())
 }
 
 pub async fn handle_verified(
     State(app): State<AppState>,
+    headers: HeaderMap,
     Json(body): Json<VerifiedRequest>,
-) -> Result<Json<VerifiedResponse>, ApiError> {
-    app.policy
-        .check_prompt(&body.prompt)
-        .map_err(policy_error_to_api)?;
-    app.policy
-        .ensure_verified_enabled()
-        .map_err(policy_error_to_api)?;
-    let allow_network = body
-        .allow_network
-        .unwrap_or_else(|| app.policy.allow_network_by_default());
-    app.policy
-        .ensure_network_allowed(allow_network)
-        .map_err(policy_error_to_api)?;
-
-    let free_local = body.free_local.unwrap_or(false);
-    app.payment
-        .validate_invoice(
-            body.lightning_invoice.as_deref(),
-            app.require_payment,
-            free_local,
+) -> Result<Response, ApiError> {
+    let request_id = Uuid::new_v4();
+    let caller = authenticate(&app.policy, &headers)?;
+    let pipeline = Arc::new(Pipeline {
+        app: app.clone(),
+        caller,
+        body,
+    });
+    let mut graph = TaskGraph::default();
+    let identity_gate = graph
+        .add_task(
+            "identity_gate",
+            &[],
+            TaskPolicy::default(),
+            step(&pipeline, |p, _| async move {
+                identity_task(p.policy(), &p.body.prompt, &p.body.extra, false)
+            }),
         )
-        .await
-        .map_err(payment_error_to_api)?;
-
-    let axiom_set = parse_axiom_set(&body.axiom_set).map_err(verification_error_to_api)?;
-    let max_steps = body.max_steps.unwrap_or(1024);
-
-    let (output, c0_signature) = app
-        .verifier
-        .verify(&body.prompt, &axiom_set, max_steps)
-        .await
-        .map_err(verification_error_to_api)?;
+        .map_err(internal_error)?;
+    // The axiom fetch and the PII scan are independent and run side by side.
+    let axiom_fetch = graph
+        .add_task(
+            "axiom_fetch",
+            &[identity_gate],
+            TaskPolicy::default(),
+            step(&pipeline, |p, _| async move {
+                let allow_network = p
+                    .body
+                    .allow_network
+                    .unwrap_or_else(|| p.policy().allow_network_by_default());
+                // Network the profile keeps off is refused at the egress gate, which
+                // audits the attempt.
+                if p.policy().ensure_network_allowed(allow_network).is_err() {
+                    let spec = p.body.axiom_set.trim();
+                    let target = if spec.contains("://") { spec } else { "inline" };
+                    p.app
+                        .egress
+                        .authorize(EgressKind::AxiomFetch, target, false)
+                        .await
+                        .map_err(|err| axiom_source_error_to_api(err.into()))?;
+                }
+                let resolved = p
+                    .app
+                    .axioms
+                    .resolve(
+                        &p.body.axiom_set,
+                        p.body.axiom_hash.as_deref(),
+                        allow_network,
+                    )
+                    .await
+                    .map_err(axiom_source_error_to_api)?;
+                Ok(TaskOutput::new(
+                    json!({
+                        "origin": resolved.origin,
+                        "url": resolved.url,
+                        "hash": resolved.hash,
+                        "allow_network": allow_network,
+                    }),
+                    resolved,
+                ))
+            }),
+        )
+        .map_err(internal_error)?;
+    let pii_scan = graph
+        .add_task(
+            "pii_scan",
+            &[identity_gate],
+            TaskPolicy::default(),
+            step(&pipeline, |p, _| async move {
+                pii_task(p.policy(), &p.body.prompt)
+            }),
+        )
+        .map_err(internal_error)?;
+    let policy_check = graph
+        .add_task(
+            "policy_check",
+            &[pii_scan, axiom_fetch],
+            TaskPolicy::default(),
+            step(&pipeline, |p, inputs| async move {
+                let scrubbed = input::<PiiScrub>(&inputs, "pii_scan")?;
+                let resolved = input::<ResolvedAxioms>(&inputs, "axiom_fetch")?;
+                let output = policy_task(
+                    &p,
+                    PolicyRequest {
+                        prompt: &scrubbed.text,
+                        mode: "verified",
+                        axiom_set: Some(&resolved.set.name),
+                        ..PolicyRequest::default()
+                    },
+                )?;
+                p.policy()
+                    .ensure_verified_enabled()
+                    .map_err(policy_error_to_api)?;
+                Ok(output)
+            }),
+        )
+        .map_err(internal_error)?;
+    let triage = graph
+        .add_task(
+            "triage",
+            &[pii_scan, policy_check],
+            TaskPolicy::default(),
+            step(&pipeline, |p, inputs| async move {
+                let scrubbed = input::<PiiScrub>(&inputs, "pii_scan")?;
+                Ok(triage_task(p.policy().triage(&scrubbed.text)))
+            }),
+        )
+        .map_err(internal_error)?;
+    let payment = graph
+        .add_task(
+            "payment",
+            &[policy_check, triage],
+            TaskPolicy::timeout(PAYMENT_TIMEOUT),
+            step(&pipeline, |p, inputs| async move {
+                let decision = input::<SafetyDecision>(&inputs, "policy_check")?;
+                let free_local = p.body.free_local.unwrap_or(false);
+                payment_task(
+                    &p.app,
+                    p.body.lightning_invoice.as_deref(),
+                    decision,
+                    free_local,
+                )
+                .await
+            }),
+        )
+        .map_err(internal_error)?;
+    let model_run = graph
+        .add_task(
+            "model_run",
+            &[pii_scan, axiom_fetch, policy_check, payment],
+            model_policy(),
+            step(&pipeline, |p, inputs| async move {
+                let scrubbed = input::<PiiScrub>(&inputs, "pii_scan")?;
+                let axiom_set = input::<ResolvedAxioms>(&inputs, "axiom_fetch")?.set.clone();
+                let max_steps = p.body.max_steps.unwrap_or(1024);
+                let (verifier, prompt, call_set) = (
+                    p.app.verifier.clone(),
+                    scrubbed.text.clone(),
+                    axiom_set.clone(),
+                );
+                let verified = run_blocking(move || async move {
+                    verifier.verify(&prompt, &call_set, max_steps).await
+                })
+                .await?
+                .map_err(verification_error_to_api)?;
+                Ok(TaskOutput::new(
+                    json!({"max_steps": max_steps, "axiom_set": axiom_set.name}),
+                    verified,
+                ))
+            }),
+        )
+        .map_err(internal_error)?;
+    graph
+        .add_task(
+            "verification",
+            &[model_run, axiom_fetch],
+            TaskPolicy::default(),
+            step(&pipeline, |p, inputs| async move {
+                let (_, c0_signature) = input::<(String, C0Signature)>(&inputs, "model_run")?;
+                let axiom_set = &input::<ResolvedAxioms>(&inputs, "axiom_fetch")?.set;
+                Ok(TaskOutput::record(json!({
+                    "rules": axiom_set.rules.len(),
+                    "free_local": p.body.free_local.unwrap_or(false),
+                    "c0_signature": c0_signature,
+                })))
+            }),
+        )
+        .map_err(internal_error)?;
+    graph
+        .add_task(
+            "output_check",
+            &[model_run],
+            TaskPolicy::default(),
+            step(&pipeline, |p, inputs| async move {
+                let (output, _) = input::<(String, C0Signature)>(&inputs, "model_run")?;
+                Ok(output_task(p.policy(), output, "verified"))
+            }),
+        )
+        .map_err(internal_error)?;
 
-    let request_id = Uuid::new_v4();
-    let mut dag = RequestDag::default();
-    dag.add_node(
-        "policy_check",
-        json!({"prompt_len": body.prompt.len(), "mode": "verified"}),
-    );
-    dag.add_node(
-        "model_run",
-        json!({"max_steps": max_steps, "axiom_set": axiom_set.name}),
-    );
-    dag.add_node(
-        "verification",
-        json!({"rules": axiom_set.rules.len(), "free_local": free_local}),
-    );
+    let policy = pipeline.policy();
+    let run = run_pipeline(&app, policy, request_id, "verified", graph).await?;
+    if run.halted.is_some() {
+        append_audit(&app, policy, request_id, "verified", &run.dag)?;
+        let triage = run_output::<TriageReport>(&run, "triage")?.clone();
+        return Ok(clarification(policy, request_id, triage));
+    }
 
-    let _ = app.dag.record_checkpoint(&dag);
-    let audit_entry = app
-        .audit
-        .append(request_id, "verified", &dag)
-        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    record_checkpoint(&app, request_id, &run.dag);
+    let audit_entry = append_audit(&app, policy, request_id, "verified", &run.dag)?;
+    let (_, c0_signature) = run_output::<(String, C0Signature)>(&run, "model_run")?.clone();
+    // The C0 signature commits to the exact output, so verified mode cannot redact in place
+    // and PII tokens are not restored.
+    let output = run_output::<OutputVerdict>(&run, "output_check")?
+        .clone()
+        .release_unaltered()
+        .map_err(policy_error_to_api)?;
 
     Ok(Json(VerifiedResponse {
         request_id,
@@ -248,12 +966,313 @@ pub async fn handle_verified(
         c0_signature,
         proof_uri: format!("zkml://proofs/{request_id}"),
         merkle_root: audit_entry.merkle_root,
+    })
+    .into_response())
+}
+
+fn append_audit(
+    app: &AppState,
+    policy: &SafetyPolicy,
+    request_id: Uuid,
+    mode: &str,
+    dag: &RequestDag,
+) -> Result<AuditEntry, ApiError> {
+    app.audit
+        .append(request_id, mode, dag, &policy.provenance())
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+fn clarification(policy: &SafetyPolicy, request_id: Uuid, triage: TriageReport) -> Response {
+    Json(ClarificationResponse {
+        request_id,
+        status: "ask".into(),
+        clarify: policy.clarification().to_string(),
+        triage,
+    })
+    .into_response()
+}
+
+/// Runs the policy engine on a prompt without generating, for tuning blocklists and rules.
+pub async fn handle_policy_explain(
+    State(app): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<PolicyExplainRequest>,
+) -> Result<Json<PolicyExplanation>, ApiError> {
+    let caller = authenticate(&app.policy, &headers)?;
+    let axiom_set = body
+        .axiom_set
+        .as_deref()
+        .map(parse_axiom_set)
+        .transpose()
+        .map_err(verification_error_to_api)?;
+    let media = body.media.unwrap_or_default();
+
+    Ok(Json(app.policy.for_caller(&caller).explain(
+        &PolicyRequest {
+            prompt: &body.prompt,
+            mode: body.mode.as_deref().unwrap_or("creative"),
+            axiom_set: axiom_set.as_ref().map(|set| set.name.as_str()),
+            media: &media,
+            caller: caller.client.as_deref(),
+        },
+    )))
+}
+
+/// Publishes the node's audit signing key.
+pub async fn handle_node_key(State(app): State<AppState>) -> Json<PublishedKey> {
+    Json(app.node_key.published())
+}
+
+/// Signed Merkle tree head over every audit entry hash.
+pub async fn handle_tree_head(
+    State(app): State<AppState>,
+) -> Result<Json<SignedTreeHead>, ApiError> {
+    app.audit.tree_head().map(Json).map_err(internal_error)
+}
+
+/// Proves one request's audit entry is in the log without handing out the log.
+pub async fn handle_inclusion_proof(
+    State(app): State<AppState>,
+    Path(request_id): Path<Uuid>,
+    Query(query): Query<InclusionQuery>,
+) -> Result<Json<InclusionResponse>, ApiError> {
+    let proof = app
+        .audit
+        .inclusion_proof(request_id, query.tree_size)
+        .map_err(internal_error)?
+        .ok_or_else(|| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("request {request_id} is not in the audit tree at that size"),
+            )
+        })?;
+    let tree_head = app
+        .audit
+        .tree_head_at(Some(proof.tree_size))
+        .map_err(internal_error)?
+        .ok_or_else(|| internal_error("audit tree shrank"))?;
+    Ok(Json(InclusionResponse { proof, tree_head }))
+}
+
+/// Proves one DAG node is under the `merkle_root` of its request's audit entry.
+pub async fn handle_node_proof(
+    State(app): State<AppState>,
+    Path((request_id, node_id)): Path<(Uuid, String)>,
+) -> Result<Json<NodeProofResponse>, ApiError> {
+    let entry = app
+        .audit
+        .get(request_id)
+        .map_err(internal_error)?
+        .ok_or_else(|| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("request {request_id} is not in the audit log"),
+            )
+        })?;
+    let proof = entry
+        .node_proof(&node_id)
+        .map_err(|err| ApiError::new(StatusCode::BAD_REQUEST, err.to_string()))?
+        .ok_or_else(|| {
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("request {request_id} has no node {node_id}"),
+            )
+        })?;
+    Ok(Json(NodeProofResponse {
+        request_id,
+        entry_hash: entry.hash,
+        merkle_root: entry.merkle_root,
+        proof,
     }))
 }
 
+/// Proves the tree of `second` entries is an append-only extension of the tree of `first`.
+pub async fn handle_consistency_proof(
+    State(app): State<AppState>,
+    Query(query): Query<ConsistencyQuery>,
+) -> Result<Json<ConsistencyResponse>, ApiError> {
+    let out_of_range = || {
+        ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "first must not exceed second, and second must not exceed the tree size",
+        )
+    };
+    let proof = app
+        .audit
+        .consistency_proof(query.first, query.second)
+        .map_err(internal_error)?
+        .ok_or_else(out_of_range)?;
+    let head = |size| {
+        app.audit
+            .tree_head_at(Some(size))
+            .map_err(internal_error)?
+            .ok_or_else(out_of_range)
+    };
+    Ok(Json(ConsistencyResponse {
+        first: head(query.first)?,
+        second: head(query.second)?,
+        proof,
+    }))
+}
+
+fn internal_error(err: impl ToString) -> ApiError {
+    ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+}
+
+/// Walks the audit log and reports broken links, reordering and Merkle mismatches.
+pub async fn handle_audit_verify(
+    State(app): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<AuditVerifyQuery>,
+) -> Result<Json<AuditReport>, ApiError> {
+    authorize_admin(&app, &headers)?;
+    let from = match (query.from_index, query.from_hash) {
+        (Some(index), Some(hash)) => Some(AuditCheckpoint { index, hash }),
+        (None, None) => None,
+        _ => {
+            return Err(ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "from_index and from_hash must be given together",
+            ))
+        }
+    };
+    let audit = app.audit.clone();
+    let node_key = app.node_key.public_key_hex();
+    let report = tokio::task::spawn_blocking(move || audit.verify(from.as_ref(), Some(&node_key)))
+        .await
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .map_err(|e| ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(report))
+}
+
+/// Removes one DAG node payload from a logged entry, e.g. for a deletion request. The entry
+/// keeps its hash and gains a redaction record signed by the node key.
+pub async fn handle_audit_redact(
+    State(app): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<AuditRedactRequest>,
+) -> Result<Json<AuditEntry>, ApiError> {
+    authorize_admin(&app, &headers)?;
+    let audit = app.audit.clone();
+    tokio::task::spawn_blocking(move || {
+        if audit.get(req.request_id).map_err(internal_error)?.is_none() {
+            return Err(ApiError::new(
+                StatusCode::NOT_FOUND,
+                format!("request {} is not in the audit log", req.request_id),
+            ));
+        }
+        audit
+            .redact(req.request_id, &req.node_id, &req.reason)
+            .map_err(|err| ApiError::new(StatusCode::BAD_REQUEST, err.to_string()))
+    })
+    .await
+    .map_err(internal_error)?
+    .map(Json)
+}
+
+/// Exports one request's audit entry as a verifiable credential signed by the node key.
+pub async fn handle_audit_credential(
+    State(app): State<AppState>,
+    headers: HeaderMap,
+    Path(request_id): Path<Uuid>,
+) -> Result<Json<AuditCredential>, ApiError> {
+    authorize_admin(&app, &headers)?;
+    tokio::task::spawn_blocking(move || {
+        AuditCredential::export(&app.audit, request_id, &app.node_key)
+            .map_err(internal_error)?
+            .ok_or_else(|| {
+                ApiError::new(
+                    StatusCode::NOT_FOUND,
+                    format!("request {request_id} is not in the audit log"),
+                )
+            })
+    })
+    .await
+    .map_err(internal_error)?
+    .map(Json)
+}
+
+/// Looks a DAG node up by content id across every audited request.
+pub async fn handle_audit_node(
+    State(app): State<AppState>,
+    headers: HeaderMap,
+    Path(node_id): Path<String>,
+) -> Result<Json<Vec<AuditedNode>>, ApiError> {
+    authorize_admin(&app, &headers)?;
+    let nodes = tokio::task::spawn_blocking(move || app.audit.find_node(&node_id))
+        .await
+        .map_err(internal_error)?
+        .map_err(internal_error)?;
+    if nodes.is_empty() {
+        return Err(ApiError::new(
+            StatusCode::NOT_FOUND,
+            "no audited DAG has that node",
+        ));
+    }
+    Ok(Json(nodes))
+}
+
+/// Streams audit entries as server-sent events as they are committed, each with the entry
+/// hash as its event id. A reconnecting client's `Last-Event-ID`, or `after`, replays the
+/// entries it missed first.
+pub async fn handle_audit_tail(
+    State(app): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<AuditTailQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, axum::Error>>>, ApiError> {
+    authorize_admin(&app, &headers)?;
+    let after = query.after.or_else(|| {
+        headers
+            .get("last-event-id")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+    });
+    let audit = app.audit.clone();
+    let tail = tokio::task::spawn_blocking(move || audit.tail(after.as_deref(), query.mode))
+        .await
+        .map_err(internal_error)?
+        .map_err(internal_error)?
+        .ok_or_else(|| ApiError::new(StatusCode::NOT_FOUND, "no audit entry has that hash"))?;
+    let events = futures::stream::unfold(Some(tail), |tail| async move {
+        let mut tail = tail?;
+        Some(match tail.next().await {
+            Ok(event) => (
+                Event::default()
+                    .event("audit")
+                    .id(event.entry.hash.clone())
+                    .json_data(&event),
+                Some(tail),
+            ),
+            Err(err) => (
+                Ok(Event::default().event("error").data(err.to_string())),
+                None,
+            ),
+        })
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
 fn policy_error_to_api(err: PolicyError) -> ApiError {
     match err {
         PolicyError::Blocked(msg) => ApiError::new(StatusCode::FORBIDDEN, msg),
+        PolicyError::PiiBlocked(kinds) => ApiError::new(
+            StatusCode::FORBIDDEN,
+            format!("prompt contains blocked personal data: {kinds}"),
+        ),
+        PolicyError::Unauthorized => {
+            ApiError::new(StatusCode::UNAUTHORIZED, "missing or unknown API key")
+        }
+        PolicyError::IdentityBarred(detail) => ApiError::new(
+            StatusCode::FORBIDDEN,
+            format!("identity processing is barred: {detail}"),
+        ),
+        PolicyError::MediaNotAllowed(kind) => ApiError::new(
+            StatusCode::FORBIDDEN,
+            format!("media kind not allowed: {kind}"),
+        ),
+        PolicyError::OutputBlocked(msg) => {
+            ApiError::new(StatusCode::FORBIDDEN, format!("blocked output: {msg}"))
+        }
         PolicyError::VerifiedDisabled => {
             ApiError::new(StatusCode::FORBIDDEN, "verified mode disabled")
         }
@@ -261,7 +1280,31 @@ fn policy_error_to_api(err: PolicyError) -> ApiError {
             ApiError::new(StatusCode::FORBIDDEN, "network disabled for request")
         }
         PolicyError::EscalationRequired(msg) => ApiError::new(StatusCode::BAD_REQUEST, msg),
-        PolicyError::ConfigLoad(msg) => ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, msg),
+        PolicyError::ConfigLoad(msg) | PolicyError::SignatureInvalid(msg) => {
+            ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, msg)
+        }
+    }
+}
+
+fn axiom_source_error_to_api(err: AxiomSourceError) -> ApiError {
+    match err {
+        AxiomSourceError::Invalid(err) => verification_error_to_api(err),
+        AxiomSourceError::Egress(EgressError::Denied { .. }) => {
+            ApiError::new(StatusCode::FORBIDDEN, "network disabled for request")
+        }
+        AxiomSourceError::Egress(err) => ApiError::new(StatusCode::BAD_GATEWAY, err.to_string()),
+        AxiomSourceError::HashMismatch { .. } => {
+            ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, err.to_string())
+        }
+        AxiomSourceError::NotCached(_) => ApiError::new(StatusCode::NOT_FOUND, err.to_string()),
+        AxiomSourceError::Cache(_) => {
+            ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, err.to_string())
+        }
+        AxiomSourceError::InsecureUrl(_)
+        | AxiomSourceError::Unpinned(_)
+        | AxiomSourceError::MalformedHash(_) => {
+            ApiError::new(StatusCode::BAD_REQUEST, err.to_string())
+        }
     }
 }
 
@@ -1,92 +1,1379 @@
-use crate::dag::dag::{DagNode, RequestDag};
+//! Hash-chained audit log. Entries are linked and signed by `AuditLog` and persisted by an
+//! `AuditStore` backend: JSON lines in a local file (`audit_jsonl`, the default) or an
+//! embedded sled database indexed by request id (`audit_sled`).
+
+use crate::{
+    audit_crypt::{AuditKeyring, EncryptedBody},
+    audit_jsonl::JsonlStore,
+    audit_segment::{load_seals, open_segment, AuditRotation, SegmentSeal},
+    audit_sled::SledStore,
+    dag::{
+        dag::{
+            commitment_proof, commitment_root, content_id, legacy_commitment_root, DagNode,
+            NodeCommitment, NodeProof, RequestDag,
+        },
+        merkletree::{LEGACY_TREE, TREE_VERSION},
+    },
+    node_key::{verify_signature, NodeKey},
+    policy::PolicyProvenance,
+    transparency::{leaf_hash, ConsistencyProof, InclusionProof, SignedTreeHead, TreeCache},
+};
 use blake3::Hasher;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 use std::{
-    fs::{self, OpenOptions},
-    io::{BufRead, BufReader, Write},
-    path::{Path, PathBuf},
-    sync::Mutex,
+    collections::{HashMap, HashSet, VecDeque},
+    fs::File,
+    io::{BufRead, BufReader},
+    ops::Range,
+    path::Path,
+    sync::{Arc, Mutex},
 };
+use tokio::sync::broadcast;
 use uuid::Uuid;
 
+/// Hash format of new entries. Format 0 hashed DAG payloads directly; format 1 commits to
+/// each node's payload hash so payloads can be redacted; format 2 builds `merkle_root` with
+/// the domain-separated tree (`merkletree::TREE_VERSION`) in place of the legacy one;
+/// format 3 keys payload hashes with per-node salts (`AuditEntry::salt_payloads`).
+pub const ENTRY_VERSION: u32 = 3;
+
+/// Context for deriving the key that payload salts are derived from.
+const SALT_CONTEXT: &str = "axiomhive audit payload salt v1";
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuditEntry {
+    #[serde(default, skip_serializing_if = "is_legacy")]
+    pub version: u32,
     pub timestamp: String,
     pub request_id: Uuid,
     pub mode: String,
     pub merkle_root: String,
     pub dag: Vec<DagNode>,
+    #[serde(default)]
+    pub policy_hash: String,
+    #[serde(default)]
+    pub policy_signer: Option<String>,
+    #[serde(default)]
+    pub policy_profile: Option<String>,
+    /// Hex ed25519 key of the node that signed `hash`; covered by the hash.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub node_key: Option<String>,
     pub prev_hash: String,
     pub hash: String,
+    /// Node signature over `hash`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Payloads removed after the entry was written; not covered by the hash.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub redactions: Vec<Redaction>,
+    /// DAG payloads encrypted at rest; the payloads in `dag` are then null. Not covered by
+    /// the hash, which commits to `payload_hashes`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encrypted: Option<EncryptedBody>,
+}
+
+fn is_legacy(version: &u32) -> bool {
+    *version == 0
+}
+
+/// Signed record that a DAG node's payload was removed. The entry keeps committing to
+/// `payload_hash`, so its hash and Merkle root still verify.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Redaction {
+    pub node_id: String,
+    pub payload_hash: String,
+    pub reason: String,
+    pub redacted_at: String,
+    pub node_key: Option<String>,
+    pub signature: Option<String>,
+}
+
+impl Redaction {
+    fn message(&self, entry_hash: &str) -> String {
+        format!(
+            "axiomhive redaction v1:{entry_hash}:{}:{}:{}:{}",
+            self.node_id, self.payload_hash, self.reason, self.redacted_at
+        )
+    }
+
+    /// Whether the record is signed by its `node_key` for the entry with `entry_hash`.
+    pub fn signature_is_valid(&self, entry_hash: &str) -> bool {
+        match (&self.node_key, &self.signature) {
+            (Some(key), Some(signature)) => {
+                verify_signature(key, self.message(entry_hash).as_bytes(), signature)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl AuditEntry {
+    /// An entry recording `dag` that is not yet linked into a chain; `ChainLinker::link`
+    /// fills in `prev_hash`, `hash` and the signature.
+    pub fn unlinked(request_id: Uuid, mode: &str, dag: &RequestDag) -> Self {
+        Self {
+            version: ENTRY_VERSION,
+            timestamp: Utc::now().to_rfc3339(),
+            request_id,
+            mode: mode.to_string(),
+            merkle_root: dag.merkle_root().unwrap_or_else(|| "empty-dag".to_string()),
+            dag: dag.nodes.clone(),
+            policy_hash: String::new(),
+            policy_signer: None,
+            policy_profile: None,
+            node_key: None,
+            prev_hash: String::new(),
+            hash: String::new(),
+            signature: None,
+            redactions: Vec::new(),
+            encrypted: None,
+        }
+    }
+
+    /// Keys each node's payload hash with a salt derived from `key` and the payload, then
+    /// re-derives node ids and `merkle_root` from the salted hashes. Without the salt, which
+    /// is removed with a redacted payload and sealed with an encrypted one, a hidden payload
+    /// cannot be found by hashing guesses. Equal payloads get equal salts under one key, so
+    /// equal nodes still share ids. Call before the entry is linked.
+    pub fn salt_payloads(&mut self, key: &[u8; 32]) {
+        let mut ids: HashMap<String, String> = HashMap::new();
+        for node in &mut self.dag {
+            let payload = serde_json::to_vec(&node.payload).unwrap_or_default();
+            node.salt = Some(blake3::keyed_hash(key, &payload).to_hex().to_string());
+            for parent in &mut node.parents {
+                if let Some(id) = ids.get(parent) {
+                    *parent = id.clone();
+                }
+            }
+            let id = content_id(&node.node_type, &node.payload_hash(), &node.parents);
+            ids.insert(std::mem::replace(&mut node.id, id.clone()), id);
+        }
+        self.merkle_root = self.computed_merkle_root();
+    }
+
+    /// What each DAG node commits to. A redacted node's payload hash comes from its
+    /// redaction record, an encrypted entry's from its encrypted body.
+    pub fn commitments(&self) -> Vec<NodeCommitment> {
+        if let Some(body) = &self.encrypted {
+            return self
+                .dag
+                .iter()
+                .enumerate()
+                .map(|(i, node)| NodeCommitment {
+                    payload_hash: body.payload_hashes.get(i).cloned().unwrap_or_default(),
+                    ..node.commitment()
+                })
+                .collect();
+        }
+        self.dag
+            .iter()
+            .map(|node| {
+                let redacted = self
+                    .redactions
+                    .iter()
+                    .find(|redaction| redaction.node_id == node.id && node.payload.is_null());
+                match redacted {
+                    Some(redaction) => NodeCommitment {
+                        payload_hash: redaction.payload_hash.clone(),
+                        ..node.commitment()
+                    },
+                    None => node.commitment(),
+                }
+            })
+            .collect()
+    }
+
+    /// Merkle root recomputed from the DAG, under the entry's hash format.
+    pub fn computed_merkle_root(&self) -> String {
+        let root = match self.version {
+            0 => RequestDag {
+                nodes: self.dag.clone(),
+            }
+            .legacy_merkle_root(),
+            1 => legacy_commitment_root(&self.commitments()),
+            _ => commitment_root(&self.commitments()),
+        };
+        root.unwrap_or_else(|| "empty-dag".to_string())
+    }
+
+    /// Proof that DAG node `node_id` is under the entry's `merkle_root`; `None` if the entry
+    /// has no such node. Works on redacted and encrypted entries, since it needs only the
+    /// node commitments. Format 0 entries hash whole nodes and have no node proofs.
+    pub fn node_proof(&self, node_id: &str) -> Result<Option<NodeProof>> {
+        if self.version == 0 {
+            bail!(
+                "audit entry {} predates payload commitments",
+                self.request_id
+            );
+        }
+        let tree_version = match self.version {
+            1 => LEGACY_TREE,
+            _ => TREE_VERSION,
+        };
+        Ok(commitment_proof(&self.commitments(), node_id, tree_version))
+    }
+
+    /// Replaces the payload of DAG node `node_id` by a redaction record signed by `key`.
+    /// Format 0 entries hash payloads directly and cannot be redacted.
+    pub fn redact(&mut self, node_id: &str, reason: &str, key: &NodeKey) -> Result<()> {
+        if self.version == 0 {
+            bail!(
+                "audit entry {} predates payload commitments",
+                self.request_id
+            );
+        }
+        if self.mode == KEY_ROTATION_MODE {
+            bail!("key rotation entries cannot be redacted");
+        }
+        let node = self
+            .dag
+            .iter_mut()
+            .find(|node| node.id == node_id)
+            .ok_or_else(|| anyhow!("audit entry {} has no node {node_id}", self.request_id))?;
+        if self.redactions.iter().any(|r| r.node_id == node_id) {
+            bail!("node {node_id} is already redacted");
+        }
+        let mut redaction = Redaction {
+            node_id: node_id.to_string(),
+            payload_hash: node.payload_hash(),
+            reason: reason.to_string(),
+            redacted_at: Utc::now().to_rfc3339(),
+            node_key: Some(key.public_key_hex()),
+            signature: None,
+        };
+        redaction.signature = Some(key.sign(redaction.message(&self.hash).as_bytes()));
+        node.payload = serde_json::Value::Null;
+        node.salt = None;
+        self.redactions.push(redaction);
+        Ok(())
+    }
+
+    /// Whether `hash` matches the entry's contents and `prev_hash`.
+    pub fn hash_is_valid(&self) -> bool {
+        let unsealed = AuditEntry {
+            hash: String::new(),
+            signature: None,
+            ..self.clone()
+        };
+        compute_hash(&unsealed) == self.hash
+    }
+
+    /// Whether `signature` is a valid signature over `hash` by `node_key`.
+    pub fn signature_is_valid(&self) -> bool {
+        match (&self.node_key, &self.signature) {
+            (Some(key), Some(signature)) => verify_signature(key, self.hash.as_bytes(), signature),
+            _ => false,
+        }
+    }
+
+    /// The `(old_key, new_key)` pair of a key rotation entry whose endorsement (old key
+    /// signs) and acceptance (new key signs) both verify.
+    pub fn key_rotation(&self) -> Option<(String, String)> {
+        let node = self
+            .dag
+            .iter()
+            .find(|node| node.node_type == "key_rotation")?;
+        let field = |name: &str| node.payload.get(name)?.as_str().map(str::to_string);
+        let (old, new) = (field("old_key")?, field("new_key")?);
+        let message = rotation_message(&old, &new);
+        (verify_signature(&old, message.as_bytes(), &field("endorsement")?)
+            && verify_signature(&new, message.as_bytes(), &field("acceptance")?))
+        .then_some((old, new))
+    }
+}
+
+/// Audit mode of entries that hand signing over to a new node key.
+pub const KEY_ROTATION_MODE: &str = "key_rotation";
+
+fn rotation_message(old_key: &str, new_key: &str) -> String {
+    format!("axiomhive key rotation v1:{old_key}:{new_key}")
+}
+
+/// Where audit entries are persisted. Stores only order and index entries; `AuditLog`
+/// builds them and `ChainLinker` links them, so every backend produces the same chain and
+/// the same verifier applies to all of them.
+pub trait AuditStore: Send + Sync {
+    /// Links `entry` to the current head with `linker` and persists it durably before
+    /// returning it. Appends must be serialized so no two entries link to the same head.
+    fn append(&self, entry: AuditEntry, linker: &ChainLinker) -> Result<AuditEntry>;
+
+    /// Index and hash of the last entry; `None` while the log is empty.
+    fn head(&self) -> Result<Option<AuditCheckpoint>>;
+
+    /// Index of the first entry recorded for `request_id`.
+    fn position(&self, request_id: Uuid) -> Result<Option<usize>>;
+
+    /// Entries whose index falls in `range`, in order. Entries deleted by retention are
+    /// skipped.
+    fn scan(&self, range: Range<usize>) -> Result<Vec<(usize, AuditEntry)>>;
+
+    /// Hash of every entry in order, deleted ones included; the Merkle tree's leaves.
+    fn hashes(&self) -> Result<Vec<String>>;
+
+    /// Hashes of the entries from index `start` on, as in `hashes`.
+    fn hashes_from(&self, start: usize) -> Result<Vec<String>> {
+        let mut hashes = self.hashes()?;
+        Ok(hashes.split_off(start.min(hashes.len())))
+    }
+
+    /// Overwrites each `(index, entry)` in place; every entry must keep its hash. Used to
+    /// store redactions and re-encrypted bodies.
+    fn replace(&self, entries: &[(usize, AuditEntry)]) -> Result<()>;
+
+    /// Checks the stored chain; see `verify_log` for what is checked.
+    fn verify(&self, from: Option<&AuditCheckpoint>, node_key: Option<&str>)
+        -> Result<AuditReport>;
+
+    fn get(&self, request_id: Uuid) -> Result<Option<AuditEntry>> {
+        let Some(index) = self.position(request_id)? else {
+            return Ok(None);
+        };
+        Ok(self
+            .scan(index..index + 1)?
+            .into_iter()
+            .next()
+            .map(|(_, entry)| entry))
+    }
+}
+
+/// Links entries into the chain on behalf of a store, signing them with the node key.
+pub struct ChainLinker<'a> {
+    key: Option<&'a NodeKey>,
+    keyring: Option<&'a AuditKeyring>,
+}
+
+impl<'a> ChainLinker<'a> {
+    pub fn new(key: Option<&'a NodeKey>) -> Self {
+        Self { key, keyring: None }
+    }
+
+    /// Encrypts linked entries' payloads under the keyring's active key.
+    pub fn with_keyring(self, keyring: Option<&'a AuditKeyring>) -> Self {
+        Self { keyring, ..self }
+    }
+
+    /// Key that signs linked entries and anything else the store seals.
+    pub fn node_key(&self) -> Option<&'a NodeKey> {
+        self.key
+    }
+
+    /// Sets `prev_hash` and the signer on `entry`, then hashes, signs and encrypts it.
+    pub fn link(&self, mut entry: AuditEntry, prev_hash: &str) -> Result<AuditEntry> {
+        entry.prev_hash = prev_hash.to_string();
+        entry.node_key = self.key.map(NodeKey::public_key_hex);
+        entry.hash = String::new();
+        entry.signature = None;
+        entry.hash = compute_hash(&entry);
+        entry.signature = self.key.map(|key| key.sign(entry.hash.as_bytes()));
+        if let Some(keyring) = self.keyring {
+            keyring.seal(&mut entry)?;
+        }
+        Ok(entry)
+    }
+}
+
+/// Hash of an entry with empty `hash` and `signature`: blake3 over `prev_hash` and the
+/// entry's JSON. From format 1 the JSON carries node commitments in place of the DAG and
+/// leaves out redaction records and the encrypted body.
+fn compute_hash(entry: &AuditEntry) -> String {
+    let body = match entry.version {
+        0 => serde_json::to_string(entry).unwrap_or_default(),
+        _ => {
+            let mut value = serde_json::to_value(entry).unwrap_or_default();
+            if let Some(fields) = value.as_object_mut() {
+                fields.insert("dag".into(), serde_json::json!(entry.commitments()));
+                fields.remove("redactions");
+                fields.remove("encrypted");
+            }
+            value.to_string()
+        }
+    };
+    let mut hasher = Hasher::new();
+    hasher.update(entry.prev_hash.as_bytes());
+    hasher.update(body.as_bytes());
+    hasher.finalize().to_hex().to_string()
+}
+
+/// Storage backend picked by `AXIOMHIVE_AUDIT_BACKEND`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AuditBackend {
+    #[default]
+    Jsonl,
+    Sled,
+}
+
+impl AuditBackend {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name {
+            "jsonl" => Ok(Self::Jsonl),
+            "sled" => Ok(Self::Sled),
+            other => bail!("unknown audit backend {other:?}; expected jsonl or sled"),
+        }
+    }
+
+    pub fn default_path(self) -> &'static str {
+        match self {
+            Self::Jsonl => "data/audit.jsonl",
+            Self::Sled => "data/audit.sled",
+        }
+    }
+
+    /// Opens the store at `path`; `rotation` only applies to the JSONL backend.
+    pub fn open(
+        self,
+        path: impl AsRef<Path>,
+        rotation: AuditRotation,
+    ) -> Result<Arc<dyn AuditStore>> {
+        Ok(match self {
+            Self::Jsonl => Arc::new(JsonlStore::open(path)?.with_rotation(rotation)),
+            Self::Sled => Arc::new(SledStore::open(path)?),
+        })
+    }
+}
+
+/// Appended entries buffered per live subscriber; a subscriber that falls further behind
+/// catches up from the store.
+pub const TAIL_BUFFER: usize = 1024;
+
+/// A DAG node found in the log by its id, with the entry that recorded it.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditedNode {
+    pub index: usize,
+    pub request_id: Uuid,
+    pub node: DagNode,
+}
+
+/// Entries read per batch when searching the whole log.
+const SCAN_BATCH: usize = 256;
+
+/// An entry published to live subscribers, with its index in the chain.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEvent {
+    pub index: usize,
+    pub entry: AuditEntry,
+}
+
+/// Entry hashes and the Merkle tree over them, extended from the store as entries are
+/// appended rather than rebuilt per proof.
+#[derive(Default)]
+struct EntryTree {
+    hashes: Vec<String>,
+    tree: TreeCache,
 }
 
 pub struct AuditLog {
-    path: PathBuf,
-    last_hash: Mutex<String>,
+    store: Arc<dyn AuditStore>,
+    /// Held across every append so a key rotation is ordered with the entries around it.
+    key: Mutex<Option<Arc<NodeKey>>>,
+    keyring: Option<Arc<AuditKeyring>>,
+    /// Salt key of a log with neither a keyring nor a node key; see `salt_key`.
+    instance_salt_key: [u8; 32],
+    tree: Mutex<EntryTree>,
+    events: broadcast::Sender<AuditEvent>,
 }
 
 impl AuditLog {
-    pub fn new(path: impl AsRef<Path>) -> Self {
-        let path_buf = path.as_ref().to_path_buf();
-        let last_hash = Self::read_last_hash(&path_buf).unwrap_or_default();
+    pub fn new(store: Arc<dyn AuditStore>) -> Self {
+        Self {
+            store,
+            key: Mutex::new(None),
+            keyring: None,
+            instance_salt_key: rand::random(),
+            tree: Mutex::new(EntryTree::default()),
+            events: broadcast::channel(TAIL_BUFFER).0,
+        }
+    }
+
+    /// Opens a JSONL log at `path`; see `JsonlStore::open`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::new(Arc::new(JsonlStore::open(path)?)))
+    }
+
+    /// Signs every appended entry hash with `key`.
+    pub fn with_node_key(self, key: Arc<NodeKey>) -> Self {
         Self {
-            path: path_buf,
-            last_hash: Mutex::new(last_hash),
+            key: Mutex::new(Some(key)),
+            ..self
         }
     }
 
-    fn read_last_hash(path: &Path) -> Option<String> {
-        if !path.exists() {
-            return None;
+    /// Encrypts appended entries' payloads at rest and decrypts entries read back.
+    pub fn with_keyring(self, keyring: Arc<AuditKeyring>) -> Self {
+        Self {
+            keyring: Some(keyring),
+            ..self
         }
-        let file = fs::File::open(path).ok()?;
-        let reader = BufReader::new(file);
-        reader
-            .lines()
-            .filter_map(Result::ok)
-            .last()
-            .and_then(|line| serde_json::from_str::<AuditEntry>(&line).ok())
-            .map(|entry| entry.hash)
     }
 
-    pub fn append(&self, request_id: Uuid, mode: &str, dag: &RequestDag) -> Result<AuditEntry> {
-        fs::create_dir_all(self.path.parent().unwrap_or_else(|| Path::new(".")))?;
+    pub fn store(&self) -> &Arc<dyn AuditStore> {
+        &self.store
+    }
+
+    fn linker<'a>(&'a self, key: Option<&'a NodeKey>) -> ChainLinker<'a> {
+        ChainLinker::new(key).with_keyring(self.keyring.as_deref())
+    }
 
-        let merkle_root = dag.merkle_root().unwrap_or_else(|| "empty-dag".to_string());
+    /// Decrypts `entry` if a keyring is set; otherwise returns it as stored.
+    fn opened(&self, mut entry: AuditEntry) -> Result<AuditEntry> {
+        if let Some(keyring) = &self.keyring {
+            keyring.open(&mut entry)?;
+        }
+        Ok(entry)
+    }
 
-        let prev_hash = { self.last_hash.lock().unwrap().clone() };
-        let entry = AuditEntry {
+    /// Key that payload salts of new entries are derived from: the active data key when
+    /// payloads are encrypted, else the node key, else a random key of this log instance.
+    fn salt_key(&self, key: Option<&NodeKey>) -> [u8; 32] {
+        match (&self.keyring, key) {
+            (Some(keyring), _) => keyring.derive_key(SALT_CONTEXT),
+            (None, Some(key)) => key.derive_key(SALT_CONTEXT),
+            (None, None) => self.instance_salt_key,
+        }
+    }
+
+    fn node_key(&self) -> Result<std::sync::MutexGuard<'_, Option<Arc<NodeKey>>>> {
+        self.key.lock().map_err(|_| anyhow!("audit key poisoned"))
+    }
+
+    /// Sends a just-committed entry to live subscribers. Called with the key mutex held, so
+    /// the head is the entry itself.
+    fn publish(&self, entry: &AuditEntry) -> Result<()> {
+        if self.events.receiver_count() == 0 {
+            return Ok(());
+        }
+        if let Some(head) = self.store.head()?.filter(|head| head.hash == entry.hash) {
+            let _ = self.events.send(AuditEvent {
+                index: head.index,
+                entry: entry.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    pub fn append(
+        &self,
+        request_id: Uuid,
+        mode: &str,
+        dag: &RequestDag,
+        policy: &PolicyProvenance,
+    ) -> Result<AuditEntry> {
+        let mut entry = AuditEntry {
+            policy_hash: policy.hash.clone(),
+            policy_signer: policy.signer.clone(),
+            policy_profile: policy.profile.clone(),
+            ..AuditEntry::unlinked(request_id, mode, dag)
+        };
+        let key = self.node_key()?;
+        entry.salt_payloads(&self.salt_key(key.as_deref()));
+        let entry = self.store.append(entry, &self.linker(key.as_deref()))?;
+        let entry = self.opened(entry)?;
+        self.publish(&entry)?;
+        Ok(entry)
+    }
+
+    /// Records a hand-over from the current node key to `new_key`, signed by the old key,
+    /// then signs later entries with `new_key`.
+    pub fn rotate_key(&self, new_key: Arc<NodeKey>) -> Result<AuditEntry> {
+        let mut key = self.node_key()?;
+        let old_key = key
+            .clone()
+            .ok_or_else(|| anyhow!("audit log has no node key to rotate"))?;
+        let (old, new) = (old_key.public_key_hex(), new_key.public_key_hex());
+        let message = rotation_message(&old, &new);
+        let mut dag = RequestDag::default();
+        dag.add_node(
+            "key_rotation",
+            serde_json::json!({
+                "old_key": old,
+                "new_key": new,
+                "endorsement": old_key.sign(message.as_bytes()),
+                "acceptance": new_key.sign(message.as_bytes()),
+            }),
+            &[],
+        )?;
+        let entry = self.store.append(
+            AuditEntry::unlinked(Uuid::new_v4(), KEY_ROTATION_MODE, &dag),
+            &self.linker(Some(&old_key)),
+        )?;
+        *key = Some(new_key);
+        self.publish(&entry)?;
+        Ok(entry)
+    }
+
+    /// Follows entries as they are appended, optionally only those with `mode`. With
+    /// `after`, the tail first replays every entry after the one with that hash, so a
+    /// subscriber that reconnects misses nothing; `None` if no entry has that hash.
+    pub fn tail(
+        self: &Arc<Self>,
+        after: Option<&str>,
+        mode: Option<String>,
+    ) -> Result<Option<AuditTail>> {
+        // Subscribe before reading the head so no append falls between the two.
+        let events = self.events.subscribe();
+        let next = match after {
+            Some(after) => match self.store.hashes()?.iter().position(|hash| hash == after) {
+                Some(index) => index + 1,
+                None => return Ok(None),
+            },
+            None => self.store.head()?.map_or(0, |head| head.index + 1),
+        };
+        Ok(Some(AuditTail {
+            log: self.clone(),
+            events,
+            next,
+            mode,
+            backlog: VecDeque::new(),
+        }))
+    }
+
+    pub fn head(&self) -> Result<Option<AuditCheckpoint>> {
+        self.store.head()
+    }
+
+    /// The entry recorded for `request_id`, unless retention deleted it.
+    pub fn get(&self, request_id: Uuid) -> Result<Option<AuditEntry>> {
+        self.store
+            .get(request_id)?
+            .map(|entry| self.opened(entry))
+            .transpose()
+    }
+
+    pub fn scan(&self, range: Range<usize>) -> Result<Vec<(usize, AuditEntry)>> {
+        self.store
+            .scan(range)?
+            .into_iter()
+            .map(|(index, entry)| Ok((index, self.opened(entry)?)))
+            .collect()
+    }
+
+    /// Every node with content id `node_id` across all audited DAGs, oldest first. Equal
+    /// computations share an id, so one id can be recorded by several requests. Nodes
+    /// whose id does not match what they commit to are skipped.
+    pub fn find_node(&self, node_id: &str) -> Result<Vec<AuditedNode>> {
+        let Some(head) = self.store.head()? else {
+            return Ok(Vec::new());
+        };
+        let mut found = Vec::new();
+        for start in (0..=head.index).step_by(SCAN_BATCH) {
+            for (index, entry) in self.scan(start..(start + SCAN_BATCH).min(head.index + 1))? {
+                for (node, commitment) in entry.dag.iter().zip(entry.commitments()) {
+                    if node.id == node_id && commitment.id_is_valid() {
+                        found.push(AuditedNode {
+                            index,
+                            request_id: entry.request_id,
+                            node: node.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        Ok(found)
+    }
+
+    pub fn verify(
+        &self,
+        from: Option<&AuditCheckpoint>,
+        node_key: Option<&str>,
+    ) -> Result<AuditReport> {
+        self.store.verify(from, node_key)
+    }
+
+    /// Removes the payload of DAG node `node_id` from the entry for `request_id`, leaving
+    /// its hash and a redaction record signed by the node key.
+    pub fn redact(&self, request_id: Uuid, node_id: &str, reason: &str) -> Result<AuditEntry> {
+        let key = self.node_key()?;
+        let key = key
+            .as_ref()
+            .ok_or_else(|| anyhow!("audit log has no node key to sign redactions"))?;
+        let index = self
+            .store
+            .position(request_id)?
+            .ok_or_else(|| anyhow!("request {request_id} is not in the audit log"))?;
+        let (_, entry) = self
+            .store
+            .scan(index..index + 1)?
+            .pop()
+            .ok_or_else(|| anyhow!("audit entry for {request_id} was deleted by retention"))?;
+        let mut entry = self.opened(entry)?;
+        if entry.encrypted.is_some() {
+            bail!("audit entry {request_id} is encrypted and no keystore is configured");
+        }
+        entry.redact(node_id, reason, key)?;
+        let mut stored = entry.clone();
+        if let Some(keyring) = &self.keyring {
+            keyring.seal(&mut stored)?;
+        }
+        if !stored.hash_is_valid() {
+            bail!("redaction would change the hash of audit entry {request_id}");
+        }
+        self.store.replace(&[(index, stored)])?;
+        Ok(entry)
+    }
+
+    /// Re-encrypts every entry under the keyring's active key, encrypting entries stored in
+    /// the clear too, `batch` entries at a time; returns how many were rewritten. Entry
+    /// hashes do not change. Entries deleted by retention are skipped.
+    pub fn reencrypt(&self, batch: usize) -> Result<usize> {
+        let keyring = self
+            .keyring
+            .as_ref()
+            .ok_or_else(|| anyhow!("audit log has no keystore to re-encrypt with"))?;
+        // Held so appends and redactions wait for the rewrite.
+        let _key = self.node_key()?;
+        let Some(head) = self.store.head()? else {
+            return Ok(0);
+        };
+        let mut rewritten = 0;
+        let mut start = 0;
+        while start <= head.index {
+            let end = (start + batch.max(1)).min(head.index + 1);
+            let mut changed = Vec::new();
+            for (index, mut entry) in self.store.scan(start..end)? {
+                let current = entry.encrypted.as_ref().map(|body| body.key_id.clone());
+                if current.as_deref() == Some(keyring.active_key_id()) {
+                    continue;
+                }
+                keyring.open(&mut entry)?;
+                keyring.seal(&mut entry)?;
+                if entry.encrypted.is_some() {
+                    changed.push((index, entry));
+                }
+            }
+            if !changed.is_empty() {
+                self.store.replace(&changed)?;
+                rewritten += changed.len();
+            }
+            start = end;
+        }
+        Ok(rewritten)
+    }
+
+    /// The Merkle tree, first extended by entries appended since it was last read. Entry
+    /// hashes never change, so only new ones are read from the store.
+    fn tree(&self) -> Result<std::sync::MutexGuard<'_, EntryTree>> {
+        let mut tree = self
+            .tree
+            .lock()
+            .map_err(|_| anyhow!("audit tree poisoned"))?;
+        for hash in self.store.hashes_from(tree.hashes.len())? {
+            tree.tree.push(leaf_hash(&hash));
+            tree.hashes.push(hash);
+        }
+        Ok(tree)
+    }
+
+    /// Current Merkle tree head over all entry hashes, signed by the node key if one is set.
+    pub fn tree_head(&self) -> Result<SignedTreeHead> {
+        self.tree_head_at(None)?
+            .ok_or_else(|| anyhow!("audit tree head unavailable"))
+    }
+
+    /// Signed head of the tree over the first `tree_size` entries (default: all); `None`
+    /// if the log is shorter than that.
+    pub fn tree_head_at(&self, tree_size: Option<usize>) -> Result<Option<SignedTreeHead>> {
+        let (tree_size, root) = {
+            let tree = &self.tree()?.tree;
+            let tree_size = tree_size.unwrap_or(tree.len());
+            match tree.root(tree_size) {
+                Some(root) => (tree_size, root),
+                None => return Ok(None),
+            }
+        };
+        let key = self.node_key()?.clone();
+        let mut head = SignedTreeHead {
+            tree_size,
+            root_hash: hex::encode(root),
             timestamp: Utc::now().to_rfc3339(),
-            request_id,
-            mode: mode.to_string(),
-            merkle_root,
-            dag: dag.nodes.clone(),
-            prev_hash,
-            hash: String::new(),
+            node_key: key.as_ref().map(|key| key.public_key_hex()),
+            signature: None,
+        };
+        head.signature = key.map(|key| key.sign(head.message().as_bytes()));
+        Ok(Some(head))
+    }
+
+    /// Proves the entry for `request_id` is in the tree of `tree_size` (default: current)
+    /// entries. `None` if the request is not logged within that size.
+    pub fn inclusion_proof(
+        &self,
+        request_id: Uuid,
+        tree_size: Option<usize>,
+    ) -> Result<Option<InclusionProof>> {
+        let Some(index) = self.store.position(request_id)? else {
+            return Ok(None);
         };
+        let entries = self.tree()?;
+        let tree_size = tree_size.unwrap_or(entries.tree.len());
+        let Some(path) = entries.tree.inclusion_path(index, tree_size) else {
+            return Ok(None);
+        };
+        Ok(Some(InclusionProof {
+            request_id,
+            entry_hash: entries.hashes[index].clone(),
+            leaf_index: index,
+            tree_size,
+            audit_path: path.iter().map(hex::encode).collect(),
+        }))
+    }
+
+    /// Proves the tree of `second` entries extends the tree of `first` entries. `None` if
+    /// `first > second` or `second` exceeds the log.
+    pub fn consistency_proof(
+        &self,
+        first: usize,
+        second: usize,
+    ) -> Result<Option<ConsistencyProof>> {
+        Ok(self
+            .tree()?
+            .tree
+            .consistency_path(first, second)
+            .map(|proof| ConsistencyProof {
+                first_size: first,
+                second_size: second,
+                proof: proof.iter().map(hex::encode).collect(),
+            }))
+    }
+}
+
+/// Live subscription created by `AuditLog::tail`. Entries arrive in chain order without
+/// gaps: ones missed while the subscriber lagged, or appended without being published
+/// (segment headers), are read back from the store.
+pub struct AuditTail {
+    log: Arc<AuditLog>,
+    events: broadcast::Receiver<AuditEvent>,
+    /// Index of the next entry to hand out.
+    next: usize,
+    mode: Option<String>,
+    backlog: VecDeque<AuditEvent>,
+}
+
+impl AuditTail {
+    /// The next matching entry, waiting for one to be appended.
+    pub async fn next(&mut self) -> Result<AuditEvent> {
+        loop {
+            if let Some(event) = self.backlog.pop_front() {
+                self.next = event.index + 1;
+                if self
+                    .mode
+                    .as_ref()
+                    .is_none_or(|mode| *mode == event.entry.mode)
+                {
+                    return Ok(event);
+                }
+                continue;
+            }
+            let upto = match self.events.recv().await {
+                Ok(event) if event.index < self.next => continue,
+                Ok(event) if event.index == self.next => {
+                    self.backlog.push_back(event);
+                    continue;
+                }
+                Ok(event) => Some(event.index + 1),
+                Err(broadcast::error::RecvError::Lagged(_)) => None,
+                Err(broadcast::error::RecvError::Closed) => bail!("audit log closed"),
+            };
+            self.catch_up(upto).await?;
+        }
+    }
+
+    /// Queues the stored entries from `next` up to `upto` (default: the head).
+    async fn catch_up(&mut self, upto: Option<usize>) -> Result<()> {
+        let (log, next) = (self.log.clone(), self.next);
+        let (upto, entries) = tokio::task::spawn_blocking(move || {
+            let upto = match upto {
+                Some(upto) => upto,
+                None => log.head()?.map_or(0, |head| head.index + 1),
+            };
+            Ok::<_, anyhow::Error>((upto, log.scan(next..upto)?))
+        })
+        .await??;
+        self.backlog.extend(
+            entries
+                .into_iter()
+                .map(|(index, entry)| AuditEvent { index, entry }),
+        );
+        // Entries deleted by retention leave nothing to queue.
+        if self.backlog.is_empty() {
+            self.next = self.next.max(upto);
+        }
+        Ok(())
+    }
+}
+
+/// A position in the log trusted from an earlier verification: the entry at `index`
+/// (0-based line number) has hash `hash`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditCheckpoint {
+    pub index: usize,
+    pub hash: String,
+}
 
-        let hash = Self::compute_hash(&entry);
-        let mut finalized = entry.clone();
-        finalized.hash = hash.clone();
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AuditIssue {
+    Unparsable {
+        index: usize,
+        error: String,
+    },
+    /// The entry's contents no longer match its `hash`.
+    HashMismatch {
+        index: usize,
+        request_id: Uuid,
+    },
+    /// `prev_hash` points at no entry in the log.
+    BrokenLink {
+        index: usize,
+        expected_prev: String,
+        found_prev: String,
+    },
+    /// `prev_hash` points at an entry other than the one before it.
+    Reordered {
+        index: usize,
+        links_to: usize,
+    },
+    MerkleMismatch {
+        index: usize,
+        stored: String,
+        computed: String,
+    },
+    MissingSignature {
+        index: usize,
+    },
+    BadSignature {
+        index: usize,
+    },
+    /// Signed by a key that is not the node key active at this point in the chain.
+    UntrustedSigner {
+        index: usize,
+        node_key: String,
+    },
+    /// A key rotation entry whose endorsement or acceptance does not verify.
+    InvalidRotation {
+        index: usize,
+    },
+    /// The trusted checkpoint is missing or its entry changed.
+    CheckpointMismatch {
+        index: usize,
+        expected: String,
+        found: Option<String>,
+    },
+    /// A sealed segment's entries differ from its seal, or it starts at the wrong index.
+    SegmentMismatch {
+        index: usize,
+        segment: u64,
+        detail: String,
+    },
+    /// A sealed segment's data is gone but its seal is not a tombstone.
+    MissingSegment {
+        index: usize,
+        segment: u64,
+    },
+    /// A seal or tombstone that is unsigned, does not verify or is signed by an untrusted key.
+    BadSeal {
+        index: usize,
+        segment: u64,
+    },
+    /// A redaction record that is unsigned, untrusted, or names a node whose payload is
+    /// still present.
+    BadRedaction {
+        index: usize,
+        node_id: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditReport {
+    pub start_index: usize,
+    pub entries_checked: usize,
+    /// Index of the first entry whose chain link is broken or out of order.
+    pub first_broken_link: Option<usize>,
+    pub issues: Vec<AuditIssue>,
+    /// Checkpoint for the last entry read, to resume from on the next run.
+    pub head: Option<AuditCheckpoint>,
+}
+
+impl AuditReport {
+    pub fn is_intact(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// One line of the log as the verifier sees it.
+enum Slot {
+    Entry(Box<AuditEntry>),
+    /// Unparsable, or before the checkpoint and not read.
+    Unread,
+    /// Deleted by retention; only the tombstone's hashes remain.
+    Deleted {
+        prev_hash: String,
+        hash: String,
+    },
+}
 
-        let mut file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&self.path)?;
-        writeln!(file, "{}", serde_json::to_string(&finalized)?)?;
+/// Entries as read back from a store, in chain order, for `check_chain`.
+#[derive(Default)]
+pub(crate) struct ChainScan {
+    slots: Vec<Slot>,
+    /// First index of each entry hash, to tell reordering from a broken link.
+    positions: HashMap<String, usize>,
+    issues: Vec<AuditIssue>,
+    /// Seals of sealed segments and the index their first entry landed at.
+    seals: Vec<(usize, SegmentSeal)>,
+    /// Entries before this index are trusted and not parsed.
+    skip_before: usize,
+}
+
+impl ChainScan {
+    pub(crate) fn new(from: Option<&AuditCheckpoint>) -> Self {
+        Self {
+            skip_before: from.map_or(0, |checkpoint| checkpoint.index),
+            ..Self::default()
+        }
+    }
 
-        let mut guard = self.last_hash.lock().unwrap();
-        *guard = hash;
+    pub(crate) fn len(&self) -> usize {
+        self.slots.len()
+    }
 
-        Ok(finalized)
+    /// Whether the next entry is past the trusted prefix and should be read.
+    fn wants_next(&self) -> bool {
+        self.slots.len() >= self.skip_before
     }
 
-    fn compute_hash(entry: &AuditEntry) -> String {
-        let mut hasher = Hasher::new();
-        hasher.update(entry.prev_hash.as_bytes());
-        hasher.update(serde_json::to_string(entry).unwrap_or_default().as_bytes());
-        hasher.finalize().to_hex().to_string()
+    pub(crate) fn push_raw(&mut self, raw: &[u8]) {
+        if !self.wants_next() {
+            self.slots.push(Slot::Unread);
+            return;
+        }
+        match serde_json::from_slice::<AuditEntry>(raw) {
+            Ok(entry) => self.push_entry(entry),
+            Err(err) => {
+                self.issues.push(AuditIssue::Unparsable {
+                    index: self.slots.len(),
+                    error: err.to_string(),
+                });
+                self.slots.push(Slot::Unread);
+            }
+        }
+    }
+
+    pub(crate) fn push_entry(&mut self, entry: AuditEntry) {
+        self.positions
+            .entry(entry.hash.clone())
+            .or_insert(self.slots.len());
+        self.slots.push(Slot::Entry(Box::new(entry)));
+    }
+
+    /// An index the store has no entry for.
+    pub(crate) fn push_missing(&mut self) {
+        self.issues.push(AuditIssue::Unparsable {
+            index: self.slots.len(),
+            error: "entry missing".to_string(),
+        });
+        self.slots.push(Slot::Unread);
+    }
+
+    fn push_deleted(&mut self, prev_hash: String, hash: String) {
+        self.positions
+            .entry(hash.clone())
+            .or_insert(self.slots.len());
+        self.slots.push(Slot::Deleted { prev_hash, hash });
+    }
+
+    fn read_lines(&mut self, reader: impl BufRead) -> Result<()> {
+        for line in reader.split(b'\n') {
+            self.push_raw(&line?);
+        }
+        Ok(())
+    }
+
+    /// Reads a sealed segment, or follows its tombstone if the data is gone.
+    fn read_segment(&mut self, log: &Path, seal: SegmentSeal) -> Result<()> {
+        let index = self.slots.len();
+        if seal.first_index != index {
+            self.issues.push(AuditIssue::SegmentMismatch {
+                index,
+                segment: seal.segment,
+                detail: format!("seal starts at entry {}", seal.first_index),
+            });
+        }
+        match open_segment(log, seal.segment)? {
+            Some(reader) => {
+                self.read_lines(reader)?;
+                let read = &self.slots[index..];
+                let differs = read.len() != seal.entries.len()
+                    || read.iter().zip(&seal.entries).any(|(slot, sealed)| {
+                        matches!(slot, Slot::Entry(entry) if entry.hash != sealed.hash)
+                    });
+                if differs {
+                    self.issues.push(AuditIssue::SegmentMismatch {
+                        index,
+                        segment: seal.segment,
+                        detail: format!(
+                            "{} entries on disk, {} sealed",
+                            read.len(),
+                            seal.entries.len()
+                        ),
+                    });
+                }
+            }
+            None => {
+                if !seal.is_tombstone() {
+                    self.issues.push(AuditIssue::MissingSegment {
+                        index,
+                        segment: seal.segment,
+                    });
+                }
+                let mut prev_hash = seal.prev_hash.clone();
+                for sealed in &seal.entries {
+                    let linked = std::mem::replace(&mut prev_hash, sealed.hash.clone());
+                    self.push_deleted(linked, sealed.hash.clone());
+                }
+            }
+        }
+        self.seals.push((index, seal));
+        Ok(())
     }
 }
-use anyhow::Result;
+
+/// Recomputes every entry hash, `prev_hash` link and Merkle root in the log, across sealed
+/// segments and the active one; deleted segments are followed through their tombstones.
+/// With `from`, entries up to the checkpoint are trusted and only later ones are checked.
+/// With `node_key`, every entry, seal and tombstone must also be signed by the node key
+/// active at that point; keys linked to `node_key` through valid rotation entries are
+/// trusted too.
+pub fn verify_log(
+    path: impl AsRef<Path>,
+    from: Option<&AuditCheckpoint>,
+    node_key: Option<&str>,
+) -> Result<AuditReport> {
+    let path = path.as_ref();
+    let file = File::open(path).with_context(|| format!("opening audit log {}", path.display()))?;
+    let mut scan = ChainScan::new(from);
+    for seal in load_seals(path)? {
+        scan.read_segment(path, seal)?;
+    }
+    scan.read_lines(BufReader::new(file))?;
+    Ok(check_chain(scan, from, node_key))
+}
+
+/// Checks every link, hash, Merkle root and signature in `scan`; shared by all stores.
+pub(crate) fn check_chain(
+    scan: ChainScan,
+    from: Option<&AuditCheckpoint>,
+    node_key: Option<&str>,
+) -> AuditReport {
+    let ChainScan {
+        slots,
+        positions,
+        issues,
+        seals,
+        ..
+    } = scan;
+    let start_index = from.map_or(0, |checkpoint| checkpoint.index + 1);
+    let mut report = AuditReport {
+        start_index,
+        entries_checked: 0,
+        first_broken_link: None,
+        issues,
+        head: None,
+    };
+
+    // `None` after an unparsable line: the next link cannot be checked.
+    let mut prev_hash = Some(String::new());
+    if let Some(checkpoint) = from {
+        let found = match slots.get(checkpoint.index) {
+            Some(Slot::Entry(entry)) if entry.hash_is_valid() => Some(entry.hash.clone()),
+            Some(Slot::Deleted { hash, .. }) => Some(hash.clone()),
+            _ => None,
+        };
+        if found.as_deref() != Some(checkpoint.hash.as_str()) {
+            report.issues.push(AuditIssue::CheckpointMismatch {
+                index: checkpoint.index,
+                expected: checkpoint.hash.clone(),
+                found,
+            });
+        }
+        prev_hash = Some(checkpoint.hash.clone());
+        report.head = Some(checkpoint.clone());
+    }
+
+    let mut rotations = Vec::new();
+    for (index, slot) in slots.iter().enumerate().skip(start_index) {
+        match slot {
+            Slot::Entry(entry) if entry.mode == KEY_ROTATION_MODE => match entry.key_rotation() {
+                Some(keys) => rotations.push(keys),
+                None => report.issues.push(AuditIssue::InvalidRotation { index }),
+            },
+            _ => {}
+        }
+    }
+    let mut trusted = node_key
+        .map(|key| HashSet::from([key.to_string()]))
+        .unwrap_or_default();
+    while let Some((old, new)) = rotations
+        .iter()
+        .find(|(old, new)| trusted.contains(old) != trusted.contains(new))
+    {
+        trusted.insert(old.clone());
+        trusted.insert(new.clone());
+    }
+    let mut active_key = rotations
+        .first()
+        .map(|(old, _)| old.clone())
+        .or_else(|| node_key.map(str::to_string));
+
+    for (index, seal) in &seals {
+        let index = *index;
+        if index + seal.entries.len() <= start_index {
+            continue;
+        }
+        let valid = match (&seal.signature, node_key) {
+            (None, None) => true,
+            (None, Some(_)) => false,
+            (Some(_), None) => seal.signature_is_valid(),
+            (Some(_), Some(_)) => {
+                seal.signature_is_valid()
+                    && seal
+                        .node_key
+                        .as_ref()
+                        .is_some_and(|key| trusted.contains(key))
+            }
+        };
+        if !valid {
+            report.issues.push(AuditIssue::BadSeal {
+                index,
+                segment: seal.segment,
+            });
+        }
+    }
+
+    for (index, slot) in slots.iter().enumerate().skip(start_index) {
+        let entry = match slot {
+            Slot::Entry(entry) => entry,
+            Slot::Unread => {
+                report.entries_checked += 1;
+                prev_hash = None;
+                continue;
+            }
+            Slot::Deleted {
+                prev_hash: linked,
+                hash,
+            } => {
+                if let Some(expected) = prev_hash.as_ref().filter(|expected| *expected != linked) {
+                    report.first_broken_link.get_or_insert(index);
+                    report.issues.push(AuditIssue::BrokenLink {
+                        index,
+                        expected_prev: expected.clone(),
+                        found_prev: linked.clone(),
+                    });
+                }
+                prev_hash = Some(hash.clone());
+                report.head = Some(AuditCheckpoint {
+                    index,
+                    hash: hash.clone(),
+                });
+                continue;
+            }
+        };
+        report.entries_checked += 1;
+        match (&entry.signature, node_key.is_some()) {
+            (None, true) => report.issues.push(AuditIssue::MissingSignature { index }),
+            (None, false) => {}
+            (Some(_), _) if !entry.signature_is_valid() => {
+                report.issues.push(AuditIssue::BadSignature { index })
+            }
+            (Some(_), false) => {}
+            (Some(_), true) => {
+                let signer = entry.node_key.clone().unwrap_or_default();
+                if !trusted.contains(&signer) || active_key.as_ref() != Some(&signer) {
+                    report.issues.push(AuditIssue::UntrustedSigner {
+                        index,
+                        node_key: signer,
+                    });
+                }
+            }
+        }
+        if entry.mode == KEY_ROTATION_MODE {
+            if let Some((_, new)) = entry.key_rotation() {
+                active_key = Some(new);
+            }
+        }
+        if !entry.hash_is_valid() {
+            report.issues.push(AuditIssue::HashMismatch {
+                index,
+                request_id: entry.request_id,
+            });
+        }
+        match &prev_hash {
+            Some(expected) if *expected != entry.prev_hash => {
+                report.first_broken_link.get_or_insert(index);
+                report.issues.push(match positions.get(&entry.prev_hash) {
+                    Some(&links_to) => AuditIssue::Reordered { index, links_to },
+                    None => AuditIssue::BrokenLink {
+                        index,
+                        expected_prev: expected.clone(),
+                        found_prev: entry.prev_hash.clone(),
+                    },
+                });
+            }
+            _ => {}
+        }
+        for redaction in &entry.redactions {
+            let redacted = entry.version > 0
+                && entry.dag.iter().any(|node| {
+                    node.id == redaction.node_id && node.payload.is_null() && node.salt.is_none()
+                });
+            let signed = redaction.signature_is_valid(&entry.hash)
+                && (node_key.is_none()
+                    || redaction
+                        .node_key
+                        .as_ref()
+                        .is_some_and(|key| trusted.contains(key)));
+            if !redacted || !signed {
+                report.issues.push(AuditIssue::BadRedaction {
+                    index,
+                    node_id: redaction.node_id.clone(),
+                });
+            }
+        }
+        let computed = entry.computed_merkle_root();
+        if computed != entry.merkle_root {
+            report.issues.push(AuditIssue::MerkleMismatch {
+                index,
+                stored: entry.merkle_root.clone(),
+                computed,
+            });
+        }
+        prev_hash = Some(entry.hash.clone());
+        report.head = Some(AuditCheckpoint {
+            index,
+            hash: entry.hash.clone(),
+        });
+    }
+    report.issues.sort_by_key(AuditIssue::index);
+    report
+}
+
+impl AuditIssue {
+    pub fn index(&self) -> usize {
+        match self {
+            AuditIssue::Unparsable { index, .. }
+            | AuditIssue::HashMismatch { index, .. }
+            | AuditIssue::BrokenLink { index, .. }
+            | AuditIssue::Reordered { index, .. }
+            | AuditIssue::MerkleMismatch { index, .. }
+            | AuditIssue::MissingSignature { index }
+            | AuditIssue::BadSignature { index }
+            | AuditIssue::UntrustedSigner { index, .. }
+            | AuditIssue::InvalidRotation { index }
+            | AuditIssue::CheckpointMismatch { index, .. }
+            | AuditIssue::SegmentMismatch { index, .. }
+            | AuditIssue::MissingSegment { index, .. }
+            | AuditIssue::BadSeal { index, .. }
+            | AuditIssue::BadRedaction { index, .. } => *index,
+        }
+    }
+}
+
+use anyhow::{anyhow, bail, Context, Result};
@@ -0,0 +1,310 @@
+//! Encryption at rest for audit entry bodies. An entry's DAG payloads are sealed with
+//! XChaCha20-Poly1305 under a key from the audit keystore, bound to the entry hash. The
+//! payload hashes stay in the clear, so hashes, links and Merkle roots verify without the key;
+//! the salts they are keyed with are sealed along with the payloads.
+
+use crate::audit::{AuditEntry, KEY_ROTATION_MODE};
+use anyhow::{anyhow, bail, Context, Result};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// DAG payloads of an entry, encrypted. `payload_hashes` are what the entry hash commits to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptedBody {
+    pub key_id: String,
+    pub nonce: String,
+    pub ciphertext: String,
+    pub payload_hashes: Vec<String>,
+}
+
+/// What the ciphertext holds: each node's payload and payload salt. Entries sealed before
+/// format 3 hold the payloads alone.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum SealedNodes {
+    Unsalted(Vec<serde_json::Value>),
+    Salted {
+        payloads: Vec<serde_json::Value>,
+        salts: Vec<Option<String>>,
+    },
+}
+
+/// A keystore key: random key material, or a salt to derive the key from the passphrase.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum StoredKey {
+    Raw { key: String },
+    Passphrase { salt: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct KeystoreFile {
+    active: String,
+    keys: BTreeMap<String, StoredKey>,
+}
+
+/// Keys for audit encryption. New entries are sealed under the active key; older keys are
+/// kept to open entries sealed before a rotation until they are re-encrypted.
+#[derive(Clone)]
+pub struct AuditKeyring {
+    path: PathBuf,
+    passphrase: Option<String>,
+    file: KeystoreFile,
+    keys: BTreeMap<String, [u8; 32]>,
+}
+
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0u8; N];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+fn derive(passphrase: &str, salt: &[u8]) -> Result<[u8; 32]> {
+    let mut key = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|err| anyhow!("deriving audit key: {err}"))?;
+    Ok(key)
+}
+
+impl AuditKeyring {
+    /// Keystore from `AXIOMHIVE_AUDIT_KEYSTORE` (default `data/audit.keys`) and
+    /// `AXIOMHIVE_AUDIT_PASSPHRASE`; `None` unless one of them is set.
+    pub fn from_env() -> Result<Option<Self>> {
+        let path = std::env::var("AXIOMHIVE_AUDIT_KEYSTORE").ok();
+        let passphrase = std::env::var("AXIOMHIVE_AUDIT_PASSPHRASE").ok();
+        if path.is_none() && passphrase.is_none() {
+            return Ok(None);
+        }
+        let path = path.unwrap_or_else(|| "data/audit.keys".into());
+        Self::load_or_create(path, passphrase.as_deref()).map(Some)
+    }
+
+    /// Loads the keystore at `path`, creating it with one key on first use: derived from
+    /// `passphrase` with Argon2id if given, random otherwise. The file is written
+    /// owner-only. Passphrase keys cannot be opened without the passphrase.
+    pub fn load_or_create(path: impl AsRef<Path>, passphrase: Option<&str>) -> Result<Self> {
+        let path = path.as_ref();
+        let mut keyring = Self {
+            path: path.to_path_buf(),
+            passphrase: passphrase.map(str::to_string),
+            file: KeystoreFile {
+                active: String::new(),
+                keys: BTreeMap::new(),
+            },
+            keys: BTreeMap::new(),
+        };
+        if !path.exists() {
+            keyring.rotate()?;
+            keyring.save()?;
+            return Ok(keyring);
+        }
+        let raw = fs::read(path).with_context(|| format!("reading keystore {}", path.display()))?;
+        keyring.file = serde_json::from_slice(&raw)
+            .with_context(|| format!("parsing keystore {}", path.display()))?;
+        for (id, stored) in &keyring.file.keys {
+            let key = match (stored, passphrase) {
+                (StoredKey::Raw { key }, _) => hex::decode(key)
+                    .ok()
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .ok_or_else(|| anyhow!("keystore key {id} is not 32 hex bytes"))?,
+                (StoredKey::Passphrase { salt }, Some(passphrase)) => {
+                    derive(passphrase, &hex::decode(salt)?)?
+                }
+                (StoredKey::Passphrase { .. }, None) => continue,
+            };
+            keyring.keys.insert(id.clone(), key);
+        }
+        if !keyring.keys.contains_key(&keyring.file.active) {
+            bail!(
+                "active audit key {} is not available; set AXIOMHIVE_AUDIT_PASSPHRASE",
+                keyring.file.active
+            );
+        }
+        Ok(keyring)
+    }
+
+    pub fn active_key_id(&self) -> &str {
+        &self.file.active
+    }
+
+    pub fn key_ids(&self) -> Vec<String> {
+        self.file.keys.keys().cloned().collect()
+    }
+
+    /// Adds a fresh key of the keyring's kind and makes it active; returns its id. Call
+    /// `save` before sealing entries under it.
+    pub fn rotate(&mut self) -> Result<String> {
+        let id = hex::encode(random_bytes::<8>());
+        let (stored, key) = match &self.passphrase {
+            Some(passphrase) => {
+                let salt = random_bytes::<16>();
+                (
+                    StoredKey::Passphrase {
+                        salt: hex::encode(salt),
+                    },
+                    derive(passphrase, &salt)?,
+                )
+            }
+            None => {
+                let key = random_bytes::<32>();
+                (
+                    StoredKey::Raw {
+                        key: hex::encode(key),
+                    },
+                    key,
+                )
+            }
+        };
+        self.file.keys.insert(id.clone(), stored);
+        self.keys.insert(id.clone(), key);
+        self.file.active = id.clone();
+        Ok(id)
+    }
+
+    /// Drops every key but the active one; returns the dropped ids. Only safe once no
+    /// entry is sealed under them.
+    pub fn retire_inactive(&mut self) -> Vec<String> {
+        let active = self.file.active.clone();
+        let retired: Vec<String> = self
+            .file
+            .keys
+            .keys()
+            .filter(|id| **id != active)
+            .cloned()
+            .collect();
+        self.file.keys.retain(|id, _| *id == active);
+        self.keys.retain(|id, _| *id == active);
+        retired
+    }
+
+    /// Writes the keystore via a temp file so a crash never leaves a partial keystore.
+    pub fn save(&self) -> Result<()> {
+        let path = &self.path;
+        fs::create_dir_all(path.parent().unwrap_or_else(|| Path::new(".")))?;
+        let tmp = path.with_extension("tmp");
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&tmp)?;
+        file.write_all(&serde_json::to_vec_pretty(&self.file)?)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// A 32-byte key for `context` derived from the active key.
+    pub fn derive_key(&self, context: &str) -> [u8; 32] {
+        blake3::derive_key(context, &self.keys[&self.file.active])
+    }
+
+    fn cipher(&self, key_id: &str) -> Result<XChaCha20Poly1305> {
+        let key = self
+            .keys
+            .get(key_id)
+            .ok_or_else(|| anyhow!("audit key {key_id} is not in the keystore"))?;
+        Ok(XChaCha20Poly1305::new(Key::from_slice(key)))
+    }
+
+    /// Encrypts the DAG payloads of a linked entry under the active key, leaving its hash
+    /// unchanged. Format 0 entries and key rotations, whose payloads the verifier reads,
+    /// stay in the clear.
+    pub fn seal(&self, entry: &mut AuditEntry) -> Result<()> {
+        if entry.version == 0 || entry.mode == KEY_ROTATION_MODE {
+            return Ok(());
+        }
+        if entry.encrypted.is_some() {
+            bail!("audit entry {} is already encrypted", entry.request_id);
+        }
+        let payload_hashes = entry
+            .commitments()
+            .into_iter()
+            .map(|commitment| commitment.payload_hash)
+            .collect();
+        let (payloads, salts) = entry
+            .dag
+            .iter_mut()
+            .map(|node| (node.payload.take(), node.salt.take()))
+            .unzip();
+        let nonce = random_bytes::<24>();
+        let ciphertext = self
+            .cipher(&self.file.active)?
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &serde_json::to_vec(&SealedNodes::Salted { payloads, salts })?,
+                    aad: entry.hash.as_bytes(),
+                },
+            )
+            .map_err(|_| anyhow!("encrypting audit entry {}", entry.request_id))?;
+        entry.encrypted = Some(EncryptedBody {
+            key_id: self.file.active.clone(),
+            nonce: hex::encode(nonce),
+            ciphertext: hex::encode(ciphertext),
+            payload_hashes,
+        });
+        Ok(())
+    }
+
+    /// Decrypts an entry's payloads in place and checks them against its payload hashes.
+    /// Entries in the clear are left as they are.
+    pub fn open(&self, entry: &mut AuditEntry) -> Result<()> {
+        let Some(body) = entry.encrypted.take() else {
+            return Ok(());
+        };
+        let nonce = hex::decode(&body.nonce)?;
+        if nonce.len() != 24 {
+            bail!("audit entry {} has a malformed nonce", entry.request_id);
+        }
+        let plaintext = self
+            .cipher(&body.key_id)?
+            .decrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &hex::decode(&body.ciphertext)?,
+                    aad: entry.hash.as_bytes(),
+                },
+            )
+            .map_err(|_| anyhow!("audit entry {} does not decrypt", entry.request_id))?;
+        let (payloads, salts) = match serde_json::from_slice(&plaintext)? {
+            SealedNodes::Salted { payloads, salts } => (payloads, salts),
+            SealedNodes::Unsalted(payloads) => {
+                let salts = vec![None; payloads.len()];
+                (payloads, salts)
+            }
+        };
+        if payloads.len() != entry.dag.len() || salts.len() != entry.dag.len() {
+            bail!(
+                "audit entry {} has the wrong payload count",
+                entry.request_id
+            );
+        }
+        for ((node, payload), salt) in entry.dag.iter_mut().zip(payloads).zip(salts) {
+            node.payload = payload;
+            node.salt = salt;
+        }
+        let opened: Vec<String> = entry
+            .commitments()
+            .into_iter()
+            .map(|commitment| commitment.payload_hash)
+            .collect();
+        if opened != body.payload_hashes {
+            bail!(
+                "audit entry {} payloads do not match their hashes",
+                entry.request_id
+            );
+        }
+        Ok(())
+    }
+}
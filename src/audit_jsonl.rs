@@ -0,0 +1,648 @@
+//! Default audit backend: one JSON line per entry in a local file, rotated into sealed
+//! segments (see `audit_segment`).
+
+use crate::{
+    audit::{verify_log, AuditCheckpoint, AuditEntry, AuditReport, AuditStore, ChainLinker},
+    audit_segment::{
+        compress_segment, load_seals, open_segment, remove_segment_data, rewrite_segment,
+        segment_path, staging_path, write_seal, AuditRotation, SegmentEntry, SegmentSeal,
+        SEGMENT_HEADER_MODE,
+    },
+    dag::dag::RequestDag,
+    node_key::NodeKey,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+pub struct JsonlStore {
+    path: PathBuf,
+    rotation: AuditRotation,
+    writer: Mutex<JsonlWriter>,
+}
+
+/// The single appender for one log file.
+struct JsonlWriter {
+    file: File,
+    chain: ChainState,
+}
+
+/// What is known about the log; `len` is the active segment's size after our last read or
+/// write, so appends by another process are noticed and the state re-read.
+#[derive(Default)]
+struct ChainState {
+    last_hash: String,
+    len: u64,
+    /// Request id and hash of every entry across all segments.
+    request_ids: Vec<Uuid>,
+    hashes: Vec<String>,
+    /// First index recorded for each request id.
+    positions: HashMap<Uuid, usize>,
+    /// Entries in sealed segments; the rest are in the active segment.
+    sealed: usize,
+    next_segment: u64,
+    /// Timestamp of the active segment's first entry.
+    segment_started: Option<DateTime<Utc>>,
+}
+
+impl ChainState {
+    fn push(&mut self, request_id: Uuid, hash: &str) {
+        self.positions
+            .entry(request_id)
+            .or_insert(self.hashes.len());
+        self.request_ids.push(request_id);
+        self.hashes.push(hash.to_string());
+    }
+
+    fn active_entries(&self) -> usize {
+        self.hashes.len() - self.sealed
+    }
+}
+
+/// The fields needed to index an entry.
+#[derive(Deserialize)]
+struct LeafRef {
+    timestamp: String,
+    request_id: Uuid,
+    hash: String,
+}
+
+fn open_active(path: &Path) -> Result<File> {
+    OpenOptions::new()
+        .create(true)
+        .read(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("opening audit log {}", path.display()))
+}
+
+/// Whether `file` is still the active segment at `path`, i.e. no rotation replaced it.
+/// Rotation never leaves `path` missing; if it was deleted from outside, keep appending to
+/// the open file rather than silently starting a new chain.
+#[cfg(unix)]
+fn same_file(path: &Path, file: &File) -> Result<bool> {
+    use std::os::unix::fs::MetadataExt;
+    let current = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(true),
+        Err(err) => return Err(err.into()),
+    };
+    let ours = file.metadata()?;
+    Ok(current.dev() == ours.dev() && current.ino() == ours.ino())
+}
+
+/// Rotation by another process cannot be detected here; run one appender per log.
+#[cfg(not(unix))]
+fn same_file(_path: &Path, _file: &File) -> Result<bool> {
+    Ok(true)
+}
+
+/// Parses the lines of `reader` (whose first line is entry `first_index`) that fall in
+/// `range`.
+fn collect_lines(
+    reader: impl BufRead,
+    first_index: usize,
+    range: &Range<usize>,
+    out: &mut Vec<(usize, AuditEntry)>,
+) -> Result<()> {
+    for (offset, line) in reader.split(b'\n').enumerate() {
+        let index = first_index + offset;
+        if index >= range.end {
+            break;
+        }
+        let line = line?;
+        if index >= range.start {
+            out.push((index, serde_json::from_slice(&line)?));
+        }
+    }
+    Ok(())
+}
+
+/// Where a rewritten copy of the active segment is written before it replaces the log.
+fn rewrite_path(log: &Path) -> PathBuf {
+    let mut path = log.as_os_str().to_owned();
+    path.push(".rewrite");
+    path.into()
+}
+
+/// `raw` lines with the line at each offset in `replacements` swapped for its entry, which
+/// must keep that line's hash.
+fn replace_lines(raw: &[u8], replacements: &HashMap<usize, &AuditEntry>) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(raw.len());
+    let mut found = 0;
+    for (offset, line) in raw
+        .split(|b| *b == b'\n')
+        .filter(|line| !line.is_empty())
+        .enumerate()
+    {
+        match replacements.get(&offset) {
+            Some(entry) => {
+                let current: LeafRef = serde_json::from_slice(line)?;
+                if current.hash != entry.hash {
+                    bail!(
+                        "audit entry {offset} has hash {}, not {}",
+                        current.hash,
+                        entry.hash
+                    );
+                }
+                out.extend(serde_json::to_vec(entry)?);
+                found += 1;
+            }
+            None => out.extend_from_slice(line),
+        }
+        out.push(b'\n');
+    }
+    if found != replacements.len() {
+        bail!("audit segment is missing entries to replace");
+    }
+    Ok(out)
+}
+
+impl JsonlStore {
+    /// Opens the log and resumes its chain from the seals of sealed segments and the active
+    /// segment. A torn final line or interrupted rotation left by a crash is repaired; any
+    /// other corruption at the head of the chain is an error.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        fs::create_dir_all(path.parent().unwrap_or_else(|| Path::new(".")))?;
+        let mut file = open_active(&path)?;
+
+        Self::lock_current(&path, &mut file)?;
+        let resumed = Self::resume(&path, &mut file);
+        file.unlock()?;
+        let chain = resumed?;
+
+        Ok(Self {
+            path,
+            rotation: AuditRotation::default(),
+            writer: Mutex::new(JsonlWriter { file, chain }),
+        })
+    }
+
+    /// Seals the active segment once it reaches the size or age limit in `rotation`.
+    pub fn with_rotation(self, rotation: AuditRotation) -> Self {
+        Self { rotation, ..self }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Locks the active segment, following any rotation that replaced `file` while we
+    /// waited. Returns whether `file` was reopened.
+    fn lock_current(path: &Path, file: &mut File) -> Result<bool> {
+        let mut reopened = false;
+        loop {
+            file.lock()?;
+            if same_file(path, file)? {
+                return Ok(reopened);
+            }
+            *file = open_active(path)?;
+            reopened = true;
+        }
+    }
+
+    /// Reads the seals and the active segment's head and indexes every entry; must be called
+    /// with the file lock held.
+    fn resume(path: &Path, file: &mut File) -> Result<ChainState> {
+        let seals = load_seals(path)?;
+        let mut chain = ChainState::default();
+        for seal in &seals {
+            if seal.first_index != chain.hashes.len() {
+                bail!(
+                    "audit segment {} starts at entry {} but {} entries precede it",
+                    seal.segment,
+                    seal.first_index,
+                    chain.hashes.len()
+                );
+            }
+            for entry in &seal.entries {
+                chain.push(entry.request_id, &entry.hash);
+            }
+            chain.last_hash = seal.final_hash.clone();
+        }
+        chain.sealed = chain.hashes.len();
+        chain.next_segment = seals.last().map_or(1, |seal| seal.segment + 1);
+
+        let mut raw = Vec::new();
+        let mut reader = &*file;
+        reader.seek(SeekFrom::Start(0))?;
+        reader.read_to_end(&mut raw)?;
+
+        let complete = raw.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1);
+        let mut len = raw.len() as u64;
+        let mut head = None;
+        if complete < raw.len() {
+            match serde_json::from_slice::<AuditEntry>(&raw[complete..]) {
+                Ok(entry) if entry.hash_is_valid() => {
+                    warn!(path = %path.display(), "completing audit line missing its newline");
+                    file.write_all(b"\n")?;
+                    file.sync_data()?;
+                    len += 1;
+                    head = Some(entry);
+                }
+                _ => {
+                    warn!(
+                        path = %path.display(),
+                        bytes = raw.len() - complete,
+                        "truncating torn final audit line"
+                    );
+                    file.set_len(complete as u64)?;
+                    file.sync_data()?;
+                    len = complete as u64;
+                }
+            }
+        }
+
+        for (index, line) in raw[..complete]
+            .split(|b| *b == b'\n')
+            .filter(|line| !line.is_empty())
+            .enumerate()
+        {
+            let leaf: LeafRef = serde_json::from_slice(line).with_context(|| {
+                format!(
+                    "audit log {} entry {index} is corrupt; cannot index the chain",
+                    path.display()
+                )
+            })?;
+            if index == 0 {
+                chain.segment_started = DateTime::parse_from_rfc3339(&leaf.timestamp)
+                    .ok()
+                    .map(|at| at.with_timezone(&Utc));
+            }
+            chain.push(leaf.request_id, &leaf.hash);
+        }
+        if let Some(entry) = &head {
+            chain.push(entry.request_id, &entry.hash);
+        }
+
+        let head = match head {
+            Some(entry) => Some(entry),
+            None => match raw[..complete]
+                .split(|b| *b == b'\n')
+                .rfind(|line| !line.is_empty())
+            {
+                Some(line) => {
+                    Some(serde_json::from_slice::<AuditEntry>(line).with_context(|| {
+                        format!(
+                            "audit log {} ends with a corrupt entry; refusing to resume the chain",
+                            path.display()
+                        )
+                    })?)
+                }
+                None => None,
+            },
+        };
+        match head {
+            Some(entry) if !entry.hash_is_valid() => bail!(
+                "audit log {} head entry {} does not match its hash; refusing to resume the chain",
+                path.display(),
+                entry.request_id
+            ),
+            Some(entry) => chain.last_hash = entry.hash,
+            None => {}
+        }
+        chain.len = len;
+
+        let staged = staging_path(path);
+        if staged.exists() {
+            // A rotation crashed. Once its seal is written the staged segment is complete;
+            // before that the rotation is rolled back.
+            let sealed = chain.active_entries() > 0
+                && seals
+                    .last()
+                    .is_some_and(|seal| seal.final_hash == chain.last_hash);
+            if sealed {
+                warn!(path = %path.display(), "completing interrupted audit segment rotation");
+                fs::rename(&staged, path)?;
+                *file = open_active(path)?;
+                Self::lock_current(path, file)?;
+                return Self::resume(path, file);
+            }
+            warn!(path = %path.display(), "rolling back interrupted audit segment rotation");
+            fs::remove_file(&staged)?;
+            remove_segment_data(path, chain.next_segment)?;
+        }
+        Ok(chain)
+    }
+
+    /// Takes the file lock on the active segment and re-reads the log if another process
+    /// appended or rotated since we last looked. The lock is released again on error.
+    fn lock(&self, writer: &mut JsonlWriter) -> Result<()> {
+        let reopened = Self::lock_current(&self.path, &mut writer.file)?;
+        let refreshed = (|| {
+            if reopened || writer.file.metadata()?.len() != writer.chain.len {
+                writer.chain = Self::resume(&self.path, &mut writer.file)?;
+            }
+            Ok(())
+        })();
+        if refreshed.is_err() {
+            writer.file.unlock()?;
+        }
+        refreshed
+    }
+
+    /// Runs `read` with the file locked and the chain up to date.
+    fn locked<T>(&self, read: impl FnOnce(&JsonlWriter) -> Result<T>) -> Result<T> {
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| anyhow!("audit writer poisoned"))?;
+        self.lock(&mut writer)?;
+        let result = read(&writer);
+        writer.file.unlock()?;
+        result
+    }
+
+    /// Rewrites each segment holding one of `entries` once.
+    fn replace_locked(
+        &self,
+        writer: &mut JsonlWriter,
+        entries: &[(usize, AuditEntry)],
+    ) -> Result<()> {
+        let in_range = |range: Range<usize>| -> HashMap<usize, &AuditEntry> {
+            entries
+                .iter()
+                .filter(|(index, _)| range.contains(index))
+                .map(|(index, entry)| (index - range.start, entry))
+                .collect()
+        };
+        let mut replaced = 0;
+        for seal in load_seals(&self.path)? {
+            let replacements = in_range(seal.first_index..seal.first_index + seal.entries.len());
+            if replacements.is_empty() {
+                continue;
+            }
+            let mut raw = Vec::new();
+            open_segment(&self.path, seal.segment)?
+                .ok_or_else(|| anyhow!("audit segment {} was deleted", seal.segment))?
+                .read_to_end(&mut raw)?;
+            rewrite_segment(
+                &self.path,
+                seal.segment,
+                &replace_lines(&raw, &replacements)?,
+            )?;
+            replaced += replacements.len();
+        }
+
+        let replacements = in_range(writer.chain.sealed..usize::MAX);
+        if !replacements.is_empty() {
+            let mut raw = Vec::new();
+            let mut reader = &writer.file;
+            reader.seek(SeekFrom::Start(0))?;
+            reader.read_to_end(&mut raw)?;
+            let rewritten = replace_lines(&raw, &replacements)?;
+
+            let staged = rewrite_path(&self.path);
+            let mut file = File::create(&staged)?;
+            file.write_all(&rewritten)?;
+            file.sync_all()?;
+            drop(file);
+            fs::rename(&staged, &self.path)?;
+            let file = open_active(&self.path)?;
+            file.lock()?;
+            writer.file = file;
+            writer.chain.len = rewritten.len() as u64;
+            replaced += replacements.len();
+        }
+        if replaced != entries.len() {
+            bail!("some audit entries to replace are not in the log");
+        }
+        Ok(())
+    }
+
+    fn rotation_due(&self, chain: &ChainState) -> bool {
+        if chain.active_entries() == 0 {
+            return false;
+        }
+        let too_big = self.rotation.max_bytes.is_some_and(|max| chain.len >= max);
+        let too_old = match (self.rotation.max_age, chain.segment_started) {
+            (Some(max), Some(started)) => {
+                (Utc::now() - started).to_std().is_ok_and(|age| age >= max)
+            }
+            _ => false,
+        };
+        too_big || too_old
+    }
+
+    /// Seals the active segment and starts a new one whose header entry chains to the sealed
+    /// segment's final hash. The new segment is staged next to the log with its header
+    /// written before the sealed one is renamed, so the log path always holds a segment.
+    fn rotate_segment(&self, writer: &mut JsonlWriter, linker: &ChainLinker) -> Result<()> {
+        let segment = writer.chain.next_segment;
+        let chain = &writer.chain;
+        let mut seal = SegmentSeal {
+            segment,
+            first_index: chain.sealed,
+            prev_hash: match chain.sealed {
+                0 => String::new(),
+                n => chain.hashes[n - 1].clone(),
+            },
+            final_hash: chain.last_hash.clone(),
+            entries: chain.request_ids[chain.sealed..]
+                .iter()
+                .zip(&chain.hashes[chain.sealed..])
+                .map(|(request_id, hash)| SegmentEntry {
+                    request_id: *request_id,
+                    hash: hash.clone(),
+                })
+                .collect(),
+            sealed_at: Utc::now().to_rfc3339(),
+            deleted_at: None,
+            node_key: None,
+            signature: None,
+        };
+        seal.sign(linker.node_key());
+
+        let staged = staging_path(&self.path);
+        let _ = fs::remove_file(&staged);
+        let next = OpenOptions::new()
+            .create_new(true)
+            .read(true)
+            .append(true)
+            .open(&staged)?;
+        next.lock()?;
+        let sealed_file = std::mem::replace(&mut writer.file, next);
+        writer.chain.sealed = writer.chain.hashes.len();
+        writer.chain.next_segment = segment + 1;
+        writer.chain.len = 0;
+        writer.chain.segment_started = None;
+
+        let mut dag = RequestDag::default();
+        dag.add_node(
+            SEGMENT_HEADER_MODE,
+            serde_json::json!({
+                "segment": segment + 1,
+                "previous_segment": segment,
+                "previous_final_hash": seal.final_hash,
+                "previous_entries": seal.entries.len(),
+            }),
+            &[],
+        )?;
+        let header = AuditEntry::unlinked(Uuid::new_v4(), SEGMENT_HEADER_MODE, &dag);
+        Self::write_entry(writer, linker, header)?;
+
+        let sealed_path = segment_path(&self.path, segment);
+        let _ = fs::remove_file(&sealed_path);
+        fs::hard_link(&self.path, &sealed_path)?;
+        write_seal(&self.path, &seal)?;
+        fs::rename(&staged, &self.path)?;
+        drop(sealed_file);
+        info!(path = %self.path.display(), segment, entries = seal.entries.len(), "sealed audit segment");
+
+        if self.rotation.compress {
+            if let Err(err) = compress_segment(&self.path, segment) {
+                warn!(segment, error = %err, "leaving audit segment uncompressed");
+            }
+        }
+        self.apply_retention(linker.node_key())
+    }
+
+    /// Deletes the entries of sealed segments past the retention limits, leaving their seals
+    /// as re-signed tombstones.
+    fn apply_retention(&self, key: Option<&NodeKey>) -> Result<()> {
+        let rotation = &self.rotation;
+        if rotation.retain_segments.is_none() && rotation.retain_age.is_none() {
+            return Ok(());
+        }
+        let live: Vec<SegmentSeal> = load_seals(&self.path)?
+            .into_iter()
+            .filter(|seal| !seal.is_tombstone())
+            .collect();
+        let excess = rotation
+            .retain_segments
+            .map_or(0, |keep| live.len().saturating_sub(keep));
+        let cutoff = rotation
+            .retain_age
+            .and_then(|age| chrono::Duration::from_std(age).ok())
+            .map(|age| Utc::now() - age);
+        for (position, mut seal) in live.into_iter().enumerate() {
+            let expired = cutoff.is_some_and(|cutoff| {
+                DateTime::parse_from_rfc3339(&seal.sealed_at).is_ok_and(|at| at < cutoff)
+            });
+            if position >= excess && !expired {
+                continue;
+            }
+            seal.deleted_at = Some(Utc::now().to_rfc3339());
+            seal.sign(key);
+            write_seal(&self.path, &seal)?;
+            remove_segment_data(&self.path, seal.segment)?;
+            info!(
+                segment = seal.segment,
+                "deleted audit segment, kept tombstone"
+            );
+        }
+        Ok(())
+    }
+
+    fn write_entry(
+        writer: &mut JsonlWriter,
+        linker: &ChainLinker,
+        entry: AuditEntry,
+    ) -> Result<AuditEntry> {
+        let entry = linker.link(entry, &writer.chain.last_hash)?;
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+        writer.file.write_all(&line)?;
+        writer.file.sync_data()?;
+
+        writer.chain.last_hash = entry.hash.clone();
+        writer.chain.len += line.len() as u64;
+        writer.chain.push(entry.request_id, &entry.hash);
+        if writer.chain.segment_started.is_none() {
+            writer.chain.segment_started = Some(Utc::now());
+        }
+        Ok(entry)
+    }
+}
+
+impl AuditStore for JsonlStore {
+    fn append(&self, entry: AuditEntry, linker: &ChainLinker) -> Result<AuditEntry> {
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| anyhow!("audit writer poisoned"))?;
+        self.lock(&mut writer)?;
+        let result = (|| {
+            if self.rotation_due(&writer.chain) {
+                self.rotate_segment(&mut writer, linker)?;
+            }
+            Self::write_entry(&mut writer, linker, entry)
+        })();
+        writer.file.unlock()?;
+        result
+    }
+
+    fn head(&self) -> Result<Option<AuditCheckpoint>> {
+        self.locked(|writer| {
+            Ok(writer.chain.hashes.last().map(|hash| AuditCheckpoint {
+                index: writer.chain.hashes.len() - 1,
+                hash: hash.clone(),
+            }))
+        })
+    }
+
+    fn position(&self, request_id: Uuid) -> Result<Option<usize>> {
+        self.locked(|writer| Ok(writer.chain.positions.get(&request_id).copied()))
+    }
+
+    fn scan(&self, range: Range<usize>) -> Result<Vec<(usize, AuditEntry)>> {
+        self.locked(|writer| {
+            let mut out = Vec::new();
+            for seal in load_seals(&self.path)? {
+                let end = seal.first_index + seal.entries.len();
+                if end <= range.start || seal.first_index >= range.end {
+                    continue;
+                }
+                if let Some(reader) = open_segment(&self.path, seal.segment)? {
+                    collect_lines(reader, seal.first_index, &range, &mut out)?;
+                }
+            }
+            if writer.chain.sealed < range.end {
+                let active = BufReader::new(File::open(&self.path)?);
+                collect_lines(active, writer.chain.sealed, &range, &mut out)?;
+            }
+            Ok(out)
+        })
+    }
+
+    fn replace(&self, entries: &[(usize, AuditEntry)]) -> Result<()> {
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| anyhow!("audit writer poisoned"))?;
+        self.lock(&mut writer)?;
+        let result = self.replace_locked(&mut writer, entries);
+        writer.file.unlock()?;
+        result
+    }
+
+    fn hashes(&self) -> Result<Vec<String>> {
+        self.locked(|writer| Ok(writer.chain.hashes.clone()))
+    }
+
+    fn hashes_from(&self, start: usize) -> Result<Vec<String>> {
+        self.locked(|writer| {
+            let hashes = &writer.chain.hashes;
+            Ok(hashes[start.min(hashes.len())..].to_vec())
+        })
+    }
+
+    fn verify(
+        &self,
+        from: Option<&AuditCheckpoint>,
+        node_key: Option<&str>,
+    ) -> Result<AuditReport> {
+        verify_log(&self.path, from, node_key)
+    }
+}
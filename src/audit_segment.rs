@@ -0,0 +1,227 @@
+//! Sealed segments of the audit log. The active segment lives at the configured log path;
+//! when it is sealed it is renamed to `<stem>.<segment>.<ext>` next to a signed
+//! `<stem>.<segment>.seal.json` manifest, and the new active segment opens with a header
+//! entry chained to the sealed segment's final hash.
+
+use crate::node_key::{verify_signature, NodeKey};
+use anyhow::{Context, Result};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{self, File},
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use uuid::Uuid;
+
+/// Mode of the first entry of every segment after the first.
+pub const SEGMENT_HEADER_MODE: &str = "segment_header";
+
+/// When the active segment is sealed and how long sealed segments are kept. Rotation is
+/// off unless `max_bytes` or `max_age` is set.
+#[derive(Debug, Clone, Default)]
+pub struct AuditRotation {
+    pub max_bytes: Option<u64>,
+    pub max_age: Option<Duration>,
+    /// Gzip sealed segments.
+    pub compress: bool,
+    /// Keep at most this many sealed segments; older ones become tombstones.
+    pub retain_segments: Option<usize>,
+    /// Tombstone sealed segments older than this.
+    pub retain_age: Option<Duration>,
+}
+
+impl AuditRotation {
+    pub fn is_enabled(&self) -> bool {
+        self.max_bytes.is_some() || self.max_age.is_some()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SegmentEntry {
+    pub request_id: Uuid,
+    pub hash: String,
+}
+
+/// Manifest of a sealed segment. Once retention deletes the segment's entries it stays
+/// behind as a tombstone: the entry hashes still link the chain and feed the Merkle tree.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SegmentSeal {
+    pub segment: u64,
+    /// Log-wide index of the segment's first entry.
+    pub first_index: usize,
+    /// Hash the segment's first entry chains to.
+    pub prev_hash: String,
+    pub final_hash: String,
+    pub entries: Vec<SegmentEntry>,
+    pub sealed_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<String>,
+    pub node_key: Option<String>,
+    pub signature: Option<String>,
+}
+
+impl SegmentSeal {
+    pub fn is_tombstone(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    fn message(&self) -> String {
+        let unsigned = SegmentSeal {
+            signature: None,
+            ..self.clone()
+        };
+        let digest = blake3::hash(&serde_json::to_vec(&unsigned).unwrap_or_default());
+        format!("axiomhive segment seal v1:{}", digest.to_hex())
+    }
+
+    pub fn sign(&mut self, key: Option<&NodeKey>) {
+        self.node_key = key.map(NodeKey::public_key_hex);
+        self.signature = None;
+        self.signature = key.map(|key| key.sign(self.message().as_bytes()));
+    }
+
+    pub fn signature_is_valid(&self) -> bool {
+        match (&self.node_key, &self.signature) {
+            (Some(key), Some(signature)) => {
+                verify_signature(key, self.message().as_bytes(), signature)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn sibling(log: &Path, middle: &str) -> PathBuf {
+    let stem = log.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match log.extension() {
+        Some(ext) => format!("{stem}.{middle}.{}", ext.to_string_lossy()),
+        None => format!("{stem}.{middle}"),
+    };
+    log.with_file_name(name)
+}
+
+pub fn segment_path(log: &Path, segment: u64) -> PathBuf {
+    sibling(log, &format!("{segment:06}"))
+}
+
+pub fn compressed_path(log: &Path, segment: u64) -> PathBuf {
+    let mut path = segment_path(log, segment).into_os_string();
+    path.push(".gz");
+    path.into()
+}
+
+pub fn seal_path(log: &Path, segment: u64) -> PathBuf {
+    let stem = log.file_stem().unwrap_or_default().to_string_lossy();
+    log.with_file_name(format!("{stem}.{segment:06}.seal.json"))
+}
+
+/// Where the next active segment is staged while a rotation is in progress.
+pub fn staging_path(log: &Path) -> PathBuf {
+    let mut path = log.as_os_str().to_owned();
+    path.push(".next");
+    path.into()
+}
+
+/// Every seal and tombstone of `log`, oldest first.
+pub fn load_seals(log: &Path) -> Result<Vec<SegmentSeal>> {
+    let dir = match log.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let prefix = format!("{}.", log.file_stem().unwrap_or_default().to_string_lossy());
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err.into()),
+    };
+    let mut seals = Vec::new();
+    for entry in entries {
+        let name = entry?.file_name().to_string_lossy().into_owned();
+        let Some(segment) = name
+            .strip_prefix(&prefix)
+            .and_then(|rest| rest.strip_suffix(".seal.json"))
+            .and_then(|number| number.parse::<u64>().ok())
+        else {
+            continue;
+        };
+        let path = seal_path(log, segment);
+        let seal: SegmentSeal = serde_json::from_slice(&fs::read(&path)?)
+            .with_context(|| format!("reading audit seal {}", path.display()))?;
+        seals.push(seal);
+    }
+    seals.sort_by_key(|seal| seal.segment);
+    Ok(seals)
+}
+
+/// Writes a seal via a temp file so readers never see a partial manifest.
+pub fn write_seal(log: &Path, seal: &SegmentSeal) -> Result<()> {
+    let path = seal_path(log, seal.segment);
+    let tmp = path.with_extension("tmp");
+    let mut file = File::create(&tmp)?;
+    file.write_all(&serde_json::to_vec_pretty(seal)?)?;
+    file.sync_all()?;
+    fs::rename(&tmp, &path)?;
+    Ok(())
+}
+
+/// Reader over a sealed segment's lines, compressed or not; `None` once it is deleted.
+pub fn open_segment(log: &Path, segment: u64) -> Result<Option<Box<dyn BufRead>>> {
+    match File::open(compressed_path(log, segment)) {
+        Ok(file) => return Ok(Some(Box::new(BufReader::new(GzDecoder::new(file))))),
+        Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+        Err(_) => {}
+    }
+    match File::open(segment_path(log, segment)) {
+        Ok(file) => Ok(Some(Box::new(BufReader::new(file)))),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Replaces a sealed segment with its gzip.
+pub fn compress_segment(log: &Path, segment: u64) -> Result<()> {
+    let plain = segment_path(log, segment);
+    let target = compressed_path(log, segment);
+    let tmp = target.with_extension("tmp");
+    let mut encoder = GzEncoder::new(File::create(&tmp)?, Compression::default());
+    io::copy(&mut File::open(&plain)?, &mut encoder)?;
+    encoder.finish()?.sync_all()?;
+    fs::rename(&tmp, &target)?;
+    fs::remove_file(&plain)?;
+    Ok(())
+}
+
+/// Replaces a sealed segment's lines with `data`, keeping it compressed if it was.
+pub fn rewrite_segment(log: &Path, segment: u64, data: &[u8]) -> Result<()> {
+    let compressed = compressed_path(log, segment);
+    let target = if compressed.exists() {
+        compressed
+    } else {
+        segment_path(log, segment)
+    };
+    let tmp = target.with_extension("tmp");
+    let file = File::create(&tmp)?;
+    let file = if target == compressed_path(log, segment) {
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        encoder.write_all(data)?;
+        encoder.finish()?
+    } else {
+        let mut file = file;
+        file.write_all(data)?;
+        file
+    };
+    file.sync_all()?;
+    fs::rename(&tmp, &target)?;
+    Ok(())
+}
+
+pub fn remove_segment_data(log: &Path, segment: u64) -> Result<()> {
+    for path in [segment_path(log, segment), compressed_path(log, segment)] {
+        match fs::remove_file(&path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err.into()),
+            _ => {}
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,164 @@
+//! Audit backend on an embedded sled database, with entries keyed by index and a request
+//! id index for lookups. sled locks the database to one process; the CLI cannot open it
+//! while the server is running.
+
+use crate::audit::{
+    check_chain, AuditCheckpoint, AuditEntry, AuditReport, AuditStore, ChainLinker, ChainScan,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use sled::{
+    transaction::{ConflictableTransactionResult, TransactionError},
+    Transactional, Tree,
+};
+use std::{ops::Range, path::Path, sync::Mutex};
+use uuid::Uuid;
+
+pub struct SledStore {
+    db: sled::Db,
+    /// Entry JSON by index.
+    entries: Tree,
+    /// Entry hash by index, so the chain head and Merkle leaves are read without parsing.
+    hashes: Tree,
+    /// First index by request id.
+    requests: Tree,
+    append: Mutex<()>,
+}
+
+fn key(index: usize) -> [u8; 8] {
+    (index as u64).to_be_bytes()
+}
+
+fn index(key: &[u8]) -> Result<usize> {
+    let bytes: [u8; 8] = key
+        .try_into()
+        .map_err(|_| anyhow!("audit index key has {} bytes", key.len()))?;
+    Ok(u64::from_be_bytes(bytes) as usize)
+}
+
+impl SledStore {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let db = sled::open(path)
+            .with_context(|| format!("opening audit database {}", path.display()))?;
+        Ok(Self {
+            entries: db.open_tree("entries")?,
+            hashes: db.open_tree("hashes")?,
+            requests: db.open_tree("requests")?,
+            db,
+            append: Mutex::new(()),
+        })
+    }
+}
+
+impl AuditStore for SledStore {
+    fn append(&self, entry: AuditEntry, linker: &ChainLinker) -> Result<AuditEntry> {
+        let _guard = self
+            .append
+            .lock()
+            .map_err(|_| anyhow!("audit writer poisoned"))?;
+        let (next, prev_hash) = match self.head()? {
+            Some(head) => (head.index + 1, head.hash),
+            None => (0, String::new()),
+        };
+        let entry = linker.link(entry, &prev_hash)?;
+        let json = serde_json::to_vec(&entry)?;
+
+        (&self.entries, &self.hashes, &self.requests)
+            .transaction(
+                |(entries, hashes, requests)| -> ConflictableTransactionResult<(), ()> {
+                    entries.insert(&key(next), json.as_slice())?;
+                    hashes.insert(&key(next), entry.hash.as_bytes())?;
+                    if requests.get(entry.request_id.as_bytes())?.is_none() {
+                        requests.insert(entry.request_id.as_bytes(), &key(next))?;
+                    }
+                    Ok(())
+                },
+            )
+            .map_err(|err: TransactionError<()>| anyhow!("audit append failed: {err:?}"))?;
+        self.db.flush()?;
+        Ok(entry)
+    }
+
+    fn head(&self) -> Result<Option<AuditCheckpoint>> {
+        self.hashes
+            .last()?
+            .map(|(key, hash)| {
+                Ok(AuditCheckpoint {
+                    index: index(&key)?,
+                    hash: String::from_utf8(hash.to_vec())?,
+                })
+            })
+            .transpose()
+    }
+
+    fn position(&self, request_id: Uuid) -> Result<Option<usize>> {
+        self.requests
+            .get(request_id.as_bytes())?
+            .map(|key| index(&key))
+            .transpose()
+    }
+
+    fn scan(&self, range: Range<usize>) -> Result<Vec<(usize, AuditEntry)>> {
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.entries
+            .range(key(range.start)..key(range.end))
+            .map(|item| {
+                let (key, json) = item?;
+                Ok((index(&key)?, serde_json::from_slice(&json)?))
+            })
+            .collect()
+    }
+
+    fn replace(&self, entries: &[(usize, AuditEntry)]) -> Result<()> {
+        let _guard = self
+            .append
+            .lock()
+            .map_err(|_| anyhow!("audit writer poisoned"))?;
+        let mut batch = sled::Batch::default();
+        for (index, entry) in entries {
+            match self.hashes.get(key(*index))? {
+                Some(hash) if hash.as_ref() == entry.hash.as_bytes() => {}
+                Some(_) => bail!("audit entry {index} has a different hash"),
+                None => bail!("no audit entry {index}"),
+            }
+            batch.insert(&key(*index), serde_json::to_vec(entry)?);
+        }
+        self.entries.apply_batch(batch)?;
+        self.db.flush()?;
+        Ok(())
+    }
+
+    fn hashes(&self) -> Result<Vec<String>> {
+        self.hashes
+            .iter()
+            .values()
+            .map(|hash| Ok(String::from_utf8(hash?.to_vec())?))
+            .collect()
+    }
+
+    fn hashes_from(&self, start: usize) -> Result<Vec<String>> {
+        self.hashes
+            .range(key(start)..)
+            .values()
+            .map(|hash| Ok(String::from_utf8(hash?.to_vec())?))
+            .collect()
+    }
+
+    fn verify(
+        &self,
+        from: Option<&AuditCheckpoint>,
+        node_key: Option<&str>,
+    ) -> Result<AuditReport> {
+        let mut scan = ChainScan::new(from);
+        for item in self.entries.iter() {
+            let (key, json) = item?;
+            while scan.len() < index(&key)? {
+                scan.push_missing();
+            }
+            scan.push_raw(&json);
+        }
+        Ok(check_chain(scan, from, node_key))
+    }
+}
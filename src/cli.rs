@@ -0,0 +1,216 @@
+use crate::{
+    audit::{verify_log, AuditBackend, AuditCheckpoint, AuditLog},
+    audit_crypt::AuditKeyring,
+    audit_segment::AuditRotation,
+    credential::{verify_credential, AuditCredential},
+    node_key::NodeKey,
+};
+use anyhow::{anyhow, bail, Result};
+use std::{fs, sync::Arc};
+
+/// Runs a subcommand named in `args` (without the program name); `None` means no
+/// subcommand was given and the node should serve.
+pub fn dispatch(args: &[String]) -> Option<Result<()>> {
+    let (command, rest) = args.split_first()?;
+    Some(match command.as_str() {
+        "verify-audit" => verify_audit(rest),
+        "node-key" => node_key(),
+        "rotate-node-key" => rotate_node_key(),
+        "rotate-audit-key" => rotate_audit_key(rest),
+        "export-credential" => export_credential(rest),
+        "verify-credential" => verify_credential_file(rest),
+        other => Err(anyhow!("unknown subcommand: {other}")),
+    })
+}
+
+fn audit_backend() -> Result<AuditBackend> {
+    AuditBackend::parse(
+        &std::env::var("AXIOMHIVE_AUDIT_BACKEND").unwrap_or_else(|_| "jsonl".into()),
+    )
+}
+
+fn audit_path(backend: AuditBackend) -> String {
+    std::env::var("AXIOMHIVE_AUDIT_PATH").unwrap_or_else(|_| backend.default_path().into())
+}
+
+fn node_key_path() -> String {
+    std::env::var("AXIOMHIVE_NODE_KEY_PATH").unwrap_or_else(|_| "data/node.key".into())
+}
+
+/// Splits `--flag value` pairs.
+fn flags(args: &[String]) -> Result<Vec<(&str, &str)>> {
+    args.chunks(2)
+        .map(|pair| match pair {
+            [flag, value] if flag.starts_with("--") => Ok((flag.as_str(), value.as_str())),
+            [flag, ..] => Err(anyhow!("expected `--flag value`, got {flag}")),
+            [] => unreachable!(),
+        })
+        .collect()
+}
+
+/// `verify-audit [--backend <jsonl|sled>] [--path <file>] [--node-key <hex>]
+/// [--from-index <n> --from-hash <hex>]`
+fn verify_audit(args: &[String]) -> Result<()> {
+    let mut backend = audit_backend()?;
+    let mut path = None;
+    let (mut index, mut hash, mut node_key) = (None, None, None);
+    for (flag, value) in flags(args)? {
+        match flag {
+            "--backend" => backend = AuditBackend::parse(value)?,
+            "--path" => path = Some(value.to_string()),
+            "--node-key" => node_key = Some(value.to_string()),
+            "--from-index" => index = Some(value.parse()?),
+            "--from-hash" => hash = Some(value.to_string()),
+            other => bail!("unknown flag: {other}"),
+        }
+    }
+    let from = match (index, hash) {
+        (Some(index), Some(hash)) => Some(AuditCheckpoint { index, hash }),
+        (None, None) => None,
+        _ => bail!("--from-index and --from-hash must be given together"),
+    };
+
+    let path = path.unwrap_or_else(|| audit_path(backend));
+    // A JSONL log is read as is; opening it as a store would repair a torn tail first.
+    let report = match backend {
+        AuditBackend::Jsonl => verify_log(&path, from.as_ref(), node_key.as_deref())?,
+        AuditBackend::Sled => backend
+            .open(&path, AuditRotation::default())?
+            .verify(from.as_ref(), node_key.as_deref())?,
+    };
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    if !report.is_intact() {
+        bail!(
+            "audit log {path} failed verification with {} issue(s)",
+            report.issues.len()
+        );
+    }
+    Ok(())
+}
+
+/// `node-key`: prints the node's public key, creating the key on first use.
+fn node_key() -> Result<()> {
+    let key = NodeKey::load_or_create(node_key_path())?;
+    println!("{}", serde_json::to_string_pretty(&key.published())?);
+    Ok(())
+}
+
+/// `rotate-node-key`: records a hand-over to a fresh key in the audit log, then replaces
+/// the key file. Run it while the node is stopped.
+fn rotate_node_key() -> Result<()> {
+    let path = node_key_path();
+    let old = Arc::new(NodeKey::load_or_create(&path)?);
+    let backend = audit_backend()?;
+    let store = backend.open(audit_path(backend), AuditRotation::default())?;
+    let log = AuditLog::new(store).with_node_key(old);
+
+    // Park the new key beside the old one first so a crash cannot lose it after the
+    // rotation entry is written.
+    let mut new = NodeKey::generate();
+    let staged = format!("{path}.next");
+    new.save(&staged)?;
+    let entry = log.rotate_key(Arc::new(new))?;
+    fs::rename(&staged, &path)?;
+
+    println!("{}", serde_json::to_string_pretty(&entry)?);
+    Ok(())
+}
+
+/// `rotate-audit-key [--retire true]`: adds a fresh encryption key to the audit keystore,
+/// re-encrypts every stored entry under it, and with `--retire` drops the old keys. Run it
+/// while the node is stopped.
+fn rotate_audit_key(args: &[String]) -> Result<()> {
+    let mut retire = false;
+    for (flag, value) in flags(args)? {
+        match flag {
+            "--retire" => retire = value.parse()?,
+            other => bail!("unknown flag: {other}"),
+        }
+    }
+    let mut keyring = AuditKeyring::from_env()?.ok_or_else(|| {
+        anyhow!(
+            "set AXIOMHIVE_AUDIT_KEYSTORE or AXIOMHIVE_AUDIT_PASSPHRASE to encrypt the audit log"
+        )
+    })?;
+    // Persist the new key before any entry is sealed under it.
+    let key_id = keyring.rotate()?;
+    keyring.save()?;
+
+    let backend = audit_backend()?;
+    let store = backend.open(audit_path(backend), AuditRotation::default())?;
+    let log = AuditLog::new(store).with_keyring(Arc::new(keyring.clone()));
+    let reencrypted = log.reencrypt(1024)?;
+    let retired = if retire {
+        let retired = keyring.retire_inactive();
+        keyring.save()?;
+        retired
+    } else {
+        Vec::new()
+    };
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&serde_json::json!({
+            "active_key": key_id,
+            "reencrypted": reencrypted,
+            "retired": retired,
+        }))?
+    );
+    Ok(())
+}
+
+/// `export-credential --request-id <uuid> [--backend <jsonl|sled>] [--path <file>]`: prints
+/// the request's audit entry as a verifiable credential signed by the node key.
+fn export_credential(args: &[String]) -> Result<()> {
+    let mut backend = audit_backend()?;
+    let (mut path, mut request_id) = (None, None);
+    for (flag, value) in flags(args)? {
+        match flag {
+            "--backend" => backend = AuditBackend::parse(value)?,
+            "--path" => path = Some(value.to_string()),
+            "--request-id" => request_id = Some(value.parse()?),
+            other => bail!("unknown flag: {other}"),
+        }
+    }
+    let request_id = request_id.ok_or_else(|| anyhow!("--request-id is required"))?;
+    let key = Arc::new(NodeKey::load_or_create(node_key_path())?);
+    let store = backend.open(
+        path.unwrap_or_else(|| audit_path(backend)),
+        AuditRotation::default(),
+    )?;
+    // The tree head in the credential must be signed by the issuing key.
+    let mut log = AuditLog::new(store).with_node_key(key.clone());
+    if let Some(keyring) = AuditKeyring::from_env()? {
+        log = log.with_keyring(Arc::new(keyring));
+    }
+    let credential = AuditCredential::export(&log, request_id, &key)?
+        .ok_or_else(|| anyhow!("request {request_id} is not in the audit log"))?;
+    println!("{}", serde_json::to_string_pretty(&credential)?);
+    Ok(())
+}
+
+/// `verify-credential --file <path> [--node-key <hex>]`: checks an exported credential
+/// offline and exits non-zero if it does not verify.
+fn verify_credential_file(args: &[String]) -> Result<()> {
+    let (mut file, mut node_key) = (None, None);
+    for (flag, value) in flags(args)? {
+        match flag {
+            "--file" => file = Some(value.to_string()),
+            "--node-key" => node_key = Some(value.to_string()),
+            other => bail!("unknown flag: {other}"),
+        }
+    }
+    let file = file.ok_or_else(|| anyhow!("--file is required"))?;
+    let credential: AuditCredential = serde_json::from_str(&fs::read_to_string(&file)?)?;
+    let issuer = verify_credential(&credential, node_key.as_deref())?;
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&serde_json::json!({
+            "valid": true,
+            "issuer": issuer,
+            "request_id": credential.credential_subject.audit_entry.request_id,
+            "entry_hash": credential.credential_subject.audit_entry.hash,
+        }))?
+    );
+    Ok(())
+}
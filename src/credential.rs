@@ -0,0 +1,249 @@
+//! Audit entries exported as W3C Verifiable Credentials (Data Model 2.0). The credential
+//! carries the entry, its C0 signature for verified requests, and an inclusion proof
+//! against a signed tree head. It is issued by the node's `did:key` with an
+//! `eddsa-jcs-2022` Data Integrity proof, so it can be checked offline with
+//! `verify_credential`.
+
+use crate::{
+    audit::{AuditEntry, AuditLog},
+    node_key::{verify_signature, NodeKey},
+    transparency::{InclusionProof, SignedTreeHead},
+    verification::axiom_checker::C0Signature,
+};
+use anyhow::{anyhow, bail, Result};
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+pub const CREDENTIALS_CONTEXT: &str = "https://www.w3.org/ns/credentials/v2";
+pub const CREDENTIAL_TYPE: &str = "AuditRecordCredential";
+pub const CRYPTOSUITE: &str = "eddsa-jcs-2022";
+
+/// Multicodec prefix of an ed25519 public key in a `did:key`.
+const ED25519_PUB: [u8; 2] = [0xed, 0x01];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditCredential {
+    #[serde(rename = "@context")]
+    pub context: Vec<String>,
+    pub id: String,
+    #[serde(rename = "type")]
+    pub types: Vec<String>,
+    pub issuer: String,
+    pub valid_from: String,
+    pub credential_subject: AuditSubject,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof: Option<DataIntegrityProof>,
+}
+
+/// The audited request: its entry and where that entry sits in the log.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditSubject {
+    /// `urn:uuid:` of the request.
+    pub id: String,
+    pub audit_entry: AuditEntry,
+    /// Recorded in the entry's `verification` node for verified requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub c0_signature: Option<C0Signature>,
+    pub tree_head: SignedTreeHead,
+    pub inclusion_proof: InclusionProof,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataIntegrityProof {
+    #[serde(rename = "type")]
+    pub proof_type: String,
+    pub cryptosuite: String,
+    pub created: String,
+    pub verification_method: String,
+    pub proof_purpose: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub proof_value: String,
+}
+
+/// `did:key` identifier of a hex ed25519 public key.
+pub fn did_key(public_key: &str) -> Result<String> {
+    let mut bytes = ED25519_PUB.to_vec();
+    bytes.extend(hex::decode(public_key)?);
+    Ok(format!("did:key:z{}", bs58::encode(bytes).into_string()))
+}
+
+/// Hex ed25519 public key named by a `did:key`.
+pub fn did_key_public_key(did: &str) -> Result<String> {
+    let encoded = did
+        .strip_prefix("did:key:z")
+        .ok_or_else(|| anyhow!("{did} is not a base58 did:key"))?;
+    let bytes = bs58::decode(encoded).into_vec()?;
+    match bytes.strip_prefix(&ED25519_PUB) {
+        Some(key) if key.len() == 32 => Ok(hex::encode(key)),
+        _ => bail!("{did} is not an ed25519 did:key"),
+    }
+}
+
+/// The C0 signature committed in a verified entry's `verification` node, if present.
+pub fn recorded_c0_signature(entry: &AuditEntry) -> Option<C0Signature> {
+    let node = entry
+        .dag
+        .iter()
+        .find(|node| node.node_type == "verification")?;
+    serde_json::from_value(node.payload.get("c0_signature")?.clone()).ok()
+}
+
+/// Sorted-key compact JSON, which is JCS (RFC 8785) for the values the node emits.
+fn canonical(value: &impl Serialize) -> Result<Vec<u8>> {
+    Ok(serde_json::to_value(value)?.to_string().into_bytes())
+}
+
+/// The bytes an `eddsa-jcs-2022` proof signs: the hash of the proof options with the
+/// document's context, followed by the hash of the credential without its proof.
+fn signing_input(credential: &AuditCredential, proof: &DataIntegrityProof) -> Result<Vec<u8>> {
+    let document = AuditCredential {
+        proof: None,
+        ..credential.clone()
+    };
+    let mut options = serde_json::to_value(DataIntegrityProof {
+        proof_value: String::new(),
+        ..proof.clone()
+    })?;
+    options["@context"] = serde_json::to_value(&credential.context)?;
+    let mut input = Sha256::digest(canonical(&options)?).to_vec();
+    input.extend(Sha256::digest(canonical(&document)?));
+    Ok(input)
+}
+
+impl AuditCredential {
+    /// Packages `entry` with its inclusion proof and tree head and signs it with `key`.
+    pub fn issue(
+        entry: AuditEntry,
+        tree_head: SignedTreeHead,
+        inclusion_proof: InclusionProof,
+        key: &NodeKey,
+    ) -> Result<Self> {
+        let issuer = did_key(&key.public_key_hex())?;
+        let mut credential = Self {
+            context: vec![CREDENTIALS_CONTEXT.into()],
+            id: format!("urn:uuid:{}", Uuid::new_v4()),
+            types: vec!["VerifiableCredential".into(), CREDENTIAL_TYPE.into()],
+            valid_from: entry.timestamp.clone(),
+            credential_subject: AuditSubject {
+                id: format!("urn:uuid:{}", entry.request_id),
+                c0_signature: recorded_c0_signature(&entry),
+                audit_entry: entry,
+                tree_head,
+                inclusion_proof,
+            },
+            issuer: issuer.clone(),
+            proof: None,
+        };
+        let mut proof = DataIntegrityProof {
+            proof_type: "DataIntegrityProof".into(),
+            cryptosuite: CRYPTOSUITE.into(),
+            created: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            verification_method: format!("{issuer}#{}", &issuer["did:key:".len()..]),
+            proof_purpose: "assertionMethod".into(),
+            proof_value: String::new(),
+        };
+        let signature = hex::decode(key.sign(&signing_input(&credential, &proof)?))?;
+        proof.proof_value = format!("z{}", bs58::encode(signature).into_string());
+        credential.proof = Some(proof);
+        Ok(credential)
+    }
+
+    /// Exports the entry for `request_id` as a credential signed by `key`; `None` if the
+    /// request is not in the log.
+    pub fn export(log: &AuditLog, request_id: Uuid, key: &NodeKey) -> Result<Option<Self>> {
+        let Some(entry) = log.get(request_id)? else {
+            return Ok(None);
+        };
+        let proof = log
+            .inclusion_proof(request_id, None)?
+            .ok_or_else(|| anyhow!("request {request_id} is not in the audit tree"))?;
+        let tree_head = log
+            .tree_head_at(Some(proof.tree_size))?
+            .ok_or_else(|| anyhow!("audit tree shrank"))?;
+        Self::issue(entry, tree_head, proof, key).map(Some)
+    }
+}
+
+/// Checks a credential offline: the issuer's proof, the tree head and inclusion proof that
+/// place the entry in the log, the entry's hash over its contents and `prev_hash`, its
+/// signatures, and that the C0 signature is the one the entry records. With `trusted_key`
+/// (hex), the issuer must be that key. Returns the issuer's hex key.
+pub fn verify_credential(
+    credential: &AuditCredential,
+    trusted_key: Option<&str>,
+) -> Result<String> {
+    if credential.context.first().map(String::as_str) != Some(CREDENTIALS_CONTEXT)
+        || !credential.types.iter().any(|kind| kind == CREDENTIAL_TYPE)
+    {
+        bail!("not an audit record credential");
+    }
+    let issuer = did_key_public_key(&credential.issuer)?;
+    if trusted_key.is_some_and(|trusted| trusted != issuer) {
+        bail!("credential is issued by {issuer}, not the trusted key");
+    }
+    let proof = credential
+        .proof
+        .as_ref()
+        .ok_or_else(|| anyhow!("credential has no proof"))?;
+    if proof.cryptosuite != CRYPTOSUITE
+        || !proof
+            .verification_method
+            .starts_with(&format!("{}#", credential.issuer))
+    {
+        bail!("credential proof is not an {CRYPTOSUITE} proof by its issuer");
+    }
+    let signature = proof
+        .proof_value
+        .strip_prefix('z')
+        .and_then(|value| bs58::decode(value).into_vec().ok())
+        .ok_or_else(|| anyhow!("credential proof value is not base58"))?;
+    if !verify_signature(
+        &issuer,
+        &signing_input(credential, proof)?,
+        &hex::encode(signature),
+    ) {
+        bail!("credential proof signature is invalid");
+    }
+
+    let subject = &credential.credential_subject;
+    let entry = &subject.audit_entry;
+    let inclusion = &subject.inclusion_proof;
+    if !subject.tree_head.verify(&issuer) {
+        bail!("tree head is not signed by the issuer");
+    }
+    if inclusion.entry_hash != entry.hash
+        || inclusion.request_id != entry.request_id
+        || inclusion.tree_size != subject.tree_head.tree_size
+        || !inclusion.verify(&subject.tree_head.root_hash)
+    {
+        bail!("entry is not included in the signed tree head");
+    }
+    if !entry.hash_is_valid() {
+        bail!("entry hash does not match its contents and prev_hash");
+    }
+    if entry.computed_merkle_root() != entry.merkle_root {
+        bail!("entry merkle_root does not match its DAG");
+    }
+    if entry.node_key.is_some() && !entry.signature_is_valid() {
+        bail!("entry signature is invalid");
+    }
+    if entry
+        .redactions
+        .iter()
+        .any(|redaction| !redaction.signature_is_valid(&entry.hash))
+    {
+        bail!("entry has an invalid redaction record");
+    }
+    if let Some(c0) = &subject.c0_signature {
+        let recorded = recorded_c0_signature(entry).map(|recorded| canonical(&recorded));
+        if recorded.transpose()? != Some(canonical(c0)?) {
+            bail!("C0 signature is not the one recorded in the entry");
+        }
+    }
+    Ok(issuer)
+}
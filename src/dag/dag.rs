@@ -1,13 +1,101 @@
 use blake3::Hasher;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use thiserror::Error;
 
-use super::merkletree::MerkleTree;
+use super::merkletree::{MerkleProof, MerkleTree, LEGACY_TREE, TREE_VERSION};
 
 #[derive(Debug, Clone, Serialize)]
 pub struct DagNode {
+    /// Content address from `content_id`. Entries written before ids were derived from
+    /// content carry `{node_type}-{index}` ids instead.
     pub id: String,
     pub node_type: String,
     pub payload: serde_json::Value,
+    /// Ids of the nodes this one depends on. Omitted when empty, so nodes recorded before
+    /// edges existed hash as they did.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parents: Vec<String>,
+    /// Salt the payload hash is keyed with, set by audit entries of format 3. It goes
+    /// wherever the payload goes, so a redacted or encrypted payload cannot be recovered
+    /// by hashing guesses.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub salt: Option<String>,
+}
+
+/// What a DAG node commits to. The payload is represented by its hash, so it can be
+/// redacted later without changing the node's Merkle leaf. Parents are committed too.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeCommitment {
+    pub id: String,
+    pub node_type: String,
+    pub payload_hash: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parents: Vec<String>,
+}
+
+/// A node's commitment with the path that places it under its DAG's Merkle root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NodeProof {
+    pub commitment: NodeCommitment,
+    pub proof: MerkleProof,
+}
+
+impl NodeProof {
+    pub fn verify(&self, merkle_root: &str) -> bool {
+        self.proof
+            .verify(&commitment_leaf(&self.commitment), merkle_root)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum DagError {
+    #[error("node {node} names unknown parent {parent}")]
+    UnknownParent { node: String, parent: String },
+    #[error("dependency cycle through node {0}")]
+    Cycle(String),
+}
+
+impl DagNode {
+    pub fn payload_hash(&self) -> String {
+        match &self.salt {
+            Some(salt) => salted_payload_hash(salt, &self.payload),
+            None => payload_hash(&self.payload),
+        }
+    }
+
+    /// Whether `id` is the content address of the node's type, payload and parents.
+    pub fn id_is_valid(&self) -> bool {
+        self.commitment().id_is_valid()
+    }
+
+    pub fn commitment(&self) -> NodeCommitment {
+        NodeCommitment {
+            id: self.id.clone(),
+            node_type: self.node_type.clone(),
+            payload_hash: self.payload_hash(),
+            parents: self.parents.clone(),
+        }
+    }
+}
+
+impl NodeCommitment {
+    /// Whether `id` is the content address of what the node commits to. Holds for redacted
+    /// nodes too, since the address covers the payload hash rather than the payload.
+    pub fn id_is_valid(&self) -> bool {
+        self.id == content_id(&self.node_type, &self.payload_hash, &self.parents)
+    }
+}
+
+/// Blake3 over the canonical JSON of a node's type, payload hash and parent ids. Equal
+/// computations get equal ids in any DAG, and an id pins down its whole ancestry.
+pub fn content_id(node_type: &str, payload_hash: &str, parents: &[String]) -> String {
+    let content = serde_json::json!({
+        "node_type": node_type,
+        "payload_hash": payload_hash,
+        "parents": parents,
+    });
+    blake3_hex(content.to_string().as_bytes())
 }
 
 #[derive(Debug, Clone, Default, Serialize)]
@@ -16,24 +104,112 @@ pub struct RequestDag {
 }
 
 impl RequestDag {
-    pub fn add_node(&mut self, node_type: &str, payload: serde_json::Value) -> DagNode {
-        let id = format!("{}-{}", node_type, self.nodes.len());
+    /// Appends a node depending on `parents`, which must already be in the DAG. Since a
+    /// node can only point at earlier nodes, a DAG built this way has no cycles. A node
+    /// equal to one already in the DAG is not added again; the existing node is returned.
+    pub fn add_node(
+        &mut self,
+        node_type: &str,
+        payload: serde_json::Value,
+        parents: &[&str],
+    ) -> Result<DagNode, DagError> {
+        if let Some(parent) = parents.iter().find(|parent| self.node(parent).is_none()) {
+            return Err(DagError::UnknownParent {
+                node: node_type.to_string(),
+                parent: parent.to_string(),
+            });
+        }
+        let parents: Vec<String> = parents.iter().map(|parent| parent.to_string()).collect();
+        let id = content_id(node_type, &payload_hash(&payload), &parents);
+        if let Some(existing) = self.node(&id) {
+            return Ok(existing.clone());
+        }
         let node = DagNode {
             id,
             node_type: node_type.to_string(),
             payload,
+            parents,
+            salt: None,
         };
         self.nodes.push(node.clone());
-        node
+        Ok(node)
+    }
+
+    pub fn node(&self, id: &str) -> Option<&DagNode> {
+        self.nodes.iter().find(|node| node.id == id)
+    }
+
+    /// Nodes with every node after all of its parents, ties kept in insertion order.
+    /// Checks the edges too, for DAGs that were not built with `add_node` (e.g. read back
+    /// from an audit entry).
+    pub fn topological_order(&self) -> Result<Vec<&DagNode>, DagError> {
+        let ids: HashSet<&str> = self.nodes.iter().map(|node| node.id.as_str()).collect();
+        let mut pending: HashMap<&str, usize> = HashMap::new();
+        let mut children: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (index, node) in self.nodes.iter().enumerate() {
+            for parent in &node.parents {
+                if !ids.contains(parent.as_str()) {
+                    return Err(DagError::UnknownParent {
+                        node: node.id.clone(),
+                        parent: parent.clone(),
+                    });
+                }
+                children.entry(parent.as_str()).or_default().push(index);
+            }
+            pending.insert(node.id.as_str(), node.parents.len());
+        }
+        let mut ready: Vec<usize> = (0..self.nodes.len())
+            .filter(|index| self.nodes[*index].parents.is_empty())
+            .rev()
+            .collect();
+        let mut order = Vec::with_capacity(self.nodes.len());
+        while let Some(index) = ready.pop() {
+            let node = &self.nodes[index];
+            order.push(node);
+            let mut released = Vec::new();
+            for child in children.get(node.id.as_str()).into_iter().flatten() {
+                let count = pending.entry(self.nodes[*child].id.as_str()).or_default();
+                *count -= 1;
+                if *count == 0 {
+                    released.push(*child);
+                }
+            }
+            ready.extend(released);
+            ready.sort_unstable_by(|a, b| b.cmp(a));
+        }
+        match self.nodes.iter().find(|node| pending[node.id.as_str()] > 0) {
+            Some(node) => Err(DagError::Cycle(node.id.clone())),
+            None => Ok(order),
+        }
     }
 
     pub fn merkle_root(&self) -> Option<String> {
+        let commitments = self
+            .nodes
+            .iter()
+            .map(DagNode::commitment)
+            .collect::<Vec<_>>();
+        commitment_root(&commitments)
+    }
+
+    /// Proof that node `node_id` is under `merkle_root`; `None` if there is no such node.
+    pub fn merkle_proof(&self, node_id: &str) -> Option<NodeProof> {
+        let commitments = self
+            .nodes
+            .iter()
+            .map(DagNode::commitment)
+            .collect::<Vec<_>>();
+        commitment_proof(&commitments, node_id, TREE_VERSION)
+    }
+
+    /// Root over whole nodes, payloads included, as computed before nodes committed to
+    /// payload hashes; audit entries written then still verify against it.
+    pub fn legacy_merkle_root(&self) -> Option<String> {
         if self.nodes.is_empty() {
             return None;
         }
         let leaves = self.nodes.iter().map(|n| node_hash(n)).collect::<Vec<_>>();
-        let tree = MerkleTree::from_leaves(leaves);
-        tree.root()
+        MerkleTree::legacy(leaves).root().ok()
     }
 }
 
@@ -52,6 +228,67 @@ impl DagScheduler {
     }
 }
 
+/// Tree of format `tree_version` over node commitments.
+pub fn commitment_tree(commitments: &[NodeCommitment], tree_version: u32) -> MerkleTree {
+    let leaves = commitments.iter().map(commitment_leaf).collect::<Vec<_>>();
+    MerkleTree {
+        version: tree_version,
+        ..MerkleTree::from_leaves(leaves)
+    }
+}
+
+/// Merkle root over node commitments; `None` for an empty DAG.
+pub fn commitment_root(commitments: &[NodeCommitment]) -> Option<String> {
+    commitment_tree(commitments, TREE_VERSION).root().ok()
+}
+
+/// Root over node commitments in the format 1 tree, as recorded by entries written before
+/// format 2; kept so their `merkle_root` still verifies.
+pub fn legacy_commitment_root(commitments: &[NodeCommitment]) -> Option<String> {
+    commitment_tree(commitments, LEGACY_TREE).root().ok()
+}
+
+/// Proof that the commitment with id `node_id` is under the root of the `tree_version`
+/// tree over `commitments`.
+pub fn commitment_proof(
+    commitments: &[NodeCommitment],
+    node_id: &str,
+    tree_version: u32,
+) -> Option<NodeProof> {
+    let index = commitments.iter().position(|c| c.id == node_id)?;
+    Some(NodeProof {
+        commitment: commitments[index].clone(),
+        proof: commitment_tree(commitments, tree_version)
+            .proof(index)
+            .ok()?,
+    })
+}
+
+pub fn commitment_leaf(commitment: &NodeCommitment) -> String {
+    blake3_hex(&serde_json::to_vec(commitment).unwrap_or_default())
+}
+
+pub fn payload_hash(payload: &serde_json::Value) -> String {
+    blake3_hex(&serde_json::to_vec(payload).unwrap_or_default())
+}
+
+/// Payload hash keyed with a key derived from `salt`.
+pub fn salted_payload_hash(salt: &str, payload: &serde_json::Value) -> String {
+    let key = blake3::hash(salt.as_bytes());
+    blake3::keyed_hash(
+        key.as_bytes(),
+        &serde_json::to_vec(payload).unwrap_or_default(),
+    )
+    .to_hex()
+    .to_string()
+}
+
+fn blake3_hex(bytes: &[u8]) -> String {
+    let mut hasher = Hasher::new();
+    hasher.update(bytes);
+    hasher.finalize().to_hex().to_string()
+}
+
 fn node_hash(node: &DagNode) -> String {
     let serialized =
         serde_json::to_vec(node).unwrap_or_else(|_| format!("{:?}", node.node_type).into_bytes());
@@ -0,0 +1,351 @@
+//! Concurrent execution of a `TaskGraph` on tokio. Each node runs once its parents have
+//! finished, with the parents' outputs as inputs; nodes with no path between them run at
+//! the same time. The executed DAG records what every node produced.
+
+use super::dag::{DagError, DagScheduler, RequestDag};
+use futures::FutureExt;
+use serde_json::Value;
+use std::{
+    any::Any, collections::HashMap, future::Future, panic::AssertUnwindSafe, pin::Pin, sync::Arc,
+    time::Duration,
+};
+use tokio::task::JoinSet;
+
+pub type TaskFuture<E> = Pin<Box<dyn Future<Output = Result<TaskOutput, E>> + Send>>;
+
+/// A unit of work in a `TaskGraph`. Implemented for closures taking the node's inputs and
+/// returning a future; it is called again for each retry.
+pub trait DagTask<E>: Send + Sync + 'static {
+    fn run(&self, inputs: TaskInputs) -> TaskFuture<E>;
+}
+
+impl<E, F, Fut> DagTask<E> for F
+where
+    F: Fn(TaskInputs) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<TaskOutput, E>> + Send + 'static,
+{
+    fn run(&self, inputs: TaskInputs) -> TaskFuture<E> {
+        Box::pin(self(inputs))
+    }
+}
+
+/// Error of a task attempt. Only transient errors are retried; a deterministic failure
+/// would fail the same way again.
+pub trait TaskError {
+    fn is_transient(&self) -> bool;
+}
+
+type Shared = Arc<dyn Any + Send + Sync>;
+
+/// Node index, attempts made, and the last attempt's result.
+type Finished<E> = (usize, u32, Result<TaskOutput, TaskFailure<E>>);
+
+/// What a task produced: `record` becomes the node's payload in the executed DAG (and so
+/// the audit log), while `value` is handed to dependents as is.
+pub struct TaskOutput {
+    record: Value,
+    value: Shared,
+    halt: bool,
+}
+
+impl TaskOutput {
+    pub fn new<T: Any + Send + Sync>(record: Value, value: T) -> Self {
+        Self {
+            record,
+            value: Arc::new(value),
+            halt: false,
+        }
+    }
+
+    /// An output with nothing for dependents beyond its record.
+    pub fn record(record: Value) -> Self {
+        Self::new(record, ())
+    }
+
+    /// Ends the run after this node: running nodes are cancelled and the rest skipped.
+    pub fn halt(self) -> Self {
+        Self { halt: true, ..self }
+    }
+}
+
+/// Outputs of a node's parents.
+#[derive(Clone)]
+pub struct TaskInputs {
+    parents: Vec<(String, Shared)>,
+    attempt: u32,
+}
+
+impl TaskInputs {
+    /// Value of the parent of type `node_type`, if it produced a `T`.
+    pub fn get<T: Any>(&self, node_type: &str) -> Option<&T> {
+        self.parents
+            .iter()
+            .find(|(parent, _)| parent == node_type)
+            .and_then(|(_, value)| value.downcast_ref())
+    }
+
+    /// 0 on the first try, counting up on retries.
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+}
+
+/// Per-node limits: each attempt is cut off after `timeout`, and a node that timed out or
+/// failed with a transient error is retried up to `retries` more times, `backoff` apart.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TaskPolicy {
+    pub timeout: Option<Duration>,
+    pub retries: u32,
+    pub backoff: Duration,
+}
+
+impl TaskPolicy {
+    pub fn timeout(timeout: Duration) -> Self {
+        Self {
+            timeout: Some(timeout),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_retries(self, retries: u32, backoff: Duration) -> Self {
+        Self {
+            retries,
+            backoff,
+            ..self
+        }
+    }
+}
+
+/// A task in a `TaskGraph`. Node ids are derived from what a node produced, so tasks are
+/// named by position until they have run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskId(usize);
+
+struct PlannedTask<E> {
+    node_type: String,
+    parents: Vec<usize>,
+    task: Arc<dyn DagTask<E>>,
+    policy: TaskPolicy,
+}
+
+/// Tasks with their dependencies, in the shape of the `RequestDag` they produce.
+pub struct TaskGraph<E> {
+    tasks: Vec<PlannedTask<E>>,
+}
+
+impl<E> Default for TaskGraph<E> {
+    fn default() -> Self {
+        Self { tasks: Vec::new() }
+    }
+}
+
+impl<E: Send + 'static> TaskGraph<E> {
+    /// Adds a task depending on `parents`, which must already be in the graph.
+    pub fn add_task(
+        &mut self,
+        node_type: &str,
+        parents: &[TaskId],
+        policy: TaskPolicy,
+        task: impl DagTask<E>,
+    ) -> Result<TaskId, DagError> {
+        if let Some(TaskId(parent)) = parents.iter().find(|parent| parent.0 >= self.tasks.len()) {
+            return Err(DagError::UnknownParent {
+                node: node_type.to_string(),
+                parent: format!("task {parent}"),
+            });
+        }
+        self.tasks.push(PlannedTask {
+            node_type: node_type.to_string(),
+            parents: parents.iter().map(|parent| parent.0).collect(),
+            task: Arc::new(task),
+            policy,
+        });
+        Ok(TaskId(self.tasks.len() - 1))
+    }
+}
+
+#[derive(Debug)]
+pub enum TaskFailure<E> {
+    Failed(E),
+    TimedOut(Duration),
+    Panicked,
+}
+
+/// A node that failed on its last attempt. `dag` holds the nodes that had completed.
+#[derive(Debug)]
+pub struct DagRunError<E> {
+    /// Type of the failed node.
+    pub node: String,
+    pub attempts: u32,
+    pub failure: TaskFailure<E>,
+    pub dag: RequestDag,
+    /// Ids in `dag` of the failed node's parents, for recording the failure in place.
+    pub parents: Vec<String>,
+}
+
+/// A finished run. `dag` holds every completed node, in graph order, with its record as
+/// payload and an id derived from it; after a halt, nodes that did not complete are left
+/// out.
+pub struct DagRun {
+    pub dag: RequestDag,
+    /// Node that halted the run, if any.
+    pub halted: Option<String>,
+    values: HashMap<String, Shared>,
+}
+
+impl std::fmt::Debug for DagRun {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DagRun")
+            .field("dag", &self.dag)
+            .field("halted", &self.halted)
+            .finish_non_exhaustive()
+    }
+}
+
+impl DagRun {
+    /// Value produced by the first completed node of type `node_type`, if it is a `T`.
+    pub fn value<T: Any>(&self, node_type: &str) -> Option<&T> {
+        let node = self
+            .dag
+            .nodes
+            .iter()
+            .find(|node| node.node_type == node_type)?;
+        self.values.get(&node.id)?.downcast_ref()
+    }
+}
+
+async fn attempt<E: 'static>(
+    task: &dyn DagTask<E>,
+    inputs: TaskInputs,
+    timeout: Option<Duration>,
+) -> Result<TaskOutput, TaskFailure<E>> {
+    let run = AssertUnwindSafe(task.run(inputs)).catch_unwind();
+    let result = match timeout {
+        Some(limit) => tokio::time::timeout(limit, run)
+            .await
+            .map_err(|_| TaskFailure::TimedOut(limit))?,
+        None => run.await,
+    };
+    result
+        .map_err(|_| TaskFailure::Panicked)?
+        .map_err(TaskFailure::Failed)
+}
+
+impl DagScheduler {
+    /// Runs `graph` to completion, starting each node as soon as its parents are done.
+    /// The first node to fail for good stops the run.
+    pub async fn execute<E: TaskError + Send + 'static>(
+        &self,
+        graph: TaskGraph<E>,
+    ) -> Result<DagRun, DagRunError<E>> {
+        let TaskGraph { tasks } = graph;
+        let mut waiting: Vec<usize> = tasks.iter().map(|task| task.parents.len()).collect();
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); tasks.len()];
+        for (i, task) in tasks.iter().enumerate() {
+            for parent in &task.parents {
+                children[*parent].push(i);
+            }
+        }
+
+        let mut done: Vec<Option<(Value, Shared)>> = (0..tasks.len()).map(|_| None).collect();
+        let mut running: JoinSet<Finished<E>> = JoinSet::new();
+        let mut halted = None;
+        let mut failed = None;
+        let start =
+            |i: usize, done: &[Option<(Value, Shared)>], running: &mut JoinSet<Finished<E>>| {
+                let parents: Vec<(String, Shared)> = tasks[i]
+                    .parents
+                    .iter()
+                    .map(|p| {
+                        let (_, value) = done[*p].as_ref().expect("parents finish first");
+                        (tasks[*p].node_type.clone(), value.clone())
+                    })
+                    .collect();
+                let task = tasks[i].task.clone();
+                let policy = tasks[i].policy;
+                running.spawn(async move {
+                    let mut attempts = 0;
+                    loop {
+                        let inputs = TaskInputs {
+                            parents: parents.clone(),
+                            attempt: attempts,
+                        };
+                        attempts += 1;
+                        let result = attempt(task.as_ref(), inputs, policy.timeout).await;
+                        let transient = match &result {
+                            Err(TaskFailure::TimedOut(_)) => true,
+                            Err(TaskFailure::Failed(err)) => err.is_transient(),
+                            Ok(_) | Err(TaskFailure::Panicked) => false,
+                        };
+                        if !transient || attempts > policy.retries {
+                            return (i, attempts, result);
+                        }
+                        tokio::time::sleep(policy.backoff).await;
+                    }
+                });
+            };
+        for i in (0..tasks.len()).filter(|i| waiting[*i] == 0) {
+            start(i, &done, &mut running);
+        }
+        while let Some(joined) = running.join_next().await {
+            let (i, attempts, result) = joined.expect("executor tasks catch panics");
+            let output = match result {
+                Ok(output) => output,
+                Err(failure) => {
+                    failed = Some((i, attempts, failure));
+                    break;
+                }
+            };
+            done[i] = Some((output.record, output.value));
+            if output.halt {
+                halted = Some(i);
+                break;
+            }
+            for child in children[i].clone() {
+                waiting[child] -= 1;
+                if waiting[child] == 0 {
+                    start(child, &done, &mut running);
+                }
+            }
+        }
+        running.abort_all();
+
+        let mut values = HashMap::new();
+        let mut ids: Vec<Option<String>> = vec![None; tasks.len()];
+        let mut executed = RequestDag::default();
+        for (i, output) in done.into_iter().enumerate() {
+            let Some((record, value)) = output else {
+                continue;
+            };
+            let parents: Vec<&str> = tasks[i]
+                .parents
+                .iter()
+                .filter_map(|p| ids[*p].as_deref())
+                .collect();
+            let node = executed
+                .add_node(&tasks[i].node_type, record, &parents)
+                .expect("parents complete first");
+            values.insert(node.id.clone(), value);
+            ids[i] = Some(node.id);
+        }
+        let halted = halted.and_then(|i| ids[i].clone());
+        match failed {
+            Some((i, attempts, failure)) => Err(DagRunError {
+                node: tasks[i].node_type.clone(),
+                attempts,
+                failure,
+                dag: executed,
+                parents: tasks[i]
+                    .parents
+                    .iter()
+                    .filter_map(|p| ids[*p].clone())
+                    .collect(),
+            }),
+            None => Ok(DagRun {
+                dag: executed,
+                halted,
+                values,
+            }),
+        }
+    }
+}
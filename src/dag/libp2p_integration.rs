@@ -1,4 +1,9 @@
-use libp2p::PeerId;
+use crate::egress::{EgressError, EgressGate, EgressKind};
+use libp2p::{
+    multiaddr::{Multiaddr, Protocol},
+    PeerId,
+};
+use tokio::net::TcpStream;
 
 #[derive(Clone, Debug)]
 pub struct PeerInfo {
@@ -19,4 +24,37 @@ impl P2PNetwork {
     pub fn add_peer(&mut self, peer: PeerInfo) {
         self.peers.push(peer);
     }
+
+    /// Dials a peer through the egress gate; `address` is a multiaddr or `host:port`.
+    pub async fn dial(
+        &self,
+        gate: &EgressGate,
+        peer: &PeerInfo,
+        request_allows: bool,
+    ) -> Result<TcpStream, EgressError> {
+        let target = socket_target(&peer.address)
+            .ok_or_else(|| EgressError::InvalidTarget(peer.address.clone()))?;
+        gate.connect(EgressKind::P2pDial, &target, request_allows)
+            .await
+    }
+}
+
+fn socket_target(address: &str) -> Option<String> {
+    if !address.starts_with('/') {
+        return Some(address.to_string());
+    }
+    let addr: Multiaddr = address.parse().ok()?;
+    let (mut host, mut port) = (None, None);
+    for protocol in addr.iter() {
+        match protocol {
+            Protocol::Ip4(ip) => host = Some(ip.to_string()),
+            Protocol::Ip6(ip) => host = Some(format!("[{ip}]")),
+            Protocol::Dns(name) | Protocol::Dns4(name) | Protocol::Dns6(name) => {
+                host = Some(name.to_string())
+            }
+            Protocol::Tcp(p) => port = Some(p),
+            _ => {}
+        }
+    }
+    Some(format!("{}:{}", host?, port?))
 }
@@ -1,38 +1,287 @@
+//! Merkle trees over DAG node leaves. Format 2 follows RFC 6962: leaves and interior nodes
+//! are hashed under distinct prefixes, the tree splits at the largest power of two below
+//! its size, and the root commits to the format and leaf count. Format 1, which hashed
+//! leaves and pairs alike and promoted an odd node unchanged, is kept to verify roots
+//! recorded with it. Format 2 trees take their shape and audit paths from `transparency`.
+
+use crate::transparency::{path_root, path_with, root_with, sibling_sides, TreeHasher};
 use blake3::Hasher;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Pairwise hashing without domain separation or leaf count.
+pub const LEGACY_TREE: u32 = 1;
+/// Tree format of new roots.
+pub const TREE_VERSION: u32 = 2;
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+const ROOT_PREFIX: u8 = 0x02;
 
 pub struct MerkleNode {
     pub hash: String,
 }
 
 pub struct MerkleTree {
+    pub version: u32,
     pub nodes: Vec<MerkleNode>,
 }
 
+/// Which side of a pair a proof's sibling hash is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofStep {
+    pub hash: String,
+    pub side: Side,
+}
+
+/// Sibling hashes from a leaf up to the root, lowest first. In a format 1 tree, a level
+/// where the leaf's ancestor is the odd node out, and so promoted unchanged, has no step.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    #[serde(default = "legacy_tree")]
+    pub version: u32,
+    pub leaf_index: usize,
+    pub leaf_count: usize,
+    pub path: Vec<ProofStep>,
+}
+
+fn legacy_tree() -> u32 {
+    LEGACY_TREE
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum MerkleError {
+    #[error("Merkle tree has no leaves")]
+    Empty,
+    #[error("Merkle tree has no leaf {0}")]
+    NoLeaf(usize),
+    #[error("unknown Merkle tree format {0}")]
+    UnknownVersion(u32),
+}
+
+/// Format 2 interior nodes: `blake3(0x01 || left || right)` over hex hashes.
+struct DagTree;
+
+impl TreeHasher for DagTree {
+    type Hash = String;
+
+    fn empty() -> String {
+        Hasher::new().finalize().to_hex().to_string()
+    }
+
+    fn node(left: &String, right: &String) -> String {
+        node_hash(left, right)
+    }
+}
+
+fn hash_pair(left: &str, right: &str) -> String {
+    let mut hasher = Hasher::new();
+    hasher.update(left.as_bytes());
+    hasher.update(right.as_bytes());
+    hasher.finalize().to_hex().to_string()
+}
+
+fn leaf_hash(leaf: &str) -> String {
+    let mut hasher = Hasher::new();
+    hasher.update(&[LEAF_PREFIX]);
+    hasher.update(leaf.as_bytes());
+    hasher.finalize().to_hex().to_string()
+}
+
+fn node_hash(left: &str, right: &str) -> String {
+    let mut hasher = Hasher::new();
+    hasher.update(&[NODE_PREFIX]);
+    hasher.update(left.as_bytes());
+    hasher.update(right.as_bytes());
+    hasher.finalize().to_hex().to_string()
+}
+
+/// The published root: the tree hash bound to the format and leaf count, so trees of
+/// different sizes cannot share a root.
+fn root_hash(version: u32, tree_hash: &str, leaf_count: usize) -> String {
+    let mut hasher = Hasher::new();
+    hasher.update(&[ROOT_PREFIX]);
+    hasher.update(&version.to_be_bytes());
+    hasher.update(&(leaf_count as u64).to_be_bytes());
+    hasher.update(tree_hash.as_bytes());
+    hasher.finalize().to_hex().to_string()
+}
+
+fn side(left: bool) -> Side {
+    if left {
+        Side::Left
+    } else {
+        Side::Right
+    }
+}
+
+/// Sides of the siblings on the path of leaf `index` in a format 1 tree of `width` leaves.
+fn legacy_path_sides(mut index: usize, mut width: usize) -> Vec<Side> {
+    let mut sides = Vec::new();
+    while width > 1 {
+        if index % 2 == 1 {
+            sides.push(Side::Left);
+        } else if index + 1 < width {
+            sides.push(Side::Right);
+        }
+        index /= 2;
+        width = width.div_ceil(2);
+    }
+    sides
+}
+
 impl MerkleTree {
     pub fn from_leaves(leaves: Vec<String>) -> Self {
         let nodes = leaves.into_iter().map(|h| MerkleNode { hash: h }).collect();
-        Self { nodes }
+        Self {
+            version: TREE_VERSION,
+            nodes,
+        }
+    }
+
+    /// A format 1 tree, for checking roots and proofs recorded before format 2.
+    pub fn legacy(leaves: Vec<String>) -> Self {
+        Self {
+            version: LEGACY_TREE,
+            ..Self::from_leaves(leaves)
+        }
+    }
+
+    fn leaves(&self) -> Vec<String> {
+        self.nodes.iter().map(|n| n.hash.clone()).collect()
+    }
+
+    fn next_layer(layer: &[String]) -> Vec<String> {
+        layer
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hash_pair(left, right),
+                [odd] => odd.clone(),
+                _ => unreachable!("chunks of two"),
+            })
+            .collect()
+    }
+
+    fn hashed_leaves(&self) -> Vec<String> {
+        self.nodes.iter().map(|n| leaf_hash(&n.hash)).collect()
     }
 
-    pub fn root(&self) -> Option<String> {
+    pub fn root(&self) -> Result<String, MerkleError> {
         if self.nodes.is_empty() {
-            return None;
-        }
-        let mut layer: Vec<String> = self.nodes.iter().map(|n| n.hash.clone()).collect();
-        while layer.len() > 1 {
-            let mut next = Vec::new();
-            for pair in layer.chunks(2) {
-                if pair.len() == 1 {
-                    next.push(pair[0].clone());
-                } else {
-                    let mut hasher = Hasher::new();
-                    hasher.update(pair[0].as_bytes());
-                    hasher.update(pair[1].as_bytes());
-                    next.push(hasher.finalize().to_hex().to_string());
+            return Err(MerkleError::Empty);
+        }
+        match self.version {
+            LEGACY_TREE => {
+                let mut layer = self.leaves();
+                while layer.len() > 1 {
+                    layer = Self::next_layer(&layer);
+                }
+                Ok(layer.swap_remove(0))
+            }
+            TREE_VERSION => {
+                let leaves = self.hashed_leaves();
+                let tree_hash = root_with::<DagTree>(&leaves);
+                Ok(root_hash(self.version, &tree_hash, leaves.len()))
+            }
+            version => Err(MerkleError::UnknownVersion(version)),
+        }
+    }
+
+    /// Inclusion proof for the leaf at `index`.
+    pub fn proof(&self, index: usize) -> Result<MerkleProof, MerkleError> {
+        if index >= self.nodes.len() {
+            return Err(MerkleError::NoLeaf(index));
+        }
+        let mut path = Vec::new();
+        if self.version == LEGACY_TREE {
+            let mut layer = self.leaves();
+            let mut position = index;
+            while layer.len() > 1 {
+                let sibling = position ^ 1;
+                if let Some(hash) = layer.get(sibling) {
+                    let side = if sibling < position {
+                        Side::Left
+                    } else {
+                        Side::Right
+                    };
+                    path.push(ProofStep {
+                        hash: hash.clone(),
+                        side,
+                    });
                 }
+                layer = Self::next_layer(&layer);
+                position /= 2;
             }
-            layer = next;
+        } else if self.version == TREE_VERSION {
+            let hashes = path_with::<DagTree>(&self.hashed_leaves(), index);
+            let sides = sibling_sides(index, self.nodes.len());
+            path = hashes
+                .into_iter()
+                .zip(sides)
+                .map(|(hash, left)| ProofStep {
+                    hash,
+                    side: side(left),
+                })
+                .collect();
+        } else {
+            return Err(MerkleError::UnknownVersion(self.version));
         }
-        layer.first().cloned()
+        Ok(MerkleProof {
+            version: self.version,
+            leaf_index: index,
+            leaf_count: self.nodes.len(),
+            path,
+        })
+    }
+}
+
+impl MerkleProof {
+    /// Root reached by hashing `leaf` with each sibling in turn, under the proof's format;
+    /// `None` for an unknown format or a path that does not fit the leaf's position.
+    pub fn root_from(&self, leaf: &str) -> Option<String> {
+        match self.version {
+            LEGACY_TREE => Some(self.path.iter().fold(
+                leaf.to_string(),
+                |hash, step| match step.side {
+                    Side::Left => hash_pair(&step.hash, &hash),
+                    Side::Right => hash_pair(&hash, &step.hash),
+                },
+            )),
+            TREE_VERSION => {
+                let path: Vec<String> = self.path.iter().map(|step| step.hash.clone()).collect();
+                let tree_hash = path_root::<DagTree>(
+                    &leaf_hash(leaf),
+                    self.leaf_index,
+                    self.leaf_count,
+                    &path,
+                )?;
+                Some(root_hash(self.version, &tree_hash, self.leaf_count))
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether `leaf` sits at `leaf_index` under `root`. The sides must be the ones that
+    /// position implies, so a proof cannot be replayed for another index.
+    pub fn verify(&self, leaf: &str, root: &str) -> bool {
+        let expected: Vec<Side> = match self.version {
+            LEGACY_TREE => legacy_path_sides(self.leaf_index, self.leaf_count),
+            TREE_VERSION => sibling_sides(self.leaf_index, self.leaf_count)
+                .into_iter()
+                .map(side)
+                .collect(),
+            _ => return false,
+        };
+        let sides: Vec<Side> = self.path.iter().map(|step| step.side).collect();
+        self.leaf_index < self.leaf_count
+            && sides == expected
+            && self.root_from(leaf).as_deref() == Some(root)
     }
 }
@@ -1,3 +1,4 @@
 pub mod dag;
+pub mod executor;
 pub mod libp2p_integration;
 pub mod merkletree;
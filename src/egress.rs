@@ -0,0 +1,231 @@
+use crate::{audit::AuditLog, dag::dag::RequestDag, policy::PolicyProvenance};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{fmt, sync::Arc, time::Duration};
+use thiserror::Error;
+use tokio::net::TcpStream;
+use tracing::warn;
+use uuid::Uuid;
+
+/// Outbound channels that must pass through the egress gate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EgressKind {
+    Http,
+    P2pDial,
+    Webhook,
+    AxiomFetch,
+}
+
+impl fmt::Display for EgressKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            EgressKind::Http => "http",
+            EgressKind::P2pDial => "p2p_dial",
+            EgressKind::Webhook => "webhook",
+            EgressKind::AxiomFetch => "axiom_fetch",
+        })
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum EgressError {
+    #[error("{kind} egress to {target} denied: network disabled")]
+    Denied { kind: EgressKind, target: String },
+    #[error("invalid egress target: {0}")]
+    InvalidTarget(String),
+    #[error("egress failed: {0}")]
+    Transport(String),
+    #[error("response body exceeds {0} bytes")]
+    TooLarge(usize),
+}
+
+/// Largest response body `EgressGate::get` reads by default.
+pub const MAX_BODY_BYTES: usize = 1 << 20;
+
+/// Single choke point for outbound I/O: HTTP, P2P dials, webhooks and axiom fetches.
+pub struct EgressGate {
+    node_allows: bool,
+    audit: Option<(Arc<AuditLog>, PolicyProvenance)>,
+    http: reqwest::Client,
+    max_body: usize,
+}
+
+impl EgressGate {
+    /// Panics if the HTTP client cannot be built; falling back to a default client would
+    /// follow redirects.
+    pub fn new(node_allows: bool) -> Self {
+        // A redirect would reach a target the gate never admitted, so none are followed.
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .expect("egress HTTP client with redirects disabled");
+        Self {
+            node_allows,
+            audit: None,
+            http,
+            max_body: MAX_BODY_BYTES,
+        }
+    }
+
+    pub fn offline() -> Self {
+        Self::new(false)
+    }
+
+    /// Records every denied attempt in `audit` as an `egress` entry.
+    pub fn with_audit(mut self, audit: Arc<AuditLog>, provenance: PolicyProvenance) -> Self {
+        self.audit = Some((audit, provenance));
+        self
+    }
+
+    /// Caps response bodies read by `get` at `bytes`.
+    pub fn with_max_body(mut self, bytes: usize) -> Self {
+        self.max_body = bytes;
+        self
+    }
+
+    pub fn node_allows(&self) -> bool {
+        self.node_allows
+    }
+
+    /// Admits an attempt only when both the node and the request allow network access.
+    /// A denied attempt is audited before this returns.
+    pub async fn authorize(
+        &self,
+        kind: EgressKind,
+        target: &str,
+        request_allows: bool,
+    ) -> Result<(), EgressError> {
+        let target = audit_target(target);
+        if self.node_allows && request_allows {
+            return Ok(());
+        }
+
+        warn!(%kind, %target, "egress denied: network disabled");
+        if let Some((audit, provenance)) = &self.audit {
+            let (audit, provenance) = (audit.clone(), provenance.clone());
+            let payload = json!({
+                "kind": kind,
+                "target": target,
+                "node_allows": self.node_allows,
+                "request_allows": request_allows,
+            });
+            // The append fsyncs, so it runs off the async workers.
+            let appended = tokio::task::spawn_blocking(move || {
+                let mut dag = RequestDag::default();
+                dag.add_node("egress_denied", payload, &[])?;
+                audit.append(Uuid::new_v4(), "egress", &dag, &provenance)
+            })
+            .await
+            .map_err(anyhow::Error::from)
+            .and_then(|appended| appended);
+            if let Err(err) = appended {
+                warn!(%err, "failed to audit denied egress");
+            }
+        }
+        Err(EgressError::Denied { kind, target })
+    }
+
+    pub async fn get(
+        &self,
+        kind: EgressKind,
+        url: &str,
+        request_allows: bool,
+    ) -> Result<Vec<u8>, EgressError> {
+        let url = parse_url(url)?;
+        self.authorize(kind, url.as_str(), request_allows).await?;
+        let mut response = self
+            .http
+            .get(url.clone())
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(transport_error)?;
+        if response.status().is_redirection() {
+            return Err(EgressError::Transport(format!(
+                "{} redirects; redirects are not followed",
+                audit_target(url.as_str())
+            )));
+        }
+        if response
+            .content_length()
+            .is_some_and(|length| length > self.max_body as u64)
+        {
+            return Err(EgressError::TooLarge(self.max_body));
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(transport_error)? {
+            if body.len() + chunk.len() > self.max_body {
+                return Err(EgressError::TooLarge(self.max_body));
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body)
+    }
+
+    pub async fn post_json<T: Serialize + ?Sized>(
+        &self,
+        kind: EgressKind,
+        url: &str,
+        body: &T,
+        request_allows: bool,
+    ) -> Result<(), EgressError> {
+        let url = parse_url(url)?;
+        self.authorize(kind, url.as_str(), request_allows).await?;
+        self.http
+            .post(url)
+            .json(body)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(transport_error)?;
+        Ok(())
+    }
+
+    /// Opens a raw TCP connection to `host:port`.
+    pub async fn connect(
+        &self,
+        kind: EgressKind,
+        addr: &str,
+        request_allows: bool,
+    ) -> Result<TcpStream, EgressError> {
+        self.authorize(kind, addr, request_allows).await?;
+        TcpStream::connect(addr)
+            .await
+            .map_err(|e| EgressError::Transport(e.to_string()))
+    }
+}
+
+fn parse_url(url: &str) -> Result<Url, EgressError> {
+    let parsed = Url::parse(url).map_err(|e| EgressError::InvalidTarget(e.to_string()))?;
+    match parsed.scheme() {
+        "http" | "https" => Ok(parsed),
+        other => Err(EgressError::InvalidTarget(format!(
+            "unsupported scheme: {other}"
+        ))),
+    }
+}
+
+/// Transport failure without the request URL, whose query may carry secrets.
+fn transport_error(err: reqwest::Error) -> EgressError {
+    EgressError::Transport(err.without_url().to_string())
+}
+
+/// Drops credentials and query strings so audit entries never carry secrets.
+pub fn audit_target(target: &str) -> String {
+    match Url::parse(target) {
+        Ok(url) if url.has_host() => {
+            let port = url.port().map(|p| format!(":{p}")).unwrap_or_default();
+            format!(
+                "{}://{}{}{}",
+                url.scheme(),
+                url.host_str().unwrap_or_default(),
+                port,
+                url.path()
+            )
+        }
+        _ => target.to_string(),
+    }
+}
@@ -1,9 +1,20 @@
 pub mod api;
 pub mod audit;
+pub mod audit_crypt;
+pub mod audit_jsonl;
+pub mod audit_segment;
+pub mod audit_sled;
+#[cfg(feature = "cli")]
+pub mod cli;
+pub mod credential;
 pub mod dag;
+pub mod egress;
 pub mod model;
+pub mod node_key;
 pub mod payment;
 pub mod policy;
+pub mod transparency;
+pub mod triage;
 pub mod verification;
 
 use model::engine::HybridModelEngine;
@@ -1,12 +1,4 @@
-mod api;
-mod audit;
-mod dag;
-mod model;
-mod payment;
-mod policy;
-mod verification;
-
-use api::{build_router, build_state};
+use axiomhive::api::{build_router, build_state};
 use std::net::SocketAddr;
 use tokio::{net::TcpListener, signal};
 use tracing::info;
@@ -15,6 +7,14 @@ use tracing::info;
 async fn main() -> anyhow::Result<()> {
     tracing_subscriber::fmt().with_env_filter("info").init();
 
+    #[cfg(feature = "cli")]
+    {
+        let args = std::env::args().skip(1).collect::<Vec<_>>();
+        if let Some(result) = axiomhive::cli::dispatch(&args) {
+            return result;
+        }
+    }
+
     let state = build_state()?;
     let app = build_router(state);
 
@@ -0,0 +1,116 @@
+use anyhow::{anyhow, Context, Result};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use rand::RngCore;
+use serde::Serialize;
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// Persistent ed25519 identity of this node; signs audit entries and other node artifacts.
+pub struct NodeKey {
+    signing: SigningKey,
+    path: Option<PathBuf>,
+}
+
+/// What the node publishes so others can check its signatures.
+#[derive(Debug, Clone, Serialize)]
+pub struct PublishedKey {
+    pub algorithm: String,
+    pub public_key: String,
+}
+
+impl NodeKey {
+    pub fn generate() -> Self {
+        let mut secret = [0u8; 32];
+        rand::rngs::OsRng.fill_bytes(&mut secret);
+        Self {
+            signing: SigningKey::from_bytes(&secret),
+            path: None,
+        }
+    }
+
+    /// Loads the hex secret at `path`, creating it (owner-only) on first start.
+    pub fn load_or_create(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if path.exists() {
+            let raw = fs::read_to_string(path)
+                .with_context(|| format!("reading node key {}", path.display()))?;
+            let secret: [u8; 32] = hex::decode(raw.trim())
+                .ok()
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or_else(|| {
+                    anyhow!("node key {} is not a hex ed25519 secret", path.display())
+                })?;
+            return Ok(Self {
+                signing: SigningKey::from_bytes(&secret),
+                path: Some(path.to_path_buf()),
+            });
+        }
+        let mut key = Self::generate();
+        key.save(path)?;
+        Ok(key)
+    }
+
+    /// Writes the secret to `path` via a temp file so a crash never leaves a partial key.
+    pub fn save(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        fs::create_dir_all(path.parent().unwrap_or_else(|| Path::new(".")))?;
+        let tmp = path.with_extension("tmp");
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(&tmp)?;
+        file.write_all(hex::encode(self.signing.to_bytes()).as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)?;
+        self.path = Some(path.to_path_buf());
+        Ok(())
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub fn public_key_hex(&self) -> String {
+        hex::encode(self.signing.verifying_key().to_bytes())
+    }
+
+    pub fn published(&self) -> PublishedKey {
+        PublishedKey {
+            algorithm: "ed25519".into(),
+            public_key: self.public_key_hex(),
+        }
+    }
+
+    /// A 32-byte key for `context` derived from the node secret.
+    pub fn derive_key(&self, context: &str) -> [u8; 32] {
+        blake3::derive_key(context, &self.signing.to_bytes())
+    }
+
+    /// Hex signature over `message`.
+    pub fn sign(&self, message: &[u8]) -> String {
+        hex::encode(self.signing.sign(message).to_bytes())
+    }
+}
+
+/// Checks a hex ed25519 signature against a hex public key.
+pub fn verify_signature(public_key: &str, message: &[u8], signature: &str) -> bool {
+    let Some(key) = hex::decode(public_key)
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok())
+    else {
+        return false;
+    };
+    let Some(signature) = hex::decode(signature)
+        .ok()
+        .and_then(|bytes| <[u8; 64]>::try_from(bytes).ok())
+        .map(|bytes| Signature::from_bytes(&bytes))
+    else {
+        return false;
+    };
+    key.verify(message, &signature).is_ok()
+}
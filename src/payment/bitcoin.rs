@@ -17,6 +17,11 @@ impl LightningGateway {
         }
     }
 
+    /// Price of one proof after a policy profile's multiplier.
+    pub fn quote_sats(&self, multiplier: f64) -> u64 {
+        (self.price_per_proof_sats as f64 * multiplier).round() as u64
+    }
+
     pub fn invoice_for_task(&self, request_id: &str) -> String {
         let mut hasher = Sha3_256::new();
         hasher.update(request_id.as_bytes());
@@ -1,13 +1,603 @@
-use serde::Deserialize;
-use std::{fs, path::Path};
+use crate::triage::{triage, TriageConfig, TriageReport};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
 use thiserror::Error;
+use tracing::warn;
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SafetyConfig {
     pub allow_network: bool,
     pub allow_verified: bool,
     pub blocklist: Vec<String>,
     pub high_risk_terms: Vec<String>,
+    #[serde(default)]
+    pub rules: Vec<PolicyRule>,
+    #[serde(default)]
+    pub output: OutputPolicy,
+    #[serde(default)]
+    pub pii: PiiConfig,
+    #[serde(default)]
+    pub identity: IdentityGate,
+    #[serde(default)]
+    pub triage: TriageConfig,
+    /// Media kinds callers may request; empty allows all.
+    #[serde(default)]
+    pub allowed_media: Vec<String>,
+    #[serde(default = "default_price_multiplier")]
+    pub price_multiplier: f64,
+    /// Reject requests that do not present a known API key.
+    #[serde(default)]
+    pub require_api_key: bool,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, PolicyProfile>,
+    /// Client id -> profile name.
+    #[serde(default)]
+    pub clients: BTreeMap<String, String>,
+    /// Hex SHA-256 of an API key -> the client it authenticates.
+    #[serde(default)]
+    pub api_keys: BTreeMap<String, ApiKeyGrant>,
+}
+
+fn default_price_multiplier() -> f64 {
+    1.0
+}
+
+/// Named overrides on top of the base config (or of the profile named in `inherits`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PolicyProfile {
+    pub inherits: Option<String>,
+    pub blocklist: Option<Vec<String>>,
+    pub high_risk_terms: Option<Vec<String>>,
+    pub allow_network: Option<bool>,
+    pub allow_verified: Option<bool>,
+    pub allowed_media: Option<Vec<String>>,
+    pub price_multiplier: Option<f64>,
+}
+
+impl PolicyProfile {
+    fn apply(&self, config: &mut SafetyConfig) {
+        if let Some(blocklist) = &self.blocklist {
+            config.blocklist = blocklist.clone();
+        }
+        if let Some(terms) = &self.high_risk_terms {
+            config.high_risk_terms = terms.clone();
+        }
+        if let Some(allow) = self.allow_network {
+            config.allow_network = allow;
+        }
+        if let Some(allow) = self.allow_verified {
+            config.allow_verified = allow;
+        }
+        if let Some(media) = &self.allowed_media {
+            config.allowed_media = media.clone();
+        }
+        if let Some(multiplier) = self.price_multiplier {
+            config.price_multiplier = multiplier;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyGrant {
+    pub client: String,
+    /// Overrides the client's profile for this key.
+    #[serde(default)]
+    pub profile: Option<String>,
+}
+
+/// Who a request runs as after API key authentication.
+#[derive(Debug, Clone, Default)]
+pub struct Caller {
+    pub client: Option<String>,
+    pub profile: Option<String>,
+}
+
+/// Resolves `name` through its `inherits` chain down to the base config.
+fn profile_config(
+    base: &SafetyConfig,
+    name: &str,
+    chain: &mut Vec<String>,
+) -> Result<SafetyConfig, PolicyError> {
+    if chain.iter().any(|seen| seen == name) {
+        chain.push(name.to_string());
+        return Err(PolicyError::ConfigLoad(format!(
+            "profile inheritance cycle: {}",
+            chain.join(" -> ")
+        )));
+    }
+    let profile = base
+        .profiles
+        .get(name)
+        .ok_or_else(|| PolicyError::ConfigLoad(format!("unknown profile: {name}")))?;
+    chain.push(name.to_string());
+    let mut config = match &profile.inherits {
+        Some(parent) => profile_config(base, parent, chain)?,
+        None => SafetyConfig {
+            profiles: BTreeMap::new(),
+            clients: BTreeMap::new(),
+            api_keys: BTreeMap::new(),
+            ..base.clone()
+        },
+    };
+    profile.apply(&mut config);
+    Ok(config)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Allow,
+    Deny,
+    Escalate,
+}
+
+/// Conditions a rule needs to fire. Every non-empty field must match; within a field any entry may.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RuleMatch {
+    /// Case-insensitive substrings of the prompt.
+    pub prompt_contains: Vec<String>,
+    pub modes: Vec<String>,
+    pub axiom_sets: Vec<String>,
+    pub media: Vec<String>,
+    pub callers: Vec<String>,
+}
+
+/// One declarative policy rule, evaluated in config order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicyRule {
+    pub id: String,
+    #[serde(default)]
+    pub when: RuleMatch,
+    pub action: Action,
+    pub risk: RiskLevel,
+    pub reason: String,
+    #[serde(default)]
+    pub requires_consent: bool,
+    #[serde(default)]
+    pub requires_payment: bool,
+}
+
+/// A prompt term hit, in character offsets into the prompt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MatchSpan {
+    pub term: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl PolicyRule {
+    /// Prompt spans that matched, or `None` when the rule does not fire for this request.
+    fn matched_spans(&self, request: &PolicyRequest<'_>) -> Option<Vec<MatchSpan>> {
+        let when = &self.when;
+        let listed = |allowed: &[String], value: &str| {
+            allowed.is_empty() || allowed.iter().any(|v| v.eq_ignore_ascii_case(value))
+        };
+        if !listed(&when.modes, request.mode)
+            || !listed(&when.callers, request.caller.unwrap_or_default())
+            || !listed(&when.axiom_sets, request.axiom_set.unwrap_or_default())
+        {
+            return None;
+        }
+        if !when.media.is_empty() && !request.media.iter().any(|m| listed(&when.media, m)) {
+            return None;
+        }
+
+        let spans = when
+            .prompt_contains
+            .iter()
+            .flat_map(|term| {
+                match_ranges(request.prompt, term)
+                    .into_iter()
+                    .map(move |range| MatchSpan {
+                        term: term.clone(),
+                        start: request.prompt[..range.start].chars().count(),
+                        end: request.prompt[..range.end].chars().count(),
+                    })
+            })
+            .collect::<Vec<_>>();
+        if !when.prompt_contains.is_empty() && spans.is_empty() {
+            return None;
+        }
+        Some(spans)
+    }
+
+    fn decide(&self, spans: &[MatchSpan]) -> SafetyDecision {
+        let mut matched_terms = Vec::<String>::new();
+        for span in spans {
+            if !matched_terms.contains(&span.term) {
+                matched_terms.push(span.term.clone());
+            }
+        }
+        SafetyDecision {
+            action: self.action,
+            reason: self.reason.clone(),
+            risk: self.risk,
+            rule_id: Some(self.id.clone()),
+            matched_terms,
+            requires_consent: self.requires_consent,
+            requires_payment: self.requires_payment,
+        }
+    }
+}
+
+/// Request attributes the policy rules can match on.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PolicyRequest<'a> {
+    pub prompt: &'a str,
+    pub mode: &'a str,
+    pub axiom_set: Option<&'a str>,
+    pub media: &'a [String],
+    pub caller: Option<&'a str>,
+}
+
+impl<'a> From<&'a str> for PolicyRequest<'a> {
+    fn from(prompt: &'a str) -> Self {
+        Self {
+            prompt,
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SafetyDecision {
+    pub action: Action,
+    pub reason: String,
+    pub risk: RiskLevel,
+    pub rule_id: Option<String>,
+    pub matched_terms: Vec<String>,
+    pub requires_consent: bool,
+    pub requires_payment: bool,
+}
+
+impl SafetyDecision {
+    fn allow() -> Self {
+        Self {
+            action: Action::Allow,
+            reason: "allowed".into(),
+            risk: RiskLevel::Low,
+            rule_id: None,
+            matched_terms: Vec::new(),
+            requires_consent: false,
+            requires_payment: false,
+        }
+    }
+
+    /// Maps deny/escalate decisions onto the policy errors the API already reports.
+    pub fn into_result(self) -> Result<Self, PolicyError> {
+        let detail = if self.matched_terms.is_empty() {
+            self.reason.clone()
+        } else {
+            self.matched_terms.join(", ")
+        };
+        match self.action {
+            Action::Allow => Ok(self),
+            Action::Deny => Err(PolicyError::Blocked(detail)),
+            Action::Escalate => Err(PolicyError::EscalationRequired(detail)),
+        }
+    }
+}
+
+/// What to do with generated text that matches a configured term.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputAction {
+    Allow,
+    Redact,
+    Escalate,
+    Block,
+}
+
+/// Output-side screening, applied to model output with the same term lists as prompts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputPolicy {
+    pub blocked_action: OutputAction,
+    pub high_risk_action: OutputAction,
+    pub redaction_marker: String,
+}
+
+impl Default for OutputPolicy {
+    fn default() -> Self {
+        Self {
+            blocked_action: OutputAction::Block,
+            high_risk_action: OutputAction::Escalate,
+            redaction_marker: "[redacted]".into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdentityAction {
+    Allow,
+    Deny,
+    /// Answer with `IdentityGate::response` instead of running the model.
+    Transmute,
+    Escalate,
+}
+
+/// Identity barrier: requests carrying identity fields or asking to profile someone.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IdentityGate {
+    pub enabled: bool,
+    /// Request field names (matched case-insensitively at any depth).
+    pub fields: Vec<String>,
+    /// Prompt phrases that ask for identity or profiling.
+    pub prompt_phrases: Vec<String>,
+    pub action: IdentityAction,
+    pub response: String,
+}
+
+impl Default for IdentityGate {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            fields: vec![
+                "name".into(),
+                "username".into(),
+                "agent_status".into(),
+                "reputation".into(),
+            ],
+            prompt_phrases: vec![
+                "who am i".into(),
+                "my username".into(),
+                "my reputation".into(),
+                "my agent status".into(),
+                "profile me".into(),
+                "what do you know about me".into(),
+            ],
+            action: IdentityAction::Transmute,
+            response: "Identity processing is structurally barred.".into(),
+        }
+    }
+}
+
+/// Outcome of the identity gate; `action` is `Allow` when nothing matched.
+#[derive(Debug, Clone, Serialize)]
+pub struct IdentityVerdict {
+    pub action: IdentityAction,
+    pub matched_fields: Vec<String>,
+    pub matched_phrases: Vec<String>,
+    #[serde(skip)]
+    pub response: String,
+}
+
+impl IdentityVerdict {
+    /// `Ok(Some(text))` means answer with the fixed response. Verified output must be
+    /// signed model output, so transmute denies there instead.
+    pub fn enforce(&self, allow_transmute: bool) -> Result<Option<&str>, PolicyError> {
+        let detail = || {
+            self.matched_fields
+                .iter()
+                .chain(&self.matched_phrases)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self.action {
+            IdentityAction::Allow => Ok(None),
+            IdentityAction::Transmute if allow_transmute => Ok(Some(&self.response)),
+            IdentityAction::Transmute | IdentityAction::Deny => {
+                Err(PolicyError::IdentityBarred(detail()))
+            }
+            IdentityAction::Escalate => Err(PolicyError::EscalationRequired(format!(
+                "identity request requires review: {}",
+                detail()
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiKind {
+    Email,
+    Phone,
+    Iban,
+    CardNumber,
+}
+
+impl PiiKind {
+    fn label(self) -> &'static str {
+        match self {
+            PiiKind::Email => "EMAIL",
+            PiiKind::Phone => "PHONE",
+            PiiKind::Iban => "IBAN",
+            PiiKind::CardNumber => "CARD",
+        }
+    }
+}
+
+/// What the PII stage does with a detected value before the prompt reaches the model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiAction {
+    Allow,
+    /// Replace with `[EMAIL]`; not recoverable.
+    Redact,
+    /// Replace with `[EMAIL_1]` and keep the original in the request's vault.
+    Tokenize,
+    /// Replace with a salted blake3 digest so equal values stay linkable.
+    Hash,
+    Block,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PiiConfig {
+    pub enabled: bool,
+    pub email: PiiAction,
+    pub phone: PiiAction,
+    pub iban: PiiAction,
+    pub card_number: PiiAction,
+    pub hash_salt: String,
+}
+
+impl Default for PiiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            email: PiiAction::Tokenize,
+            phone: PiiAction::Tokenize,
+            iban: PiiAction::Redact,
+            card_number: PiiAction::Block,
+            hash_salt: String::new(),
+        }
+    }
+}
+
+impl PiiConfig {
+    fn action(&self, kind: PiiKind) -> PiiAction {
+        match kind {
+            PiiKind::Email => self.email,
+            PiiKind::Phone => self.phone,
+            PiiKind::Iban => self.iban,
+            PiiKind::CardNumber => self.card_number,
+        }
+    }
+}
+
+/// Per-kind counts of what the PII stage did; safe to store, never holds raw values.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PiiReport {
+    pub detected: BTreeMap<PiiKind, usize>,
+    pub actions: BTreeMap<PiiKind, PiiAction>,
+}
+
+/// Originals for tokenized values, kept only for the lifetime of one request.
+#[derive(Clone, Default)]
+pub struct PiiVault {
+    entries: Vec<(String, String)>,
+}
+
+impl fmt::Debug for PiiVault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PiiVault")
+            .field("entries", &self.entries.len())
+            .finish()
+    }
+}
+
+impl PiiVault {
+    /// Swaps tokens in model output back to the caller's original values.
+    pub fn restore(&self, text: &str) -> String {
+        self.entries
+            .iter()
+            .fold(text.to_string(), |acc, (token, original)| {
+                acc.replace(token, original)
+            })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PiiScrub {
+    pub text: String,
+    pub vault: PiiVault,
+    pub report: PiiReport,
+}
+
+fn pii_patterns() -> &'static [(PiiKind, Regex)] {
+    static PATTERNS: OnceLock<Vec<(PiiKind, Regex)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        // Earlier kinds win overlaps: IBANs and card numbers would otherwise read as phone numbers.
+        [
+            (PiiKind::Iban, r"\b[A-Z]{2}\d{2}(?: ?[A-Z0-9]){11,30}\b"),
+            (PiiKind::CardNumber, r"\b\d(?:[ -]?\d){12,18}\b"),
+            (
+                PiiKind::Email,
+                r"[A-Za-z0-9._%+-]+@[A-Za-z0-9-]+(?:\.[A-Za-z0-9-]+)*\.[A-Za-z]{2,}",
+            ),
+            (
+                PiiKind::Phone,
+                r"(?:\+\d{1,3}[ .-]?)?\(?\d{2,5}\)?[ .-]?\d{3,4}[ .-]?\d{3,6}\b",
+            ),
+        ]
+        .into_iter()
+        .map(|(kind, pattern)| (kind, Regex::new(pattern).expect("valid PII pattern")))
+        .collect()
+    })
+}
+
+/// The valid `kind` value in the `pattern` match at `range` of `text`: the match itself,
+/// or else its longest prefix ending before a separator that still matches. The greedy
+/// patterns take a following word or digit group ("DE89… EUR"), and the value must still
+/// be found when that longer match fails validation.
+fn pii_match(
+    kind: PiiKind,
+    pattern: &Regex,
+    text: &str,
+    range: Range<usize>,
+) -> Option<Range<usize>> {
+    let candidate = &text[range.clone()];
+    let separators = candidate
+        .char_indices()
+        .filter(|(_, c)| matches!(c, ' ' | '-'))
+        .map(|(i, _)| i);
+    let mut ends = separators.collect::<Vec<_>>();
+    ends.push(candidate.len());
+    ends.into_iter()
+        .rev()
+        .find(|&end| {
+            let prefix = &candidate[..end];
+            pattern.find(prefix).is_some_and(|m| m.range() == (0..end))
+                && pii_candidate_valid(kind, prefix)
+        })
+        .map(|end| range.start..range.start + end)
+}
+
+fn pii_candidate_valid(kind: PiiKind, value: &str) -> bool {
+    let digits = value
+        .chars()
+        .filter(char::is_ascii_digit)
+        .map(|c| c as u32 - '0' as u32)
+        .collect::<Vec<_>>();
+    match kind {
+        PiiKind::Email => true,
+        PiiKind::Phone => (8..=15).contains(&digits.len()),
+        PiiKind::CardNumber => {
+            let sum: u32 = digits
+                .iter()
+                .rev()
+                .enumerate()
+                .map(|(i, &d)| match (i % 2 == 1, d * 2) {
+                    (true, doubled) if doubled > 9 => doubled - 9,
+                    (true, doubled) => doubled,
+                    (false, _) => d,
+                })
+                .sum();
+            sum.is_multiple_of(10)
+        }
+        PiiKind::Iban => {
+            let compact = value.replace(' ', "");
+            let (head, tail) = compact.split_at(4);
+            tail.chars().chain(head.chars()).try_fold(0u32, |acc, c| {
+                let n = c.to_digit(36)?;
+                let acc = if n > 9 { acc * 100 + n } else { acc * 10 + n };
+                Some(acc % 97)
+            }) == Some(1)
+        }
+    }
 }
 
 fn env_flag(key: &str, default: bool) -> bool {
@@ -34,6 +624,17 @@ impl Default for SafetyConfig {
                 "medical diagnosis".into(),
                 "legal judgment".into(),
             ],
+            rules: Vec::new(),
+            output: OutputPolicy::default(),
+            pii: PiiConfig::default(),
+            identity: IdentityGate::default(),
+            triage: TriageConfig::default(),
+            allowed_media: Vec::new(),
+            price_multiplier: default_price_multiplier(),
+            require_api_key: false,
+            profiles: BTreeMap::new(),
+            clients: BTreeMap::new(),
+            api_keys: BTreeMap::new(),
         }
     }
 }
@@ -42,6 +643,8 @@ impl Default for SafetyConfig {
 pub enum PolicyError {
     #[error("blocked content: {0}")]
     Blocked(String),
+    #[error("blocked output: {0}")]
+    OutputBlocked(String),
     #[error("verified mode disabled")]
     VerifiedDisabled,
     #[error("network disabled for this request")]
@@ -50,52 +653,515 @@ pub enum PolicyError {
     EscalationRequired(String),
     #[error("failed to load safety config: {0}")]
     ConfigLoad(String),
+    #[error("policy signature rejected: {0}")]
+    SignatureInvalid(String),
+    #[error("prompt contains blocked personal data: {0}")]
+    PiiBlocked(String),
+    #[error("missing or unknown API key")]
+    Unauthorized,
+    #[error("media kind not allowed: {0}")]
+    MediaNotAllowed(String),
+    #[error("identity processing is barred: {0}")]
+    IdentityBarred(String),
 }
 
+/// How strictly `SafetyPolicy::load` treats the policy file.
+#[derive(Debug, Clone, Default)]
+pub struct PolicyLoadOptions {
+    /// Refuse to fall back to built-in defaults when the file is missing.
+    pub strict: bool,
+    /// When set, the policy must carry a detached `<path>.sig` from this ed25519 key (hex).
+    pub publisher_key: Option<String>,
+}
+
+impl PolicyLoadOptions {
+    pub fn from_env() -> Self {
+        Self {
+            strict: env_flag("AXIOMHIVE_POLICY_STRICT", false),
+            publisher_key: std::env::var("AXIOMHIVE_POLICY_PUBKEY")
+                .ok()
+                .filter(|key| !key.trim().is_empty()),
+        }
+    }
+}
+
+/// Which policy governed a request; stamped into every audit entry.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PolicyProvenance {
+    pub hash: String,
+    /// Hex ed25519 public key of the publisher that signed the policy, if verified.
+    pub signer: Option<String>,
+    /// Tenant profile the request ran under; `None` for the base config.
+    pub profile: Option<String>,
+}
+
+/// Path of the detached signature that accompanies a signed policy file.
+pub fn signature_path(path: &Path) -> PathBuf {
+    let mut sig = path.as_os_str().to_owned();
+    sig.push(".sig");
+    PathBuf::from(sig)
+}
+
+/// Hex ed25519 signature over the raw policy bytes, as expected in `<path>.sig`.
+pub fn sign_policy(raw: &[u8], key: &SigningKey) -> String {
+    hex::encode(key.sign(raw).to_bytes())
+}
+
+fn verify_policy_signature(
+    raw: &[u8],
+    signature_hex: &str,
+    publisher_hex: &str,
+) -> Result<(), PolicyError> {
+    let invalid = |msg: &str| PolicyError::SignatureInvalid(msg.to_string());
+    let key_bytes: [u8; 32] = hex::decode(publisher_hex.trim())
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| invalid("publisher key must be 32 hex-encoded bytes"))?;
+    let sig_bytes: [u8; 64] = hex::decode(signature_hex.trim())
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| invalid("signature must be 64 hex-encoded bytes"))?;
+    let key = VerifyingKey::from_bytes(&key_bytes).map_err(|e| invalid(&e.to_string()))?;
+    key.verify(raw, &Signature::from_bytes(&sig_bytes))
+        .map_err(|_| invalid("signature does not match publisher key"))
+}
+
+/// Result of screening generated text before it leaves the node.
 #[derive(Debug, Clone)]
-pub struct PolicyVerdict {
-    pub high_risk_terms: Vec<String>,
+pub struct OutputVerdict {
+    pub action: OutputAction,
+    pub matched_terms: Vec<String>,
+    pub redactions: usize,
+    pub output: String,
+}
+
+impl OutputVerdict {
+    /// Returns the (possibly redacted) output, or the policy error for blocked/escalated output.
+    pub fn release(self) -> Result<String, PolicyError> {
+        match self.action {
+            OutputAction::Allow | OutputAction::Redact => Ok(self.output),
+            OutputAction::Escalate => Err(PolicyError::EscalationRequired(
+                self.matched_terms.join(", "),
+            )),
+            OutputAction::Block => Err(PolicyError::OutputBlocked(self.matched_terms.join(", "))),
+        }
+    }
+
+    /// Like `release`, but refuses redaction: signed output must leave the node unaltered.
+    pub fn release_unaltered(self) -> Result<String, PolicyError> {
+        if self.action == OutputAction::Redact {
+            return Err(PolicyError::OutputBlocked(self.matched_terms.join(", ")));
+        }
+        self.release()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleEvaluation {
+    pub rule_id: String,
+    pub action: Action,
+    pub risk: RiskLevel,
+    pub matched: bool,
+    /// Whether this rule produced the final decision.
+    pub decisive: bool,
+    pub spans: Vec<MatchSpan>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PolicyExplanation {
+    pub policy_hash: String,
+    pub profile: Option<String>,
+    pub decision: SafetyDecision,
+    pub rules: Vec<RuleEvaluation>,
 }
 
 #[derive(Clone)]
 pub struct SafetyPolicy {
     config: SafetyConfig,
+    rules: Vec<PolicyRule>,
+    /// blake3 of the policy file as loaded (or of the serialized built-in defaults).
+    hash: String,
+    signer: Option<String>,
+    profile: Option<String>,
+    profiles: BTreeMap<String, SafetyPolicy>,
 }
 
 impl SafetyPolicy {
     pub fn load_from_disk(path: impl AsRef<Path>) -> Result<Self, PolicyError> {
-        let config = match fs::read_to_string(path) {
-            Ok(raw) => serde_json::from_str::<SafetyConfig>(&raw)
-                .map_err(|e| PolicyError::ConfigLoad(e.to_string()))?,
-            Err(_) => SafetyConfig::default(),
+        Self::load(path, &PolicyLoadOptions::default())
+    }
+
+    pub fn load(path: impl AsRef<Path>, options: &PolicyLoadOptions) -> Result<Self, PolicyError> {
+        let path = path.as_ref();
+        let raw = match fs::read(path) {
+            Ok(raw) => raw,
+            Err(e) if options.strict || options.publisher_key.is_some() => {
+                return Err(PolicyError::ConfigLoad(format!(
+                    "{}: {e} (strict policy loading is enabled)",
+                    path.display()
+                )));
+            }
+            Err(e) => {
+                warn!(
+                    "safety policy {} unreadable ({e}); running with built-in defaults",
+                    path.display()
+                );
+                return Self::from_config(SafetyConfig::default());
+            }
         };
-        Ok(Self { config })
+
+        let signer = match &options.publisher_key {
+            Some(publisher) => {
+                let sig_path = signature_path(path);
+                let signature = fs::read_to_string(&sig_path).map_err(|e| {
+                    PolicyError::SignatureInvalid(format!("{}: {e}", sig_path.display()))
+                })?;
+                verify_policy_signature(&raw, &signature, publisher)?;
+                Some(publisher.trim().to_lowercase())
+            }
+            None => None,
+        };
+
+        let config = serde_json::from_slice::<SafetyConfig>(&raw)
+            .map_err(|e| PolicyError::ConfigLoad(e.to_string()))?;
+        let mut policy = Self::from_config(config)?;
+        policy.stamp(blake3::hash(&raw).to_hex().as_str(), signer);
+        Ok(policy)
     }
 
-    pub fn check_prompt(&self, prompt: &str) -> Result<PolicyVerdict, PolicyError> {
-        let lower = prompt.to_lowercase();
-        if let Some(term) = self
+    fn stamp(&mut self, hash: &str, signer: Option<String>) {
+        for profile in self.profiles.values_mut() {
+            profile.stamp(hash, signer.clone());
+        }
+        self.hash = hash.to_string();
+        self.signer = signer;
+    }
+
+    pub fn provenance(&self) -> PolicyProvenance {
+        PolicyProvenance {
+            hash: self.hash.clone(),
+            signer: self.signer.clone(),
+            profile: self.profile.clone(),
+        }
+    }
+
+    /// Maps an `x-api-key` value to its client; requests without a key run as the base profile
+    /// unless `require_api_key` is set.
+    pub fn authenticate(&self, api_key: Option<&str>) -> Result<Caller, PolicyError> {
+        let Some(api_key) = api_key else {
+            if self.config.require_api_key {
+                return Err(PolicyError::Unauthorized);
+            }
+            return Ok(Caller::default());
+        };
+        let digest = hex::encode(Sha256::digest(api_key.as_bytes()));
+        let grant = self
             .config
-            .blocklist
+            .api_keys
+            .get(&digest)
+            .ok_or(PolicyError::Unauthorized)?;
+        Ok(Caller {
+            client: Some(grant.client.clone()),
+            profile: grant
+                .profile
+                .clone()
+                .or_else(|| self.config.clients.get(&grant.client).cloned()),
+        })
+    }
+
+    /// The policy that governs `caller`: its profile, or the base config.
+    pub fn for_caller(&self, caller: &Caller) -> &SafetyPolicy {
+        caller
+            .profile
+            .as_ref()
+            .and_then(|name| self.profiles.get(name))
+            .unwrap_or(self)
+    }
+
+    pub fn from_config(config: SafetyConfig) -> Result<Self, PolicyError> {
+        let hash = blake3::hash(&serde_json::to_vec(&config).unwrap_or_default())
+            .to_hex()
+            .to_string();
+
+        let referenced = config
+            .clients
+            .values()
+            .chain(config.api_keys.values().filter_map(|g| g.profile.as_ref()));
+        for name in referenced {
+            if !config.profiles.contains_key(name) {
+                return Err(PolicyError::ConfigLoad(format!("unknown profile: {name}")));
+            }
+        }
+
+        let mut profiles = BTreeMap::new();
+        for name in config.profiles.keys() {
+            let mut profile = Self::compile(profile_config(&config, name, &mut Vec::new())?);
+            profile.profile = Some(name.clone());
+            profiles.insert(name.clone(), profile);
+        }
+
+        let mut policy = Self::compile(config);
+        policy.profiles = profiles;
+        policy.stamp(&hash, None);
+        Ok(policy)
+    }
+
+    /// Blocklist and verified-mode denials always run first and cannot be overridden;
+    /// configured rules come next, so they can pre-empt the generic high-risk escalation.
+    fn compile(config: SafetyConfig) -> Self {
+        let mut rules = Vec::new();
+        if !config.blocklist.is_empty() {
+            rules.push(PolicyRule {
+                id: "blocklist".into(),
+                when: RuleMatch {
+                    prompt_contains: config.blocklist.clone(),
+                    ..RuleMatch::default()
+                },
+                action: Action::Deny,
+                risk: RiskLevel::High,
+                reason: "blocked content".into(),
+                requires_consent: false,
+                requires_payment: false,
+            });
+        }
+        if !config.allow_verified {
+            rules.push(PolicyRule {
+                id: "verified-disabled".into(),
+                when: RuleMatch {
+                    modes: vec!["verified".into()],
+                    ..RuleMatch::default()
+                },
+                action: Action::Deny,
+                risk: RiskLevel::Medium,
+                reason: "verified mode disabled".into(),
+                requires_consent: false,
+                requires_payment: false,
+            });
+        }
+        rules.extend(config.rules.iter().cloned());
+        if !config.high_risk_terms.is_empty() {
+            rules.push(PolicyRule {
+                id: "high-risk-terms".into(),
+                when: RuleMatch {
+                    prompt_contains: config.high_risk_terms.clone(),
+                    ..RuleMatch::default()
+                },
+                action: Action::Escalate,
+                risk: RiskLevel::Medium,
+                reason: "high-risk content requires consent".into(),
+                requires_consent: true,
+                requires_payment: false,
+            });
+        }
+        Self {
+            config,
+            rules,
+            hash: String::new(),
+            signer: None,
+            profile: None,
+            profiles: BTreeMap::new(),
+        }
+    }
+
+    /// First matching rule wins; a request no rule matches is allowed at low risk.
+    pub fn evaluate(&self, request: &PolicyRequest<'_>) -> SafetyDecision {
+        self.rules
             .iter()
-            .find(|term| lower.contains(term.as_str()))
-        {
-            return Err(PolicyError::Blocked(term.clone()));
+            .find_map(|rule| rule.matched_spans(request).map(|spans| rule.decide(&spans)))
+            .unwrap_or_else(SafetyDecision::allow)
+    }
+
+    /// Dry run: evaluates every rule (not just up to the first match) and reports where each hit.
+    pub fn explain(&self, request: &PolicyRequest<'_>) -> PolicyExplanation {
+        let mut decision = None;
+        let rules = self
+            .rules
+            .iter()
+            .map(|rule| {
+                let spans = rule.matched_spans(request);
+                let decisive = decision.is_none() && spans.is_some();
+                if decisive {
+                    decision = spans.as_deref().map(|spans| rule.decide(spans));
+                }
+                RuleEvaluation {
+                    rule_id: rule.id.clone(),
+                    action: rule.action,
+                    risk: rule.risk,
+                    matched: spans.is_some(),
+                    decisive,
+                    spans: spans.unwrap_or_default(),
+                }
+            })
+            .collect();
+        PolicyExplanation {
+            policy_hash: self.hash.clone(),
+            profile: self.profile.clone(),
+            decision: decision.unwrap_or_else(SafetyDecision::allow),
+            rules,
         }
+    }
 
-        let high_risk_terms = self
-            .config
-            .high_risk_terms
+    pub fn check_prompt<'a>(
+        &self,
+        request: impl Into<PolicyRequest<'a>>,
+    ) -> Result<SafetyDecision, PolicyError> {
+        self.evaluate(&request.into()).into_result()
+    }
+
+    /// Detects personal data and rewrites it per `pii` config before the prompt goes anywhere else.
+    pub fn scrub_pii(&self, prompt: &str) -> Result<PiiScrub, PolicyError> {
+        let config = &self.config.pii;
+        let mut report = PiiReport::default();
+        if !config.enabled {
+            return Ok(PiiScrub {
+                text: prompt.to_string(),
+                vault: PiiVault::default(),
+                report,
+            });
+        }
+
+        let mut hits: Vec<(Range<usize>, PiiKind)> = Vec::new();
+        for (kind, pattern) in pii_patterns() {
+            let mut at = 0;
+            while let Some(found) = pattern.find_at(prompt, at) {
+                at = found.end();
+                let Some(range) = pii_match(*kind, pattern, prompt, found.range()) else {
+                    continue;
+                };
+                // Resume after the value, so a word it was cut from can start the next match.
+                at = range.end;
+                let overlaps = hits
+                    .iter()
+                    .any(|(r, _)| r.start < range.end && range.start < r.end);
+                if !overlaps {
+                    hits.push((range, *kind));
+                }
+            }
+        }
+        hits.sort_by_key(|(range, _)| range.start);
+
+        for (_, kind) in &hits {
+            *report.detected.entry(*kind).or_default() += 1;
+            report.actions.insert(*kind, config.action(*kind));
+        }
+        let blocked = report
+            .actions
             .iter()
-            .filter(|term| lower.contains(term.as_str()))
-            .cloned()
+            .filter(|(_, action)| **action == PiiAction::Block)
+            .map(|(kind, _)| kind.label().to_lowercase())
             .collect::<Vec<_>>();
+        if !blocked.is_empty() {
+            return Err(PolicyError::PiiBlocked(blocked.join(", ")));
+        }
 
-        if !high_risk_terms.is_empty() {
-            return Err(PolicyError::EscalationRequired(high_risk_terms.join(", ")));
+        let hash_key = blake3::derive_key("axiomhive pii hash v1", config.hash_salt.as_bytes());
+        let mut vault = PiiVault::default();
+        let mut counters = BTreeMap::<PiiKind, usize>::new();
+        let mut text = String::with_capacity(prompt.len());
+        let mut cursor = 0;
+        for (range, kind) in hits {
+            let original = &prompt[range.clone()];
+            let replacement = match config.action(kind) {
+                PiiAction::Allow | PiiAction::Block => continue,
+                PiiAction::Redact => format!("[{}]", kind.label()),
+                PiiAction::Hash => {
+                    let digest = blake3::keyed_hash(&hash_key, original.as_bytes()).to_hex();
+                    format!("[{}#{}]", kind.label(), &digest[..12])
+                }
+                PiiAction::Tokenize => {
+                    let n = counters.entry(kind).or_default();
+                    *n += 1;
+                    let token = format!("[{}_{}]", kind.label(), n);
+                    vault.entries.push((token.clone(), original.to_string()));
+                    token
+                }
+            };
+            text.push_str(&prompt[cursor..range.start]);
+            text.push_str(&replacement);
+            cursor = range.end;
         }
+        text.push_str(&prompt[cursor..]);
 
-        Ok(PolicyVerdict { high_risk_terms })
+        Ok(PiiScrub {
+            text,
+            vault,
+            report,
+        })
+    }
+
+    /// Screens structured request field names and the prompt for identity requests.
+    pub fn check_identity(&self, prompt: &str, fields: &[String]) -> IdentityVerdict {
+        let gate = &self.config.identity;
+        let mut verdict = IdentityVerdict {
+            action: IdentityAction::Allow,
+            matched_fields: Vec::new(),
+            matched_phrases: Vec::new(),
+            response: gate.response.clone(),
+        };
+        if !gate.enabled {
+            return verdict;
+        }
+        for field in &gate.fields {
+            if fields.iter().any(|name| name.eq_ignore_ascii_case(field)) {
+                verdict.matched_fields.push(field.clone());
+            }
+        }
+        for phrase in &gate.prompt_phrases {
+            if !match_ranges(prompt, phrase).is_empty() {
+                verdict.matched_phrases.push(phrase.clone());
+            }
+        }
+        if !verdict.matched_fields.is_empty() || !verdict.matched_phrases.is_empty() {
+            verdict.action = gate.action;
+        }
+        verdict
+    }
+
+    pub fn triage(&self, prompt: &str) -> TriageReport {
+        triage(prompt, &self.config.triage)
+    }
+
+    pub fn clarification(&self) -> &str {
+        &self.config.triage.clarification
+    }
+
+    pub fn check_output(&self, output: &str) -> OutputVerdict {
+        let policy = &self.config.output;
+        let mut action = OutputAction::Allow;
+        let mut matched_terms = Vec::new();
+        let mut ranges = Vec::new();
+
+        let screened = [
+            (&self.config.blocklist, policy.blocked_action),
+            (&self.config.high_risk_terms, policy.high_risk_action),
+        ];
+        for (terms, term_action) in screened {
+            for term in terms.iter() {
+                let hits = match_ranges(output, term);
+                if hits.is_empty() || term_action == OutputAction::Allow {
+                    continue;
+                }
+                matched_terms.push(term.clone());
+                action = action.max(term_action);
+                if term_action == OutputAction::Redact {
+                    ranges.extend(hits);
+                }
+            }
+        }
+
+        let redactions = ranges.len();
+        let output = if action == OutputAction::Redact {
+            redact(output, ranges, &policy.redaction_marker)
+        } else {
+            output.to_string()
+        };
+
+        OutputVerdict {
+            action,
+            matched_terms,
+            redactions,
+            output,
+        }
     }
 
     pub fn ensure_verified_enabled(&self) -> Result<(), PolicyError> {
@@ -115,4 +1181,75 @@ impl SafetyPolicy {
     pub fn allow_network_by_default(&self) -> bool {
         self.config.allow_network
     }
+
+    /// Whether any profile may go online; the node-level flag for the egress gate.
+    pub fn node_allows_network(&self) -> bool {
+        self.config.allow_network
+            || self
+                .profiles
+                .values()
+                .any(|profile| profile.config.allow_network)
+    }
+
+    pub fn ensure_media_allowed(&self, media: &[String]) -> Result<(), PolicyError> {
+        let allowed = &self.config.allowed_media;
+        match media.iter().find(|kind| {
+            !allowed.is_empty() && !allowed.iter().any(|a| a.eq_ignore_ascii_case(kind))
+        }) {
+            Some(kind) => Err(PolicyError::MediaNotAllowed(kind.clone())),
+            None => Ok(()),
+        }
+    }
+
+    pub fn price_multiplier(&self) -> f64 {
+        self.config.price_multiplier
+    }
+}
+
+/// Byte ranges of case-insensitive, non-overlapping occurrences of `term` in `text`.
+fn match_ranges(text: &str, term: &str) -> Vec<Range<usize>> {
+    let needle = term.to_lowercase().chars().collect::<Vec<_>>();
+    let mut ranges = Vec::new();
+    if needle.is_empty() {
+        return ranges;
+    }
+    let mut resume_at = 0;
+    for (start, _) in text.char_indices() {
+        if start < resume_at {
+            continue;
+        }
+        let mut matched = 0;
+        for (offset, ch) in text[start..].char_indices() {
+            let lowered = ch.to_lowercase().collect::<Vec<_>>();
+            if !needle[matched..].starts_with(&lowered) && !lowered.starts_with(&needle[matched..])
+            {
+                break;
+            }
+            matched = (matched + lowered.len()).min(needle.len());
+            if matched == needle.len() {
+                let end = start + offset + ch.len_utf8();
+                ranges.push(start..end);
+                resume_at = end;
+                break;
+            }
+        }
+    }
+    ranges
+}
+
+fn redact(text: &str, mut ranges: Vec<Range<usize>>, marker: &str) -> String {
+    ranges.sort_by_key(|r| r.start);
+    let mut redacted = String::with_capacity(text.len());
+    let mut cursor = 0;
+    for range in ranges {
+        if range.start < cursor {
+            cursor = cursor.max(range.end);
+            continue;
+        }
+        redacted.push_str(&text[cursor..range.start]);
+        redacted.push_str(marker);
+        cursor = range.end;
+    }
+    redacted.push_str(&text[cursor..]);
+    redacted
 }
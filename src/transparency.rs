@@ -0,0 +1,426 @@
+//! RFC 6962 Merkle tree over audit entry hashes, with signed tree heads and offline
+//! verification of inclusion and consistency proofs. The tree shape and audit paths are
+//! generic over `TreeHasher`, so DAG Merkle trees (`dag::merkletree`) share them.
+
+use crate::node_key::verify_signature;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+pub type TreeHash = [u8; 32];
+
+/// Hashing of an RFC 6962 tree's interior nodes. Leaves are passed in already hashed.
+pub trait TreeHasher {
+    type Hash: Clone + PartialEq;
+
+    /// Root of the tree with no leaves.
+    fn empty() -> Self::Hash;
+
+    fn node(left: &Self::Hash, right: &Self::Hash) -> Self::Hash;
+}
+
+/// The audit tree's hasher: `SHA-256(0x01 || left || right)`.
+pub struct Sha256Tree;
+
+impl TreeHasher for Sha256Tree {
+    type Hash = TreeHash;
+
+    fn empty() -> TreeHash {
+        Sha256::digest([]).into()
+    }
+
+    fn node(left: &TreeHash, right: &TreeHash) -> TreeHash {
+        node_hash(left, right)
+    }
+}
+
+/// Leaf hash of an audit entry: `SHA-256(0x00 || entry.hash)`.
+pub fn leaf_hash(entry_hash: &str) -> TreeHash {
+    let mut hasher = Sha256::new();
+    hasher.update([0x00]);
+    hasher.update(entry_hash.as_bytes());
+    hasher.finalize().into()
+}
+
+fn node_hash(left: &TreeHash, right: &TreeHash) -> TreeHash {
+    let mut hasher = Sha256::new();
+    hasher.update([0x01]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Largest power of two strictly below `n` (n >= 2).
+fn split_point(n: usize) -> usize {
+    let mut k = 1;
+    while k << 1 < n {
+        k <<= 1;
+    }
+    k
+}
+
+/// Merkle tree hash of already-hashed leaves under `H`.
+pub fn root_with<H: TreeHasher>(leaves: &[H::Hash]) -> H::Hash {
+    match leaves.len() {
+        0 => H::empty(),
+        1 => leaves[0].clone(),
+        n => {
+            let k = split_point(n);
+            H::node(&root_with::<H>(&leaves[..k]), &root_with::<H>(&leaves[k..]))
+        }
+    }
+}
+
+/// Audit path for leaf `index` under `H` (RFC 6962 section 2.1.1), lowest sibling first.
+pub fn path_with<H: TreeHasher>(leaves: &[H::Hash], index: usize) -> Vec<H::Hash> {
+    subtree_path(index, 0, leaves.len(), &|start, n| {
+        root_with::<H>(&leaves[start..start + n])
+    })
+}
+
+/// Audit path for leaf `index` of the `n` leaves from `start`, taking the hash of the `len`
+/// leaves from `at` from `subtree(at, len)`.
+fn subtree_path<T>(
+    index: usize,
+    start: usize,
+    n: usize,
+    subtree: &impl Fn(usize, usize) -> T,
+) -> Vec<T> {
+    if n <= 1 {
+        return Vec::new();
+    }
+    let k = split_point(n);
+    let (mut path, sibling) = if index < k {
+        (
+            subtree_path(index, start, k, subtree),
+            subtree(start + k, n - k),
+        )
+    } else {
+        (
+            subtree_path(index - k, start + k, n - k, subtree),
+            subtree(start, k),
+        )
+    };
+    path.push(sibling);
+    path
+}
+
+/// Consistency proof between the first `first` of the `n` leaves from `start` and all of
+/// them (RFC 6962 section 2.1.2), with subtree hashes as in `subtree_path`.
+fn subtree_consistency<T>(
+    first: usize,
+    start: usize,
+    n: usize,
+    complete: bool,
+    subtree: &impl Fn(usize, usize) -> T,
+) -> Vec<T> {
+    if first == n {
+        return if complete {
+            Vec::new()
+        } else {
+            vec![subtree(start, n)]
+        };
+    }
+    let k = split_point(n);
+    let (mut path, sibling) = if first <= k {
+        (
+            subtree_consistency(first, start, k, complete, subtree),
+            subtree(start + k, n - k),
+        )
+    } else {
+        (
+            subtree_consistency(first - k, start + k, n - k, false, subtree),
+            subtree(start, k),
+        )
+    };
+    path.push(sibling);
+    path
+}
+
+/// For each sibling on the audit path of leaf `index` in a tree of `tree_size` leaves,
+/// lowest first, whether it is on the left (RFC 9162 section 2.1.3.2). Empty when the
+/// index is out of range.
+pub fn sibling_sides(index: usize, tree_size: usize) -> Vec<bool> {
+    let mut sides = Vec::new();
+    if index >= tree_size {
+        return sides;
+    }
+    let (mut fn_, mut sn) = (index, tree_size - 1);
+    while sn > 0 {
+        let left = fn_ & 1 == 1 || fn_ == sn;
+        sides.push(left);
+        if left {
+            while fn_ & 1 == 0 && fn_ != 0 {
+                fn_ >>= 1;
+                sn >>= 1;
+            }
+        }
+        fn_ >>= 1;
+        sn >>= 1;
+    }
+    sides
+}
+
+/// Root reached from `leaf` along `path` under `H`; `None` if the path does not fit leaf
+/// `index` of a tree of `tree_size` leaves.
+pub fn path_root<H: TreeHasher>(
+    leaf: &H::Hash,
+    index: usize,
+    tree_size: usize,
+    path: &[H::Hash],
+) -> Option<H::Hash> {
+    let sides = sibling_sides(index, tree_size);
+    if index >= tree_size || sides.len() != path.len() {
+        return None;
+    }
+    Some(
+        path.iter()
+            .zip(sides)
+            .fold(leaf.clone(), |hash, (sibling, left)| {
+                if left {
+                    H::node(sibling, &hash)
+                } else {
+                    H::node(&hash, sibling)
+                }
+            }),
+    )
+}
+
+/// Merkle tree hash of already-hashed leaves; the empty tree hashes the empty string.
+pub fn tree_root(leaves: &[TreeHash]) -> TreeHash {
+    root_with::<Sha256Tree>(leaves)
+}
+
+/// Audit path for leaf `index` (RFC 6962 section 2.1.1).
+pub fn inclusion_path(leaves: &[TreeHash], index: usize) -> Vec<TreeHash> {
+    path_with::<Sha256Tree>(leaves, index)
+}
+
+/// Consistency proof between the first `first` leaves and all of `leaves` (section 2.1.2).
+pub fn consistency_path(leaves: &[TreeHash], first: usize) -> Vec<TreeHash> {
+    if first == 0 || first > leaves.len() {
+        return Vec::new();
+    }
+    subtree_consistency(first, 0, leaves.len(), true, &|start, n| {
+        tree_root(&leaves[start..start + n])
+    })
+}
+
+/// The audit tree kept incrementally: every complete power-of-two subtree is hashed once,
+/// when its last leaf is pushed. Roots, audit paths and consistency proofs for any prefix
+/// of the leaves then take O(log n) hashes instead of rehashing every leaf.
+#[derive(Debug, Clone, Default)]
+pub struct TreeCache {
+    /// `levels[h][i]` hashes leaves `i * 2^h .. (i + 1) * 2^h`; `levels[0]` are the leaves.
+    levels: Vec<Vec<TreeHash>>,
+}
+
+impl TreeCache {
+    /// Number of leaves.
+    pub fn len(&self) -> usize {
+        self.levels.first().map_or(0, Vec::len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Appends an already-hashed leaf and hashes the subtrees it completes.
+    pub fn push(&mut self, leaf: TreeHash) {
+        let mut hash = leaf;
+        for height in 0.. {
+            if self.levels.len() == height {
+                self.levels.push(Vec::new());
+            }
+            let level = &mut self.levels[height];
+            level.push(hash);
+            if level.len() % 2 == 1 {
+                break;
+            }
+            hash = node_hash(&level[level.len() - 2], &level[level.len() - 1]);
+        }
+    }
+
+    /// Hash of the `n` leaves from `start`, a subtree of the RFC 6962 split: either an
+    /// aligned power of two, read from the cache, or a tail split further.
+    fn subtree(&self, start: usize, n: usize) -> TreeHash {
+        if n == 0 {
+            return Sha256Tree::empty();
+        }
+        if n.is_power_of_two() && start.is_multiple_of(n) {
+            return self.levels[n.trailing_zeros() as usize][start / n];
+        }
+        let k = split_point(n);
+        node_hash(&self.subtree(start, k), &self.subtree(start + k, n - k))
+    }
+
+    /// Root of the tree over the first `tree_size` leaves; `None` past the end.
+    pub fn root(&self, tree_size: usize) -> Option<TreeHash> {
+        (tree_size <= self.len()).then(|| self.subtree(0, tree_size))
+    }
+
+    /// Audit path for leaf `index` in the tree of the first `tree_size` leaves; `None`
+    /// unless `index < tree_size <= len`.
+    pub fn inclusion_path(&self, index: usize, tree_size: usize) -> Option<Vec<TreeHash>> {
+        (index < tree_size && tree_size <= self.len())
+            .then(|| subtree_path(index, 0, tree_size, &|start, n| self.subtree(start, n)))
+    }
+
+    /// Consistency proof between the trees of the first `first` and `second` leaves; `None`
+    /// unless `first <= second <= len`.
+    pub fn consistency_path(&self, first: usize, second: usize) -> Option<Vec<TreeHash>> {
+        if first > second || second > self.len() {
+            return None;
+        }
+        if first == 0 {
+            return Some(Vec::new());
+        }
+        Some(subtree_consistency(first, 0, second, true, &|start, n| {
+            self.subtree(start, n)
+        }))
+    }
+}
+
+/// Checks an audit path against `root` (RFC 9162 section 2.1.3.2).
+pub fn verify_inclusion(
+    leaf: &TreeHash,
+    index: usize,
+    tree_size: usize,
+    path: &[TreeHash],
+    root: &TreeHash,
+) -> bool {
+    path_root::<Sha256Tree>(leaf, index, tree_size, path).as_ref() == Some(root)
+}
+
+/// Checks that the tree of size `second` extends the tree of size `first`
+/// (RFC 9162 section 2.1.4.2).
+pub fn verify_consistency(
+    first: usize,
+    second: usize,
+    first_root: &TreeHash,
+    second_root: &TreeHash,
+    proof: &[TreeHash],
+) -> bool {
+    if first > second {
+        return false;
+    }
+    if first == second {
+        return proof.is_empty() && first_root == second_root;
+    }
+    if first == 0 {
+        return proof.is_empty();
+    }
+    let mut path = proof.to_vec();
+    if first.is_power_of_two() {
+        path.insert(0, *first_root);
+    }
+    let Some((seed, rest)) = path.split_first() else {
+        return false;
+    };
+    let (mut fn_, mut sn) = (first - 1, second - 1);
+    while fn_ & 1 == 1 {
+        fn_ >>= 1;
+        sn >>= 1;
+    }
+    let (mut fr, mut sr) = (*seed, *seed);
+    for c in rest {
+        if sn == 0 {
+            return false;
+        }
+        if fn_ & 1 == 1 || fn_ == sn {
+            fr = node_hash(c, &fr);
+            sr = node_hash(c, &sr);
+            while fn_ & 1 == 0 && fn_ != 0 {
+                fn_ >>= 1;
+                sn >>= 1;
+            }
+        } else {
+            sr = node_hash(&sr, c);
+        }
+        fn_ >>= 1;
+        sn >>= 1;
+    }
+    sn == 0 && fr == *first_root && sr == *second_root
+}
+
+fn decode(hash: &str) -> Option<TreeHash> {
+    hex::decode(hash).ok()?.try_into().ok()
+}
+
+fn decode_all(hashes: &[String]) -> Option<Vec<TreeHash>> {
+    hashes.iter().map(|h| decode(h)).collect()
+}
+
+/// Root of the audit tree at `tree_size`, signed by the node key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedTreeHead {
+    pub tree_size: usize,
+    pub root_hash: String,
+    pub timestamp: String,
+    pub node_key: Option<String>,
+    pub signature: Option<String>,
+}
+
+impl SignedTreeHead {
+    pub fn message(&self) -> String {
+        format!(
+            "axiomhive tree head v1:{}:{}:{}",
+            self.tree_size, self.root_hash, self.timestamp
+        )
+    }
+
+    /// Whether the head is signed by `public_key`.
+    pub fn verify(&self, public_key: &str) -> bool {
+        self.node_key.as_deref() == Some(public_key)
+            && self.signature.as_deref().is_some_and(|signature| {
+                verify_signature(public_key, self.message().as_bytes(), signature)
+            })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InclusionProof {
+    pub request_id: Uuid,
+    pub entry_hash: String,
+    pub leaf_index: usize,
+    pub tree_size: usize,
+    pub audit_path: Vec<String>,
+}
+
+impl InclusionProof {
+    /// Checks the proof against a tree head's root; pair with `SignedTreeHead::verify`.
+    pub fn verify(&self, root_hash: &str) -> bool {
+        match (decode_all(&self.audit_path), decode(root_hash)) {
+            (Some(path), Some(root)) => verify_inclusion(
+                &leaf_hash(&self.entry_hash),
+                self.leaf_index,
+                self.tree_size,
+                &path,
+                &root,
+            ),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsistencyProof {
+    pub first_size: usize,
+    pub second_size: usize,
+    pub proof: Vec<String>,
+}
+
+impl ConsistencyProof {
+    pub fn verify(&self, first_root: &str, second_root: &str) -> bool {
+        match (
+            decode_all(&self.proof),
+            decode(first_root),
+            decode(second_root),
+        ) {
+            (Some(proof), Some(first), Some(second)) => {
+                verify_consistency(self.first_size, self.second_size, &first, &second, &proof)
+            }
+            _ => false,
+        }
+    }
+}
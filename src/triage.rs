@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+
+/// Words that point at context the prompt does not carry.
+const VAGUE_REFERENCES: &[&str] = &[
+    "it", "this", "these", "those", "they", "them", "above", "previous", "same", "again", "stuff",
+    "thing", "things",
+];
+
+/// Instructions that name a task but not what it applies to; alone they are a prompt
+/// missing its subject.
+const BARE_INSTRUCTIONS: &[&str] = &[
+    "answer",
+    "continue",
+    "create",
+    "describe",
+    "do",
+    "explain",
+    "fix",
+    "generate",
+    "help",
+    "list",
+    "make",
+    "rewrite",
+    "summarize",
+    "translate",
+    "write",
+];
+
+/// Shortest single word treated as naming a topic rather than a fragment.
+const MIN_TOPIC_CHARS: usize = 3;
+
+/// Pre-model triage; `threshold` mirrors `triage_threshold` in `configs/directive.yaml`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TriageConfig {
+    pub enabled: bool,
+    /// Prompts scoring below this confidence are sent back for clarification.
+    pub threshold: f64,
+    pub clarification: String,
+}
+
+impl Default for TriageConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 0.9,
+            clarification: "Need clarification".into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TriageOutcome {
+    Act,
+    Ask,
+}
+
+/// One ambiguity signal and how much confidence it cost.
+#[derive(Debug, Clone, Serialize)]
+pub struct TriageSignal {
+    pub kind: String,
+    pub penalty: f64,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TriageReport {
+    pub confidence: f64,
+    pub threshold: f64,
+    pub outcome: TriageOutcome,
+    pub signals: Vec<TriageSignal>,
+}
+
+/// Scores prompt ambiguity from length and unresolved references. A single word is scored
+/// on what it is: a topic acts, while a fragment or an instruction without a subject asks.
+/// Axiom rules constrain the output, not the prompt, so they play no part here.
+pub fn triage(prompt: &str, config: &TriageConfig) -> TriageReport {
+    let words = prompt
+        .split_whitespace()
+        .map(|w| {
+            w.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>();
+    let mut signals = Vec::new();
+
+    match words.as_slice() {
+        [] => signals.push(TriageSignal {
+            kind: "length".into(),
+            penalty: 1.0,
+            detail: "0 word(s)".into(),
+        }),
+        [word] if word.chars().count() < MIN_TOPIC_CHARS => signals.push(TriageSignal {
+            kind: "length".into(),
+            penalty: 0.2,
+            detail: format!("single fragment \"{word}\""),
+        }),
+        [word] if BARE_INSTRUCTIONS.contains(&word.as_str()) => signals.push(TriageSignal {
+            kind: "missing_subject".into(),
+            penalty: 0.2,
+            detail: format!("\"{word}\" without what to {word}"),
+        }),
+        _ => {}
+    }
+
+    let vague = words
+        .iter()
+        .filter(|w| VAGUE_REFERENCES.contains(&w.as_str()))
+        .cloned()
+        .collect::<Vec<_>>();
+    if !vague.is_empty() {
+        signals.push(TriageSignal {
+            kind: "vague_reference".into(),
+            penalty: (0.4 * vague.len() as f64 / words.len() as f64).min(0.4),
+            detail: vague.join(", "),
+        });
+    }
+
+    let penalty: f64 = signals.iter().map(|s| s.penalty).sum();
+    let confidence = ((1.0 - penalty).clamp(0.0, 1.0) * 1000.0).round() / 1000.0;
+    let outcome = if config.enabled && confidence < config.threshold {
+        TriageOutcome::Ask
+    } else {
+        TriageOutcome::Act
+    };
+    TriageReport {
+        confidence,
+        threshold: config.threshold,
+        outcome,
+        signals,
+    }
+}
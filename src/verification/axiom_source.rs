@@ -0,0 +1,184 @@
+use crate::{
+    egress::{audit_target, EgressError, EgressGate, EgressKind},
+    verification::axiom_checker::{parse_axiom_set, AxiomSet, VerificationError},
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum AxiomSourceError {
+    #[error(transparent)]
+    Invalid(#[from] VerificationError),
+    #[error("remote axiom sets must use https: {0}")]
+    InsecureUrl(String),
+    #[error("remote axiom set {0} must be pinned with axiom_hash")]
+    Unpinned(String),
+    #[error("malformed axiom hash: {0}")]
+    MalformedHash(String),
+    #[error("axiom set hash mismatch: expected {expected}, got {actual}")]
+    HashMismatch { expected: String, actual: String },
+    #[error("axiom set {0} is not cached locally")]
+    NotCached(String),
+    #[error(transparent)]
+    Egress(#[from] EgressError),
+    #[error("axiom cache error: {0}")]
+    Cache(#[from] std::io::Error),
+}
+
+/// Where a resolved axiom set came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AxiomOrigin {
+    Inline,
+    Cache,
+    Remote,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResolvedAxioms {
+    pub set: AxiomSet,
+    pub origin: AxiomOrigin,
+    /// Remote URL without credentials or query, as the egress gate audits it.
+    pub url: Option<String>,
+    /// Hex SHA-256 of the raw axiom set bytes.
+    pub hash: String,
+}
+
+/// Resolves inline JSON, `https://` URLs and `sha256:` content hashes to axiom sets.
+pub struct AxiomResolver {
+    cache_dir: PathBuf,
+    gate: Arc<EgressGate>,
+}
+
+impl AxiomResolver {
+    pub fn new(cache_dir: impl AsRef<Path>, gate: Arc<EgressGate>) -> Self {
+        Self {
+            cache_dir: cache_dir.as_ref().to_path_buf(),
+            gate,
+        }
+    }
+
+    /// Remote sets are served from the pinned-hash cache when present and only fetched
+    /// through the egress gate when `allow_network` is set.
+    pub async fn resolve(
+        &self,
+        spec: &str,
+        expected_hash: Option<&str>,
+        allow_network: bool,
+    ) -> Result<ResolvedAxioms, AxiomSourceError> {
+        let spec = spec.trim();
+        // URLs are recorded in the audit log, so only their sanitized form leaves here.
+        if spec.starts_with("http://") {
+            return Err(AxiomSourceError::InsecureUrl(audit_target(spec)));
+        }
+        if spec.starts_with("https://") {
+            let expected = expected_hash
+                .ok_or_else(|| AxiomSourceError::Unpinned(audit_target(spec)))
+                .and_then(normalize_hash)?;
+            let mut resolved = match self.read_cache(&expected)? {
+                Some(resolved) => resolved,
+                None => self.fetch(spec, &expected, allow_network).await?,
+            };
+            resolved.url = Some(audit_target(spec));
+            return Ok(resolved);
+        }
+        if let Some(hash) = content_hash(spec) {
+            let hash = normalize_hash(hash)?;
+            return self
+                .read_cache(&hash)?
+                .ok_or(AxiomSourceError::NotCached(hash));
+        }
+
+        let set = parse_axiom_set(spec)?;
+        Ok(ResolvedAxioms {
+            set,
+            origin: AxiomOrigin::Inline,
+            url: None,
+            hash: sha256_hex(spec.as_bytes()),
+        })
+    }
+
+    pub fn cache_path(&self, hash: &str) -> PathBuf {
+        self.cache_dir.join(format!("{hash}.json"))
+    }
+
+    async fn fetch(
+        &self,
+        url: &str,
+        expected: &str,
+        allow_network: bool,
+    ) -> Result<ResolvedAxioms, AxiomSourceError> {
+        let raw = self
+            .gate
+            .get(EgressKind::AxiomFetch, url, allow_network)
+            .await?;
+        let hash = sha256_hex(&raw);
+        if hash != expected {
+            return Err(AxiomSourceError::HashMismatch {
+                expected: expected.to_string(),
+                actual: hash,
+            });
+        }
+        let set = parse_axiom_set(&String::from_utf8_lossy(&raw))?;
+
+        fs::create_dir_all(&self.cache_dir)?;
+        let path = self.cache_path(&hash);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, &raw)?;
+        fs::rename(&tmp, &path)?;
+
+        Ok(ResolvedAxioms {
+            set,
+            origin: AxiomOrigin::Remote,
+            url: None,
+            hash,
+        })
+    }
+
+    /// Cached files are re-hashed on read so a tampered cache is never trusted.
+    fn read_cache(&self, hash: &str) -> Result<Option<ResolvedAxioms>, AxiomSourceError> {
+        let path = self.cache_path(hash);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let raw = fs::read(&path)?;
+        let actual = sha256_hex(&raw);
+        if actual != hash {
+            return Err(AxiomSourceError::HashMismatch {
+                expected: hash.to_string(),
+                actual,
+            });
+        }
+        let set = parse_axiom_set(&String::from_utf8_lossy(&raw))?;
+        Ok(Some(ResolvedAxioms {
+            set,
+            origin: AxiomOrigin::Cache,
+            url: None,
+            hash: actual,
+        }))
+    }
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+fn content_hash(spec: &str) -> Option<&str> {
+    spec.strip_prefix("sha256:").or_else(|| {
+        (spec.len() == 64 && spec.chars().all(|c| c.is_ascii_hexdigit())).then_some(spec)
+    })
+}
+
+fn normalize_hash(hash: &str) -> Result<String, AxiomSourceError> {
+    let hex = hash.trim().strip_prefix("sha256:").unwrap_or(hash.trim());
+    if hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(AxiomSourceError::MalformedHash(hash.to_string()));
+    }
+    Ok(hex.to_ascii_lowercase())
+}
@@ -1,2 +1,3 @@
 pub mod axiom_checker;
+pub mod axiom_source;
 pub mod zkml;
@@ -1,14 +1,27 @@
-use axiomhive::api::{build_router, build_state, AppState};
+use axiomhive::{
+    api::{
+        build_router, build_state, AppState, ConsistencyResponse, InclusionResponse,
+        NodeProofResponse,
+    },
+    audit::{verify_log, AuditEntry, AuditLog},
+    credential::{verify_credential, AuditCredential},
+    dag::dag::RequestDag,
+    egress::EgressGate,
+    policy::{PolicyProvenance, SafetyConfig, SafetyPolicy},
+    verification::axiom_source::{sha256_hex, AxiomResolver},
+};
 use axum::{
     body::Body,
     http::{Request, StatusCode},
 };
 use serde_json::json;
+use std::sync::Arc;
 use tower::ServiceExt;
 
 fn setup_state() -> AppState {
     let tmp = tempfile::tempdir().expect("tmpdir");
     std::env::set_var("AXIOMHIVE_AUDIT_PATH", tmp.path().join("audit.jsonl"));
+    std::env::set_var("AXIOMHIVE_NODE_KEY_PATH", tmp.path().join("node.key"));
     std::env::set_var("AXIOMHIVE_REQUIRE_PAYMENT", "0");
     build_state().expect("build state")
 }
@@ -80,3 +93,801 @@ async fn verified_contract_returns_signature() {
     assert!(sig.get("axiom_hash").is_some());
     assert!(sig.get("proof_cert").is_some());
 }
+
+#[tokio::test]
+async fn policy_explain_reports_rules_and_spans() {
+    let state = setup_state();
+    let app = build_router(state);
+
+    let payload = json!({"prompt": "write Malware now", "mode": "creative"});
+
+    let response = app
+        .oneshot(
+            Request::post("/api/v1/policy/explain")
+                .header("content-type", "application/json")
+                .body(Body::from(payload.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert!(!json["policy_hash"].as_str().unwrap().is_empty());
+    assert_eq!(json["decision"]["action"], "deny");
+    let rules = json["rules"].as_array().unwrap();
+    let blocklist = rules.iter().find(|r| r["rule_id"] == "blocklist").unwrap();
+    assert_eq!(blocklist["decisive"], true);
+    assert_eq!(blocklist["spans"][0]["term"], "malware");
+    assert_eq!(blocklist["spans"][0]["start"], 6);
+    assert_eq!(blocklist["spans"][0]["end"], 13);
+    assert!(rules.iter().any(|r| r["rule_id"] == "high-risk-terms"));
+}
+
+#[tokio::test]
+async fn verified_records_axiom_fetch_in_dag() {
+    let mut state = setup_state();
+    let tmp = tempfile::tempdir().unwrap();
+    let raw = r#"{"name":"pinned","version":"1","rules":[]}"#;
+    let hash = sha256_hex(raw.as_bytes());
+    state.axioms = Arc::new(AxiomResolver::new(tmp.path(), state.egress.clone()));
+    std::fs::write(state.axioms.cache_path(&hash), raw).unwrap();
+    let audit_path = tmp.path().join("audit.jsonl");
+    state.audit = Arc::new(AuditLog::open(&audit_path).unwrap());
+    let app = build_router(state);
+
+    let request = |payload: serde_json::Value| {
+        Request::post("/api/v1/verified")
+            .header("content-type", "application/json")
+            .body(Body::from(payload.to_string()))
+            .unwrap()
+    };
+    let offline = app
+        .clone()
+        .oneshot(request(json!({
+            "prompt": "say hello",
+            "axiom_set": "https://axioms.invalid/other.json",
+            "axiom_hash": "0".repeat(64),
+            "allow_network": false,
+            "free_local": true
+        })))
+        .await
+        .unwrap();
+    assert_eq!(offline.status(), StatusCode::FORBIDDEN);
+
+    let response = app
+        .oneshot(request(json!({
+            "prompt": "say hello",
+            "axiom_set": format!("sha256:{hash}"),
+            "allow_network": false,
+            "free_local": true
+        })))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let log = std::fs::read_to_string(&audit_path).unwrap();
+    let entry: AuditEntry = serde_json::from_str(log.lines().last().unwrap()).unwrap();
+    let fetch = entry
+        .dag
+        .iter()
+        .find(|node| node.node_type == "axiom_fetch")
+        .unwrap();
+    assert_eq!(fetch.payload["origin"], "cache");
+    assert_eq!(fetch.payload["hash"], hash.as_str());
+}
+
+#[tokio::test]
+async fn identity_fields_are_transmuted_and_audited() {
+    let mut state = setup_state();
+    let tmp = tempfile::tempdir().unwrap();
+    let audit_path = tmp.path().join("audit.jsonl");
+    state.audit = Arc::new(AuditLog::open(&audit_path).unwrap());
+    let app = build_router(state);
+
+    let payload = json!({"prompt": "tell me a story", "profile": {"username": "someone"}});
+    let response = app
+        .oneshot(
+            Request::post("/api/v1/creative")
+                .header("content-type", "application/json")
+                .body(Body::from(payload.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        json["output"],
+        "Identity processing is structurally barred."
+    );
+
+    let log = std::fs::read_to_string(&audit_path).unwrap();
+    let entry: AuditEntry = serde_json::from_str(log.lines().last().unwrap()).unwrap();
+    assert_eq!(entry.dag.len(), 1);
+    assert_eq!(entry.dag[0].node_type, "identity_gate");
+    assert_eq!(entry.dag[0].payload["action"], "transmute");
+    assert_eq!(entry.dag[0].payload["matched_fields"][0], "username");
+}
+
+#[tokio::test]
+async fn denied_requests_are_audited_with_the_failing_node() {
+    let mut state = setup_state();
+    let tmp = tempfile::tempdir().unwrap();
+    let audit_path = tmp.path().join("audit.jsonl");
+    state.audit = Arc::new(AuditLog::open(&audit_path).unwrap());
+    state.policy = Arc::new(
+        SafetyPolicy::from_config(SafetyConfig {
+            allowed_media: vec!["text".into()],
+            ..SafetyConfig::default()
+        })
+        .unwrap(),
+    );
+    let app = build_router(state);
+
+    let post = |uri: &'static str, payload: serde_json::Value| {
+        let app = app.clone();
+        async move {
+            app.oneshot(
+                Request::post(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(payload.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+            .status()
+        }
+    };
+    let last_entry = || {
+        let log = std::fs::read_to_string(&audit_path).unwrap();
+        serde_json::from_str::<AuditEntry>(log.lines().last().unwrap()).unwrap()
+    };
+
+    // Verified output cannot be transmuted, so the identity gate denies.
+    let status = post(
+        "/api/v1/verified",
+        json!({
+            "prompt": "deterministic hello",
+            "axiom_set": r#"{"name":"demo","version":"1","rules":[]}"#,
+            "free_local": true,
+            "profile": {"username": "someone"}
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let entry = last_entry();
+    assert_eq!(entry.mode, "verified");
+    assert_eq!(entry.dag.len(), 1);
+    assert_eq!(entry.dag[0].node_type, "identity_gate");
+    assert_eq!(entry.dag[0].payload["action"], "transmute");
+    assert_eq!(entry.dag[0].payload["matched_fields"][0], "username");
+
+    let status = post("/api/v1/creative", json!({"prompt": "write Malware now"})).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let entry = last_entry();
+    assert_eq!(entry.mode, "creative");
+    let denied = entry.dag.last().unwrap();
+    assert_eq!(denied.node_type, "policy_check");
+    assert_eq!(denied.payload["status"], 403);
+    let pii_scan = entry
+        .dag
+        .iter()
+        .find(|n| n.node_type == "pii_scan")
+        .unwrap();
+    assert_eq!(denied.parents, vec![pii_scan.id.clone()]);
+    assert!(!entry.dag.iter().any(|n| n.node_type == "model_run"));
+
+    // A disallowed media kind is denied by the policy check, after the PII scan ran.
+    let status = post(
+        "/api/v1/creative",
+        json!({"prompt": "describe the picture", "media": ["video"]}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+    let entry = last_entry();
+    let denied = entry.dag.last().unwrap();
+    assert_eq!(denied.node_type, "policy_check");
+    assert_eq!(denied.payload["error"], "media kind not allowed: video");
+    assert!(entry.dag.iter().any(|n| n.node_type == "pii_scan"));
+    let report = verify_log(&audit_path, None, None).unwrap();
+    assert!(report.is_intact(), "{:?}", report.issues);
+}
+
+#[tokio::test]
+async fn network_requests_on_an_offline_node_are_denied_at_the_egress_gate() {
+    let mut state = setup_state();
+    let tmp = tempfile::tempdir().unwrap();
+    let audit_path = tmp.path().join("audit.jsonl");
+    state.audit = Arc::new(AuditLog::open(&audit_path).unwrap());
+    state.egress = Arc::new(
+        EgressGate::offline().with_audit(state.audit.clone(), PolicyProvenance::default()),
+    );
+    let app = build_router(state);
+
+    let payload = json!({
+        "prompt": "deterministic hello",
+        "axiom_set": "https://axioms.example/demo.json?token=secret",
+        "axiom_hash": "sha256:".to_string() + &"0".repeat(64),
+        "allow_network": true,
+        "free_local": true
+    });
+    let response = app
+        .oneshot(
+            Request::post("/api/v1/verified")
+                .header("content-type", "application/json")
+                .body(Body::from(payload.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+    let entries: Vec<AuditEntry> = std::fs::read_to_string(&audit_path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let denied = entries.iter().find(|e| e.mode == "egress").unwrap();
+    assert_eq!(denied.dag[0].node_type, "egress_denied");
+    assert_eq!(denied.dag[0].payload["kind"], "axiom_fetch");
+    assert_eq!(
+        denied.dag[0].payload["target"],
+        "https://axioms.example/demo.json"
+    );
+    let request = entries.last().unwrap();
+    assert_eq!(request.mode, "verified");
+    assert_eq!(request.dag.last().unwrap().node_type, "axiom_fetch");
+}
+
+#[tokio::test]
+async fn ambiguous_prompt_returns_clarification() {
+    let mut state = setup_state();
+    let tmp = tempfile::tempdir().unwrap();
+    let audit_path = tmp.path().join("audit.jsonl");
+    state.audit = Arc::new(AuditLog::open(&audit_path).unwrap());
+    let app = build_router(state);
+
+    let response = app
+        .oneshot(
+            Request::post("/api/v1/creative")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({"prompt": "do it again"}).to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["status"], "ask");
+    assert!(json.get("output").is_none());
+    assert_eq!(json["triage"]["outcome"], "ask");
+
+    let log = std::fs::read_to_string(&audit_path).unwrap();
+    let entry: AuditEntry = serde_json::from_str(log.lines().last().unwrap()).unwrap();
+    let node = entry.dag.last().unwrap();
+    assert_eq!(node.node_type, "triage");
+    assert_eq!(node.payload["outcome"], "ask");
+    assert!(!entry.dag.iter().any(|node| node.node_type == "model_run"));
+}
+
+#[tokio::test]
+async fn admin_audit_verify_requires_token() {
+    let mut state = setup_state();
+    let tmp = tempfile::tempdir().unwrap();
+    state.audit = Arc::new(AuditLog::open(tmp.path().join("audit.jsonl")).unwrap());
+    state.admin_token = None;
+    let disabled = build_router(state.clone())
+        .oneshot(
+            Request::get("/api/v1/admin/audit/verify")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(disabled.status(), StatusCode::FORBIDDEN);
+
+    state.admin_token = Some("s3cret".into());
+    let app = build_router(state);
+    let wrong = app
+        .clone()
+        .oneshot(
+            Request::get("/api/v1/admin/audit/verify")
+                .header("x-admin-token", "guess")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(wrong.status(), StatusCode::UNAUTHORIZED);
+
+    let response = app
+        .oneshot(
+            Request::get("/api/v1/admin/audit/verify")
+                .header("x-admin-token", "s3cret")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["issues"], json!([]));
+}
+
+#[tokio::test]
+async fn node_key_is_published_and_signs_audit_entries() {
+    let mut state = setup_state();
+    let tmp = tempfile::tempdir().unwrap();
+    let audit_path = tmp.path().join("audit.jsonl");
+    state.audit = Arc::new(
+        AuditLog::open(&audit_path)
+            .unwrap()
+            .with_node_key(state.node_key.clone()),
+    );
+    let public_key = state.node_key.public_key_hex();
+    let app = build_router(state);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::get("/api/v1/node/key")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["algorithm"], "ed25519");
+    assert_eq!(json["public_key"], public_key.as_str());
+
+    let response = app
+        .oneshot(
+            Request::post("/api/v1/creative")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({"prompt": "hello creative"}).to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let report = verify_log(&audit_path, None, Some(&public_key)).unwrap();
+    assert!(report.is_intact(), "{:?}", report.issues);
+}
+
+#[tokio::test]
+async fn audit_proofs_are_served_with_signed_tree_heads() {
+    let mut state = setup_state();
+    let tmp = tempfile::tempdir().unwrap();
+    state.audit = Arc::new(
+        AuditLog::open(tmp.path().join("audit.jsonl"))
+            .unwrap()
+            .with_node_key(state.node_key.clone()),
+    );
+    let public_key = state.node_key.public_key_hex();
+    let app = build_router(state);
+
+    let mut request_ids = Vec::new();
+    for _ in 0..3 {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::post("/api/v1/creative")
+                    .header("content-type", "application/json")
+                    .body(Body::from(json!({"prompt": "hello creative"}).to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        request_ids.push(json["request_id"].as_str().unwrap().to_string());
+    }
+
+    let get = |uri: String| {
+        let app = app.clone();
+        async move {
+            let response = app
+                .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let status = response.status();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            (status, body)
+        }
+    };
+
+    let (status, body) = get(format!("/api/v1/audit/proof/inclusion/{}", request_ids[1])).await;
+    assert_eq!(status, StatusCode::OK);
+    let inclusion: InclusionResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(inclusion.tree_head.tree_size, 3);
+    assert!(inclusion.tree_head.verify(&public_key));
+    assert!(inclusion.proof.verify(&inclusion.tree_head.root_hash));
+
+    let (status, body) = get("/api/v1/audit/proof/consistency?first=1&second=3".into()).await;
+    assert_eq!(status, StatusCode::OK);
+    let consistency: ConsistencyResponse = serde_json::from_slice(&body).unwrap();
+    assert!(consistency.first.verify(&public_key));
+    assert!(consistency
+        .proof
+        .verify(&consistency.first.root_hash, &consistency.second.root_hash));
+    assert_eq!(consistency.second.root_hash, inclusion.tree_head.root_hash);
+
+    let (status, _) = get(format!(
+        "/api/v1/audit/proof/inclusion/{}",
+        uuid::Uuid::new_v4()
+    ))
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _) = get("/api/v1/audit/proof/consistency?first=2&second=9".into()).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn admin_redaction_removes_payload_and_keeps_chain() {
+    let mut state = setup_state();
+    let tmp = tempfile::tempdir().unwrap();
+    let audit_path = tmp.path().join("audit.jsonl");
+    state.audit = Arc::new(
+        AuditLog::open(&audit_path)
+            .unwrap()
+            .with_node_key(state.node_key.clone()),
+    );
+    state.admin_token = Some("s3cret".into());
+    let app = build_router(state.clone());
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::post("/api/v1/creative")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({"prompt": "erase me later"}).to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let logged: AuditEntry = serde_json::from_str(
+        std::fs::read_to_string(&audit_path)
+            .unwrap()
+            .lines()
+            .last()
+            .unwrap(),
+    )
+    .unwrap();
+    let node_id = logged.dag[0].id.clone();
+
+    let redact = |request_id: uuid::Uuid| {
+        Request::post("/api/v1/admin/audit/redact")
+            .header("x-admin-token", "s3cret")
+            .header("content-type", "application/json")
+            .body(Body::from(
+                json!({"request_id": request_id, "node_id": node_id, "reason": "erasure request"})
+                    .to_string(),
+            ))
+            .unwrap()
+    };
+    let missing = app
+        .clone()
+        .oneshot(redact(uuid::Uuid::new_v4()))
+        .await
+        .unwrap();
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    let response = app
+        .clone()
+        .oneshot(redact(logged.request_id))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let redacted: AuditEntry = serde_json::from_slice(&body).unwrap();
+    assert_eq!(redacted.hash, logged.hash);
+    assert!(redacted.dag[0].payload.is_null());
+    let again = app.oneshot(redact(logged.request_id)).await.unwrap();
+    assert_eq!(again.status(), StatusCode::BAD_REQUEST);
+
+    let report = verify_log(&audit_path, None, Some(&state.node_key.public_key_hex())).unwrap();
+    assert!(report.is_intact(), "{:?}", report.issues);
+}
+
+#[tokio::test]
+async fn admin_tail_streams_committed_entries_as_events() {
+    use futures::StreamExt;
+
+    let mut state = setup_state();
+    let tmp = tempfile::tempdir().unwrap();
+    state.audit = Arc::new(AuditLog::open(tmp.path().join("audit.jsonl")).unwrap());
+    state.admin_token = Some("s3cret".into());
+    let app = build_router(state.clone());
+    let tail = |query: &str| {
+        Request::get(format!("/api/v1/admin/audit/tail{query}"))
+            .header("x-admin-token", "s3cret")
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let unknown = app.clone().oneshot(tail("?after=deadbeef")).await.unwrap();
+    assert_eq!(unknown.status(), StatusCode::NOT_FOUND);
+    let response = app.clone().oneshot(tail("?mode=creative")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "text/event-stream");
+
+    let creative = app
+        .clone()
+        .oneshot(
+            Request::post("/api/v1/creative")
+                .header("content-type", "application/json")
+                .body(Body::from(json!({"prompt": "watch me"}).to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(creative.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let request_id = serde_json::from_slice::<serde_json::Value>(&body).unwrap()["request_id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let mut stream = response.into_body().into_data_stream();
+    let mut frame = String::new();
+    while !frame.contains("\n\n") {
+        let chunk = tokio::time::timeout(std::time::Duration::from_secs(5), stream.next())
+            .await
+            .expect("no audit event")
+            .unwrap()
+            .unwrap();
+        frame.push_str(std::str::from_utf8(&chunk).unwrap());
+    }
+    let field = |name: &str| {
+        frame
+            .lines()
+            .find_map(|line| line.strip_prefix(&format!("{name}:")))
+            .unwrap()
+            .trim()
+            .to_string()
+    };
+    assert_eq!(field("event"), "audit");
+    let event: serde_json::Value = serde_json::from_str(&field("data")).unwrap();
+    assert_eq!(event["index"], 0);
+    assert_eq!(event["entry"]["request_id"], request_id.as_str());
+    assert_eq!(field("id"), event["entry"]["hash"].as_str().unwrap());
+
+    let resumed = app
+        .oneshot(tail(&format!("?after={}", field("id"))))
+        .await
+        .unwrap();
+    assert_eq!(resumed.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn admin_credential_export_packages_the_c0_signature() {
+    let mut state = setup_state();
+    let tmp = tempfile::tempdir().unwrap();
+    state.audit = Arc::new(
+        AuditLog::open(tmp.path().join("audit.jsonl"))
+            .unwrap()
+            .with_node_key(state.node_key.clone()),
+    );
+    state.admin_token = Some("s3cret".into());
+    let app = build_router(state.clone());
+
+    let payload = json!({
+        "prompt": "deterministic hello",
+        "axiom_set": r#"{"name":"demo","version":"1","rules":[{"id":"contains-hello","must_contain":["hello"]}]}"#,
+        "free_local": true
+    });
+    let response = app
+        .clone()
+        .oneshot(
+            Request::post("/api/v1/verified")
+                .header("content-type", "application/json")
+                .body(Body::from(payload.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let verified: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let request_id = verified["request_id"].as_str().unwrap();
+
+    let export = |id: &str| {
+        Request::get(format!("/api/v1/admin/audit/credential/{id}"))
+            .header("x-admin-token", "s3cret")
+            .body(Body::empty())
+            .unwrap()
+    };
+    let missing = app
+        .clone()
+        .oneshot(export(&uuid::Uuid::new_v4().to_string()))
+        .await
+        .unwrap();
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+    let response = app.oneshot(export(request_id)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let credential: AuditCredential = serde_json::from_slice(&body).unwrap();
+    assert_eq!(
+        serde_json::to_value(credential.credential_subject.c0_signature.as_ref().unwrap()).unwrap(),
+        verified["c0_signature"]
+    );
+    let dag = RequestDag {
+        nodes: credential.credential_subject.audit_entry.dag.clone(),
+    };
+    let model_run = dag
+        .nodes
+        .iter()
+        .find(|node| node.node_type == "model_run")
+        .unwrap();
+    let payment = dag
+        .nodes
+        .iter()
+        .find(|node| node.node_type == "payment")
+        .unwrap();
+    assert!(model_run.parents.contains(&payment.id));
+    assert!(dag.nodes.iter().all(|node| node.id_is_valid()));
+    assert_eq!(dag.topological_order().unwrap().len(), dag.nodes.len());
+    let issuer = verify_credential(&credential, Some(&state.node_key.public_key_hex())).unwrap();
+    assert_eq!(issuer, state.node_key.public_key_hex());
+}
+
+#[tokio::test]
+async fn admin_node_lookup_finds_equal_nodes_across_requests() {
+    let mut state = setup_state();
+    let tmp = tempfile::tempdir().unwrap();
+    state.audit = Arc::new(AuditLog::open(tmp.path().join("audit.jsonl")).unwrap());
+    state.admin_token = Some("s3cret".into());
+    let app = build_router(state.clone());
+
+    let payload = json!({
+        "prompt": "deterministic hello",
+        "axiom_set": r#"{"name":"demo","version":"1","rules":[{"id":"contains-hello","must_contain":["hello"]}]}"#,
+        "free_local": true
+    });
+    let mut request_ids = Vec::new();
+    for _ in 0..2 {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::post("/api/v1/verified")
+                    .header("content-type", "application/json")
+                    .body(Body::from(payload.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let verified: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        request_ids.push(verified["request_id"].as_str().unwrap().to_string());
+    }
+
+    let entry = state
+        .audit
+        .get(request_ids[0].parse().unwrap())
+        .unwrap()
+        .unwrap();
+    let model_run = entry
+        .dag
+        .iter()
+        .find(|node| node.node_type == "model_run")
+        .unwrap();
+    let lookup = |id: &str| {
+        Request::get(format!("/api/v1/admin/audit/node/{id}"))
+            .header("x-admin-token", "s3cret")
+            .body(Body::empty())
+            .unwrap()
+    };
+    let response = app.clone().oneshot(lookup(&model_run.id)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let found: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let found_ids: Vec<&str> = found
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|node| node["request_id"].as_str().unwrap())
+        .collect();
+    assert_eq!(found_ids, request_ids);
+    assert_eq!(found[0]["node"]["payload"], model_run.payload);
+
+    let missing = app.oneshot(lookup(&"0".repeat(64))).await.unwrap();
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn node_proof_places_model_run_under_the_verified_merkle_root() {
+    let mut state = setup_state();
+    let tmp = tempfile::tempdir().unwrap();
+    state.audit = Arc::new(AuditLog::open(tmp.path().join("audit.jsonl")).unwrap());
+    let app = build_router(state.clone());
+
+    let payload = json!({
+        "prompt": "deterministic hello",
+        "axiom_set": r#"{"name":"demo","version":"1","rules":[{"id":"contains-hello","must_contain":["hello"]}]}"#,
+        "free_local": true
+    });
+    let response = app
+        .clone()
+        .oneshot(
+            Request::post("/api/v1/verified")
+                .header("content-type", "application/json")
+                .body(Body::from(payload.to_string()))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let verified: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let request_id = verified["request_id"].as_str().unwrap();
+    let entry = state
+        .audit
+        .get(request_id.parse().unwrap())
+        .unwrap()
+        .unwrap();
+    let model_run = entry
+        .dag
+        .iter()
+        .find(|node| node.node_type == "model_run")
+        .unwrap();
+
+    let proof = |node_id: &str| {
+        Request::get(format!("/api/v1/audit/proof/node/{request_id}/{node_id}"))
+            .body(Body::empty())
+            .unwrap()
+    };
+    let response = app.clone().oneshot(proof(&model_run.id)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let node_proof: NodeProofResponse = serde_json::from_slice(&body).unwrap();
+    assert_eq!(node_proof.merkle_root, verified["merkle_root"]);
+    assert_eq!(node_proof.entry_hash, entry.hash);
+    assert_eq!(node_proof.proof.commitment, model_run.commitment());
+    assert!(!node_proof.proof.proof.path.is_empty());
+    assert!(node_proof
+        .proof
+        .verify(verified["merkle_root"].as_str().unwrap()));
+
+    let missing = app.oneshot(proof("missing")).await.unwrap();
+    assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+}
@@ -1,17 +1,30 @@
-use axiomhive::audit::AuditLog;
-use axiomhive::dag::dag::RequestDag;
+use axiomhive::audit::{
+    verify_log, AuditCheckpoint, AuditEntry, AuditEvent, AuditIssue, AuditLog, AuditStore,
+    AuditTail,
+};
+use axiomhive::audit_jsonl::JsonlStore;
+use axiomhive::audit_segment::{
+    compressed_path, load_seals, segment_path, staging_path, write_seal, AuditRotation,
+    SEGMENT_HEADER_MODE,
+};
+use axiomhive::audit_sled::SledStore;
+use axiomhive::dag::dag::{legacy_commitment_root, payload_hash, RequestDag};
+use axiomhive::dag::merkletree::LEGACY_TREE;
+use axiomhive::node_key::NodeKey;
+use axiomhive::policy::PolicyProvenance;
 use serde_json::json;
+use std::{fs::OpenOptions, io::Write, sync::Arc, thread};
 use uuid::Uuid;
 
 #[test]
 fn merkle_root_is_deterministic() {
     let mut dag = RequestDag::default();
-    dag.add_node("policy", json!({"ok": true}));
-    dag.add_node("model", json!({"run": 1}));
+    dag.add_node("policy", json!({"ok": true}), &[]).unwrap();
+    dag.add_node("model", json!({"run": 1}), &[]).unwrap();
     let first = dag.merkle_root().expect("root");
     let mut dag2 = RequestDag::default();
-    dag2.add_node("policy", json!({"ok": true}));
-    dag2.add_node("model", json!({"run": 1}));
+    dag2.add_node("policy", json!({"ok": true}), &[]).unwrap();
+    dag2.add_node("model", json!({"run": 1}), &[]).unwrap();
     let second = dag2.merkle_root().expect("root");
     assert_eq!(first, second);
 }
@@ -20,17 +33,810 @@ fn merkle_root_is_deterministic() {
 fn audit_log_chains_hashes() {
     let dir = tempfile::tempdir().unwrap();
     let path = dir.path().join("audit.jsonl");
-    let log = AuditLog::new(&path);
+    let log = AuditLog::open(&path).unwrap();
+    let policy = PolicyProvenance {
+        hash: "policy-hash".into(),
+        signer: Some("publisher".into()),
+        profile: Some("medical".into()),
+    };
 
     let mut dag = RequestDag::default();
-    dag.add_node("policy", json!({"ok": true}));
+    dag.add_node("policy", json!({"ok": true}), &[]).unwrap();
     let first = log
-        .append(Uuid::new_v4(), "creative", &dag)
+        .append(Uuid::new_v4(), "creative", &dag, &policy)
         .expect("append");
 
     let second = log
-        .append(Uuid::new_v4(), "verified", &dag)
+        .append(Uuid::new_v4(), "verified", &dag, &policy)
         .expect("append2");
 
     assert_eq!(second.prev_hash, first.hash);
+    assert_eq!(second.policy_hash, "policy-hash");
+    assert_eq!(second.policy_signer.as_deref(), Some("publisher"));
+    assert_eq!(second.policy_profile.as_deref(), Some("medical"));
+}
+
+fn read_entries(path: &std::path::Path) -> Vec<AuditEntry> {
+    std::fs::read_to_string(path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect()
+}
+
+#[test]
+fn concurrent_appends_keep_a_single_chain() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.jsonl");
+    let log = Arc::new(AuditLog::open(&path).unwrap());
+    // A second handle stands in for another process appending to the same file.
+    let other = Arc::new(AuditLog::open(&path).unwrap());
+    let mut dag = RequestDag::default();
+    dag.add_node("policy", json!({"ok": true}), &[]).unwrap();
+
+    let workers = (0..8)
+        .map(|i| {
+            let log = if i % 2 == 0 {
+                log.clone()
+            } else {
+                other.clone()
+            };
+            let dag = dag.clone();
+            thread::spawn(move || {
+                for _ in 0..10 {
+                    log.append(
+                        Uuid::new_v4(),
+                        "creative",
+                        &dag,
+                        &PolicyProvenance::default(),
+                    )
+                    .unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+
+    let entries = read_entries(&path);
+    assert_eq!(entries.len(), 80);
+    assert_eq!(entries[0].prev_hash, "");
+    for pair in entries.windows(2) {
+        assert_eq!(pair[1].prev_hash, pair[0].hash);
+    }
+    assert!(entries.iter().all(AuditEntry::hash_is_valid));
+}
+
+#[test]
+fn torn_final_line_is_truncated_on_open() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.jsonl");
+    let dag = RequestDag::default();
+    let first = AuditLog::open(&path)
+        .unwrap()
+        .append(
+            Uuid::new_v4(),
+            "creative",
+            &dag,
+            &PolicyProvenance::default(),
+        )
+        .unwrap();
+    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(br#"{"timestamp":"2026-"#).unwrap();
+
+    let log = AuditLog::open(&path).unwrap();
+    let second = log
+        .append(
+            Uuid::new_v4(),
+            "creative",
+            &dag,
+            &PolicyProvenance::default(),
+        )
+        .unwrap();
+
+    assert_eq!(second.prev_hash, first.hash);
+    assert_eq!(read_entries(&path).len(), 2);
+}
+
+#[test]
+fn corrupt_chain_head_fails_open() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.jsonl");
+    let mut entry = AuditLog::open(&path)
+        .unwrap()
+        .append(
+            Uuid::new_v4(),
+            "creative",
+            &RequestDag::default(),
+            &PolicyProvenance::default(),
+        )
+        .unwrap();
+    entry.mode = "verified".into();
+    std::fs::write(
+        &path,
+        format!("{}\n", serde_json::to_string(&entry).unwrap()),
+    )
+    .unwrap();
+    assert!(AuditLog::open(&path).is_err());
+
+    std::fs::write(&path, "not json\n").unwrap();
+    assert!(AuditLog::open(&path).is_err());
+}
+
+fn write_entries(path: &std::path::Path, entries: &[AuditEntry]) {
+    let lines = entries
+        .iter()
+        .map(|entry| serde_json::to_string(entry).unwrap() + "\n")
+        .collect::<String>();
+    std::fs::write(path, lines).unwrap();
+}
+
+fn seeded_log(count: usize) -> (tempfile::TempDir, std::path::PathBuf, Vec<AuditEntry>) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.jsonl");
+    let log = AuditLog::open(&path).unwrap();
+    for i in 0..count {
+        let mut dag = RequestDag::default();
+        dag.add_node("model_run", json!({ "run": i }), &[]).unwrap();
+        log.append(
+            Uuid::new_v4(),
+            "creative",
+            &dag,
+            &PolicyProvenance::default(),
+        )
+        .unwrap();
+    }
+    let entries = read_entries(&path);
+    (dir, path, entries)
+}
+
+#[test]
+fn verifier_accepts_an_intact_log_and_resumes_from_checkpoint() {
+    let (_dir, path, entries) = seeded_log(4);
+
+    let report = verify_log(&path, None, None).unwrap();
+    assert!(report.is_intact(), "{:?}", report.issues);
+    assert_eq!(report.entries_checked, 4);
+    let head = report.head.unwrap();
+    assert_eq!(head.hash, entries[3].hash);
+
+    let checkpoint = AuditCheckpoint {
+        index: 1,
+        hash: entries[1].hash.clone(),
+    };
+    let report = verify_log(&path, Some(&checkpoint), None).unwrap();
+    assert!(report.is_intact());
+    assert_eq!(report.start_index, 2);
+    assert_eq!(report.entries_checked, 2);
+
+    let stale = AuditCheckpoint {
+        index: 1,
+        hash: entries[0].hash.clone(),
+    };
+    let report = verify_log(&path, Some(&stale), None).unwrap();
+    assert!(matches!(
+        report.issues[0],
+        AuditIssue::CheckpointMismatch { index: 1, .. }
+    ));
+}
+
+#[test]
+fn verifier_reports_tampering_reordering_and_merkle_mismatch() {
+    let (_dir, path, entries) = seeded_log(4);
+
+    let mut tampered = entries.clone();
+    tampered[1].mode = "verified".into();
+    write_entries(&path, &tampered);
+    let report = verify_log(&path, None, None).unwrap();
+    assert_eq!(
+        report.issues,
+        vec![AuditIssue::HashMismatch {
+            index: 1,
+            request_id: entries[1].request_id
+        }]
+    );
+
+    let mut reordered = entries.clone();
+    reordered.swap(1, 2);
+    write_entries(&path, &reordered);
+    let report = verify_log(&path, None, None).unwrap();
+    assert_eq!(report.first_broken_link, Some(1));
+    assert!(report.issues.contains(&AuditIssue::Reordered {
+        index: 1,
+        links_to: 2
+    }));
+
+    let mut resealed = entries.clone();
+    resealed[3].dag[0].payload = json!({ "run": 99 });
+    // Reseal the edited entry so only its Merkle root is wrong.
+    resealed[3].hash = String::new();
+    resealed[3].hash = rehash(&resealed[3]);
+    write_entries(&path, &resealed);
+    let report = verify_log(&path, None, None).unwrap();
+    assert!(matches!(
+        report.issues.as_slice(),
+        [AuditIssue::MerkleMismatch { index: 3, .. }]
+    ));
+}
+
+fn rehash(entry: &AuditEntry) -> String {
+    let mut body = serde_json::to_value(entry).unwrap();
+    let commitments: Vec<_> = entry.dag.iter().map(|node| node.commitment()).collect();
+    body["dag"] = json!(commitments);
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(entry.prev_hash.as_bytes());
+    hasher.update(body.to_string().as_bytes());
+    hasher.finalize().to_hex().to_string()
+}
+
+fn legacy_rehash(entry: &AuditEntry) -> String {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(entry.prev_hash.as_bytes());
+    hasher.update(serde_json::to_string(entry).unwrap().as_bytes());
+    hasher.finalize().to_hex().to_string()
+}
+
+#[test]
+fn signed_entries_verify_across_key_rotation() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.jsonl");
+    let first_key = Arc::new(NodeKey::load_or_create(dir.path().join("node.key")).unwrap());
+    let second_key = Arc::new(NodeKey::generate());
+    let log = AuditLog::open(&path)
+        .unwrap()
+        .with_node_key(first_key.clone());
+    let dag = RequestDag::default();
+    let policy = PolicyProvenance::default();
+
+    let signed = log
+        .append(Uuid::new_v4(), "creative", &dag, &policy)
+        .unwrap();
+    assert!(signed.signature_is_valid());
+    assert_eq!(signed.node_key, Some(first_key.public_key_hex()));
+    let rotation = log.rotate_key(second_key.clone()).unwrap();
+    assert_eq!(
+        rotation.key_rotation(),
+        Some((first_key.public_key_hex(), second_key.public_key_hex()))
+    );
+    let after = log
+        .append(Uuid::new_v4(), "creative", &dag, &policy)
+        .unwrap();
+    assert_eq!(after.node_key, Some(second_key.public_key_hex()));
+
+    for anchor in [first_key.public_key_hex(), second_key.public_key_hex()] {
+        let report = verify_log(&path, None, Some(&anchor)).unwrap();
+        assert!(report.is_intact(), "{:?}", report.issues);
+    }
+    let stranger = NodeKey::generate().public_key_hex();
+    let report = verify_log(&path, None, Some(&stranger)).unwrap();
+    assert!(matches!(
+        report.issues[0],
+        AuditIssue::UntrustedSigner { index: 0, .. }
+    ));
+
+    // The retired key keeps signing after the hand-over.
+    let stale = AuditLog::open(&path)
+        .unwrap()
+        .with_node_key(first_key.clone());
+    stale
+        .append(Uuid::new_v4(), "creative", &dag, &policy)
+        .unwrap();
+    let report = verify_log(&path, None, Some(&second_key.public_key_hex())).unwrap();
+    assert_eq!(
+        report.issues,
+        vec![AuditIssue::UntrustedSigner {
+            index: 3,
+            node_key: first_key.public_key_hex()
+        }]
+    );
+}
+
+#[test]
+fn tree_heads_and_proofs_verify_offline() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.jsonl");
+    let key = Arc::new(NodeKey::generate());
+    let log = AuditLog::open(&path).unwrap().with_node_key(key.clone());
+    let dag = RequestDag::default();
+    let policy = PolicyProvenance::default();
+    let ids: Vec<Uuid> = (0..6).map(|_| Uuid::new_v4()).collect();
+    for id in &ids[..3] {
+        log.append(*id, "creative", &dag, &policy).unwrap();
+    }
+    let early = log.tree_head().unwrap();
+    for id in &ids[3..] {
+        log.append(*id, "verified", &dag, &policy).unwrap();
+    }
+
+    let head = log.tree_head().unwrap();
+    assert_eq!(head.tree_size, 6);
+    assert!(head.verify(&key.public_key_hex()));
+    assert!(!head.verify(&NodeKey::generate().public_key_hex()));
+
+    let entries = read_entries(&path);
+    let proof = log.inclusion_proof(ids[4], None).unwrap().unwrap();
+    assert_eq!(proof.entry_hash, entries[4].hash);
+    assert!(proof.verify(&head.root_hash));
+    assert!(!proof.verify(&early.root_hash));
+    let old = log.inclusion_proof(ids[1], Some(3)).unwrap().unwrap();
+    assert!(old.verify(&early.root_hash));
+    assert!(log.inclusion_proof(ids[4], Some(3)).unwrap().is_none());
+    assert!(log.inclusion_proof(Uuid::new_v4(), None).unwrap().is_none());
+
+    let consistency = log.consistency_proof(3, 6).unwrap().unwrap();
+    assert!(consistency.verify(&early.root_hash, &head.root_hash));
+    assert!(log.consistency_proof(6, 3).unwrap().is_none());
+    assert!(log.consistency_proof(3, 7).unwrap().is_none());
+
+    // A second handle re-indexes the file, including entries appended by others.
+    let reopened = AuditLog::open(&path).unwrap();
+    log.append(Uuid::new_v4(), "creative", &dag, &policy)
+        .unwrap();
+    assert_eq!(
+        reopened.tree_head_at(Some(6)).unwrap().unwrap().root_hash,
+        head.root_hash
+    );
+    assert_eq!(reopened.tree_head().unwrap().tree_size, 7);
+}
+
+#[test]
+fn segments_rotate_compress_and_leave_signed_tombstones() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.jsonl");
+    let key = Arc::new(NodeKey::generate());
+    let anchor = key.public_key_hex();
+    let store = JsonlStore::open(&path)
+        .unwrap()
+        .with_rotation(AuditRotation {
+            max_bytes: Some(1),
+            compress: true,
+            retain_segments: Some(2),
+            ..Default::default()
+        });
+    let log = AuditLog::new(Arc::new(store)).with_node_key(key.clone());
+    let dag = RequestDag::default();
+    let policy = PolicyProvenance::default();
+    let ids: Vec<Uuid> = (0..5).map(|_| Uuid::new_v4()).collect();
+    for id in &ids {
+        log.append(*id, "creative", &dag, &policy).unwrap();
+    }
+
+    // Every append after the first seals a segment; the two oldest are past retention.
+    let seals = load_seals(&path).unwrap();
+    assert_eq!(seals.len(), 4);
+    assert!(seals[0].is_tombstone() && seals[1].is_tombstone());
+    assert!(!seals[2].is_tombstone() && !seals[3].is_tombstone());
+    assert!(!segment_path(&path, 1).exists() && !compressed_path(&path, 1).exists());
+    assert!(compressed_path(&path, 4).exists() && !segment_path(&path, 4).exists());
+    let active = read_entries(&path);
+    assert_eq!(active[0].mode, SEGMENT_HEADER_MODE);
+    assert_eq!(active[0].prev_hash, seals[3].final_hash);
+
+    let report = verify_log(&path, None, Some(&anchor)).unwrap();
+    assert!(report.is_intact(), "{:?}", report.issues);
+    assert_eq!(report.head.as_ref().unwrap().index, 8);
+    let head = log.tree_head().unwrap();
+    assert_eq!(head.tree_size, 9);
+    let proof = log.inclusion_proof(ids[0], None).unwrap().unwrap();
+    assert!(proof.verify(&head.root_hash));
+
+    // A reopened log resumes from the seals without reading sealed segments.
+    drop(log);
+    let reopened = AuditLog::open(&path).unwrap().with_node_key(key.clone());
+    assert_eq!(reopened.tree_head().unwrap().root_hash, head.root_hash);
+    reopened
+        .append(Uuid::new_v4(), "creative", &dag, &policy)
+        .unwrap();
+    let report = verify_log(&path, None, Some(&anchor)).unwrap();
+    assert!(report.is_intact(), "{:?}", report.issues);
+
+    let mut forged = seals[0].clone();
+    forged.final_hash = "0".repeat(64);
+    write_seal(&path, &forged).unwrap();
+    std::fs::remove_file(compressed_path(&path, 4)).unwrap();
+    let report = verify_log(&path, None, Some(&anchor)).unwrap();
+    assert_eq!(
+        report.issues,
+        vec![
+            AuditIssue::BadSeal {
+                index: 0,
+                segment: 1
+            },
+            AuditIssue::MissingSegment {
+                index: 5,
+                segment: 4
+            },
+        ]
+    );
+}
+
+#[test]
+fn interrupted_rotation_is_rolled_back_or_completed_on_open() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.jsonl");
+    let dag = RequestDag::default();
+    let policy = PolicyProvenance::default();
+    let log = AuditLog::open(&path).unwrap();
+    for _ in 0..2 {
+        log.append(Uuid::new_v4(), "creative", &dag, &policy)
+            .unwrap();
+    }
+    drop(log);
+
+    // Crash after staging the next segment and linking the sealed one, before its seal.
+    std::fs::write(staging_path(&path), "partial").unwrap();
+    std::fs::hard_link(&path, segment_path(&path, 1)).unwrap();
+    let log = AuditLog::open(&path).unwrap();
+    assert!(!staging_path(&path).exists());
+    assert!(!segment_path(&path, 1).exists());
+    assert_eq!(log.tree_head().unwrap().tree_size, 2);
+    drop(log);
+
+    // Crash after the seal, before the staged segment replaced the log.
+    let store = JsonlStore::open(&path)
+        .unwrap()
+        .with_rotation(AuditRotation {
+            max_bytes: Some(1),
+            ..Default::default()
+        });
+    let log = AuditLog::new(Arc::new(store));
+    log.append(Uuid::new_v4(), "creative", &dag, &policy)
+        .unwrap();
+    let head = log.tree_head().unwrap();
+    drop(log);
+    std::fs::rename(&path, staging_path(&path)).unwrap();
+    std::fs::copy(segment_path(&path, 1), &path).unwrap();
+    let log = AuditLog::open(&path).unwrap();
+    assert!(!staging_path(&path).exists());
+    assert_eq!(log.tree_head().unwrap().root_hash, head.root_hash);
+    log.append(Uuid::new_v4(), "creative", &dag, &policy)
+        .unwrap();
+    let report = verify_log(&path, None, None).unwrap();
+    assert!(report.is_intact(), "{:?}", report.issues);
+    assert_eq!(report.entries_checked, 5);
+}
+
+#[test]
+fn jsonl_store_gets_and_scans_across_segments() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.jsonl");
+    let store = JsonlStore::open(&path)
+        .unwrap()
+        .with_rotation(AuditRotation {
+            max_bytes: Some(1),
+            ..Default::default()
+        });
+    let log = AuditLog::new(Arc::new(store));
+    let dag = RequestDag::default();
+    let policy = PolicyProvenance::default();
+    let ids: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+    for id in &ids {
+        log.append(*id, "creative", &dag, &policy).unwrap();
+    }
+
+    // Entries 1 and 3 are segment headers.
+    let head = log.head().unwrap().unwrap();
+    assert_eq!(head.index, 4);
+    assert_eq!(log.get(ids[1]).unwrap().unwrap().request_id, ids[1]);
+    assert!(log.get(Uuid::new_v4()).unwrap().is_none());
+    let scanned = log.scan(1..4).unwrap();
+    let modes: Vec<_> = scanned.iter().map(|(_, e)| e.mode.as_str()).collect();
+    assert_eq!(
+        modes,
+        [SEGMENT_HEADER_MODE, "creative", SEGMENT_HEADER_MODE]
+    );
+    assert_eq!(scanned.last().unwrap().0, 3);
+    assert_eq!(log.scan(0..10).unwrap().last().unwrap().1.hash, head.hash);
+}
+
+#[test]
+fn sled_store_shares_the_chain_format() {
+    let dir = tempfile::tempdir().unwrap();
+    let key = Arc::new(NodeKey::generate());
+    let anchor = key.public_key_hex();
+    let store = Arc::new(SledStore::open(dir.path().join("audit.sled")).unwrap());
+    let log = AuditLog::new(store.clone()).with_node_key(key.clone());
+    let mut dag = RequestDag::default();
+    dag.add_node("input", json!({"prompt": "hi"}), &[]).unwrap();
+    let policy = PolicyProvenance::default();
+    let ids: Vec<Uuid> = (0..4).map(|_| Uuid::new_v4()).collect();
+    for id in &ids {
+        log.append(*id, "verified", &dag, &policy).unwrap();
+    }
+    let rotated = Arc::new(NodeKey::generate());
+    log.rotate_key(rotated.clone()).unwrap();
+
+    let report = log.verify(None, Some(&anchor)).unwrap();
+    assert!(report.is_intact(), "{:?}", report.issues);
+    assert_eq!(report.head, log.head().unwrap());
+    let entry = log.get(ids[2]).unwrap().unwrap();
+    assert_eq!(entry.prev_hash, log.get(ids[1]).unwrap().unwrap().hash);
+    let scanned = log.scan(1..3).unwrap();
+    assert_eq!(scanned.len(), 2);
+    assert_eq!(scanned[1].1.request_id, ids[2]);
+    let head = log.tree_head().unwrap();
+    assert_eq!(head.tree_size, 5);
+    assert!(head.verify(&rotated.public_key_hex()));
+    let proof = log.inclusion_proof(ids[3], None).unwrap().unwrap();
+    assert!(proof.verify(&head.root_hash));
+
+    // The same entries written out as JSON lines pass the file verifier.
+    let export = dir.path().join("export.jsonl");
+    let lines: String = store
+        .scan(0..usize::MAX)
+        .unwrap()
+        .iter()
+        .map(|(_, entry)| serde_json::to_string(entry).unwrap() + "\n")
+        .collect();
+    std::fs::write(&export, lines).unwrap();
+    let exported = verify_log(&export, None, Some(&anchor)).unwrap();
+    assert!(exported.is_intact(), "{:?}", exported.issues);
+    assert_eq!(exported.head, report.head);
+}
+
+#[test]
+fn redacted_payloads_keep_the_chain_and_merkle_root_verifiable() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.jsonl");
+    let key = Arc::new(NodeKey::generate());
+    let anchor = key.public_key_hex();
+    let store = JsonlStore::open(&path)
+        .unwrap()
+        .with_rotation(AuditRotation {
+            max_bytes: Some(1),
+            compress: true,
+            ..Default::default()
+        });
+    let log = AuditLog::new(Arc::new(store)).with_node_key(key.clone());
+    let policy = PolicyProvenance::default();
+    let ids: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+    for (i, id) in ids.iter().enumerate() {
+        let mut dag = RequestDag::default();
+        dag.add_node(
+            "input",
+            json!({ "prompt": format!("user {i} secret") }),
+            &[],
+        )
+        .unwrap();
+        dag.add_node("model_run", json!({ "run": i }), &[]).unwrap();
+        log.append(*id, "creative", &dag, &policy).unwrap();
+    }
+
+    // The first entry sits in a compressed sealed segment, the last in the active one.
+    for id in [ids[0], ids[2]] {
+        let before = log.get(id).unwrap().unwrap();
+        let redacted = log
+            .redact(id, &before.dag[0].id, "erasure request")
+            .unwrap();
+        assert_eq!(redacted.hash, before.hash);
+        assert!(redacted.dag[0].payload.is_null());
+        assert_eq!(
+            redacted.redactions[0].payload_hash,
+            before.dag[0].payload_hash()
+        );
+        // The salt goes with the payload, so a guessed payload cannot be checked against
+        // the hash that stays.
+        assert!(redacted.dag[0].salt.is_none());
+        assert_ne!(
+            redacted.redactions[0].payload_hash,
+            payload_hash(&before.dag[0].payload)
+        );
+        let stored = log.get(id).unwrap().unwrap();
+        assert_eq!(stored.redactions, redacted.redactions);
+        assert_eq!(stored.computed_merkle_root(), stored.merkle_root);
+    }
+    let input = log.get(ids[2]).unwrap().unwrap().dag[0].id.clone();
+    assert!(log.redact(ids[2], &input, "again").is_err());
+    assert!(!std::fs::read_to_string(&path)
+        .unwrap()
+        .contains("user 2 secret"));
+
+    let report = verify_log(&path, None, Some(&anchor)).unwrap();
+    assert!(report.is_intact(), "{:?}", report.issues);
+
+    // A redaction record must be signed by a trusted key and name an emptied payload.
+    let original = read_entries(&path);
+    let mut entries = original.clone();
+    let last = entries.iter().position(|e| e.request_id == ids[2]).unwrap();
+    entries[last].redactions[0].reason = "edited".into();
+    write_entries(&path, &entries);
+    let report = verify_log(&path, None, Some(&anchor)).unwrap();
+    assert_eq!(
+        report.issues,
+        vec![AuditIssue::BadRedaction {
+            index: 4,
+            node_id: input.clone()
+        }]
+    );
+    entries[last].redactions = original[last].redactions.clone();
+    entries[last].dag[0].salt = log.get(ids[1]).unwrap().unwrap().dag[0].salt.clone();
+    write_entries(&path, &entries);
+    let report = verify_log(&path, None, Some(&anchor)).unwrap();
+    assert_eq!(
+        report.issues,
+        vec![AuditIssue::BadRedaction {
+            index: 4,
+            node_id: input.clone()
+        }]
+    );
+    entries[last].dag[0].payload = json!({ "prompt": "forged" });
+    write_entries(&path, &entries);
+    let report = verify_log(&path, None, Some(&anchor)).unwrap();
+    assert!(report
+        .issues
+        .iter()
+        .any(|issue| matches!(issue, AuditIssue::HashMismatch { index: 4, .. })));
+
+    // The writer follows the rewritten active segment.
+    write_entries(&path, &original);
+    log.append(Uuid::new_v4(), "creative", &RequestDag::default(), &policy)
+        .unwrap();
+    let report = verify_log(&path, None, Some(&anchor)).unwrap();
+    assert!(report.is_intact(), "{:?}", report.issues);
+    assert_eq!(report.entries_checked, 7);
+}
+
+#[test]
+fn legacy_entries_still_verify_but_cannot_be_redacted() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.jsonl");
+    let mut dag = RequestDag::default();
+    dag.add_node("input", json!({ "prompt": "old" }), &[])
+        .unwrap();
+    let log = AuditLog::open(&path).unwrap();
+    let current = log
+        .append(
+            Uuid::new_v4(),
+            "creative",
+            &dag,
+            &PolicyProvenance::default(),
+        )
+        .unwrap();
+    drop(log);
+
+    // Written the way entries were before payload commitments.
+    let mut legacy = current.clone();
+    legacy.version = 0;
+    legacy.dag = dag.nodes.clone();
+    legacy.request_id = Uuid::new_v4();
+    legacy.merkle_root = dag.legacy_merkle_root().unwrap();
+    legacy.prev_hash = current.hash.clone();
+    legacy.hash = String::new();
+    legacy.hash = legacy_rehash(&legacy);
+    let line = serde_json::to_string(&legacy).unwrap();
+    assert!(!line.contains("version"));
+    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+    writeln!(file, "{line}").unwrap();
+    drop(file);
+
+    assert!(verify_log(&path, None, None).unwrap().is_intact());
+    let log = AuditLog::open(&path)
+        .unwrap()
+        .with_node_key(Arc::new(NodeKey::generate()));
+    let err = log.redact(legacy.request_id, &dag.nodes[0].id, "erasure request");
+    assert!(err.unwrap_err().to_string().contains("predates"));
+}
+
+/// Entry hash as format 1 computed it: over the JSON with node commitments as the DAG.
+fn format1_rehash(entry: &AuditEntry) -> String {
+    let mut value = serde_json::to_value(entry).unwrap();
+    let fields = value.as_object_mut().unwrap();
+    fields.insert("dag".into(), json!(entry.commitments()));
+    fields.remove("redactions");
+    fields.remove("encrypted");
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(entry.prev_hash.as_bytes());
+    hasher.update(value.to_string().as_bytes());
+    hasher.finalize().to_hex().to_string()
+}
+
+#[test]
+fn format1_roots_verify_through_the_legacy_tree() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.jsonl");
+    let mut dag = RequestDag::default();
+    let input = dag
+        .add_node("input", json!({ "prompt": "old" }), &[])
+        .unwrap();
+    let model = dag
+        .add_node("model_run", json!({ "run": 1 }), &[&input.id])
+        .unwrap();
+    dag.add_node("output_check", json!({ "action": "allow" }), &[&model.id])
+        .unwrap();
+    let log = AuditLog::open(&path).unwrap();
+    let current = log
+        .append(
+            Uuid::new_v4(),
+            "verified",
+            &dag,
+            &PolicyProvenance::default(),
+        )
+        .unwrap();
+    drop(log);
+    assert_eq!(current.merkle_root, current.computed_merkle_root());
+
+    // Written the way entries were before the domain-separated tree and salted payloads.
+    let mut old = current.clone();
+    old.version = 1;
+    old.dag = dag.nodes.clone();
+    old.request_id = Uuid::new_v4();
+    old.merkle_root = legacy_commitment_root(&old.commitments()).unwrap();
+    assert_ne!(old.merkle_root, current.merkle_root);
+    old.prev_hash = current.hash.clone();
+    old.hash = String::new();
+    old.hash = format1_rehash(&old);
+    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+    writeln!(file, "{}", serde_json::to_string(&old).unwrap()).unwrap();
+    drop(file);
+
+    let report = verify_log(&path, None, None).unwrap();
+    assert!(report.is_intact(), "{:?}", report.issues);
+    let proof = old.node_proof(&model.id).unwrap().unwrap();
+    assert_eq!(proof.proof.version, LEGACY_TREE);
+    assert!(proof.verify(&old.merkle_root));
+    assert!(!proof.verify(&current.merkle_root));
+    let proof = current.node_proof(&current.dag[1].id).unwrap().unwrap();
+    assert!(proof.verify(&current.merkle_root));
+
+    // A format 1 root is not accepted as a format 2 one.
+    let mut relabeled = old.clone();
+    relabeled.version = 2;
+    assert_ne!(relabeled.computed_merkle_root(), relabeled.merkle_root);
+}
+
+async fn next_event(tail: &mut AuditTail) -> AuditEvent {
+    tokio::time::timeout(std::time::Duration::from_secs(5), tail.next())
+        .await
+        .expect("tail stalled")
+        .unwrap()
+}
+
+#[tokio::test]
+async fn tail_streams_appends_in_order_and_resumes_after_a_hash() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.jsonl");
+    let store = JsonlStore::open(&path)
+        .unwrap()
+        .with_rotation(AuditRotation {
+            max_bytes: Some(1),
+            ..Default::default()
+        });
+    let log = Arc::new(AuditLog::new(Arc::new(store)));
+    let policy = PolicyProvenance::default();
+    let mut dag = RequestDag::default();
+    dag.add_node("policy", json!({"ok": true}), &[]).unwrap();
+    let seen = log
+        .append(Uuid::new_v4(), "creative", &dag, &policy)
+        .unwrap();
+    assert!(log.tail(Some("not-a-hash"), None).unwrap().is_none());
+
+    let mut live = log.tail(None, Some("verified".into())).unwrap().unwrap();
+    let mut everything = log.tail(Some(&seen.hash), None).unwrap().unwrap();
+    let ids: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+    for (i, id) in ids.iter().enumerate() {
+        let mode = if i == 1 { "creative" } else { "verified" };
+        log.append(*id, mode, &dag, &policy).unwrap();
+    }
+
+    let first = next_event(&mut live).await;
+    let second = next_event(&mut live).await;
+    assert_eq!(
+        (first.entry.request_id, second.entry.request_id),
+        (ids[0], ids[2])
+    );
+    assert!(first.index < second.index);
+
+    // Segment headers written by rotation are not published but still reach the tail.
+    let mut indexes = Vec::new();
+    let mut modes = Vec::new();
+    while indexes.last() != Some(&second.index) {
+        let event = next_event(&mut everything).await;
+        indexes.push(event.index);
+        modes.push(event.entry.mode);
+    }
+    assert_eq!(indexes, (1..=second.index).collect::<Vec<_>>());
+    assert!(modes.iter().any(|mode| mode == SEGMENT_HEADER_MODE));
+    let hashes = log.store().hashes().unwrap();
+    assert_eq!(second.entry.hash, hashes[second.index]);
 }
@@ -0,0 +1,181 @@
+use axiomhive::audit::{verify_log, AuditEntry, AuditLog, AuditStore};
+use axiomhive::audit_crypt::AuditKeyring;
+use axiomhive::audit_jsonl::JsonlStore;
+use axiomhive::audit_segment::{load_seals, AuditRotation};
+use axiomhive::audit_sled::SledStore;
+use axiomhive::dag::dag::{payload_hash, RequestDag};
+use axiomhive::node_key::NodeKey;
+use axiomhive::policy::PolicyProvenance;
+use serde_json::json;
+use std::sync::Arc;
+use uuid::Uuid;
+
+fn secret_dag(i: usize) -> RequestDag {
+    let mut dag = RequestDag::default();
+    dag.add_node(
+        "input",
+        json!({ "axiom_set": format!("secret-set-{i}") }),
+        &[],
+    )
+    .unwrap();
+    dag.add_node("model_run", json!({ "media": ["text"] }), &[])
+        .unwrap();
+    dag
+}
+
+fn stored_text(dir: &std::path::Path) -> String {
+    let mut text = String::new();
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|ext| ext == "jsonl") {
+            text.push_str(&std::fs::read_to_string(path).unwrap());
+        }
+    }
+    text
+}
+
+#[test]
+fn encrypted_entries_verify_without_the_key_and_decrypt_with_it() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.jsonl");
+    let key = Arc::new(NodeKey::generate());
+    let anchor = key.public_key_hex();
+    let keyring = AuditKeyring::load_or_create(dir.path().join("audit.keys"), None).unwrap();
+    let log = AuditLog::open(&path)
+        .unwrap()
+        .with_node_key(key.clone())
+        .with_keyring(Arc::new(keyring));
+    let policy = PolicyProvenance::default();
+    let ids: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+    for (i, id) in ids.iter().enumerate() {
+        let entry = log
+            .append(*id, "verified", &secret_dag(i), &policy)
+            .unwrap();
+        assert_eq!(entry.dag[0].payload["axiom_set"], format!("secret-set-{i}"));
+    }
+    log.rotate_key(Arc::new(NodeKey::generate())).unwrap();
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(!text.contains("secret-set"));
+    let stored: AuditEntry = serde_json::from_str(text.lines().next().unwrap()).unwrap();
+    assert!(stored.encrypted.is_some() && stored.dag[0].payload.is_null());
+    // Payload hashes are salted and the salts are sealed, so a guess cannot be confirmed.
+    assert!(stored.dag.iter().all(|node| node.salt.is_none()));
+    let guess = payload_hash(&json!({ "axiom_set": "secret-set-0" }));
+    assert!(!text.contains(&guess));
+    assert!(!text.contains(&secret_dag(0).nodes[0].id));
+    assert!(stored.hash_is_valid());
+    assert_eq!(stored.computed_merkle_root(), stored.merkle_root);
+
+    // Integrity checks need no key.
+    let report = verify_log(&path, None, Some(&anchor)).unwrap();
+    assert!(report.is_intact(), "{:?}", report.issues);
+
+    let opened = log.get(ids[1]).unwrap().unwrap();
+    assert_eq!(opened.dag[0].payload["axiom_set"], "secret-set-1");
+    assert!(opened.encrypted.is_none() && opened.hash_is_valid());
+
+    // A log opened without the keystore reads entries as stored.
+    let keyless = AuditLog::open(&path).unwrap();
+    assert!(keyless.get(ids[1]).unwrap().unwrap().encrypted.is_some());
+
+    // Ciphertext is bound to the entry: moving it to another entry fails to decrypt.
+    let mut lines: Vec<AuditEntry> = text
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    lines[0].encrypted = lines[1].encrypted.clone();
+    let keyring = AuditKeyring::load_or_create(dir.path().join("audit.keys"), None).unwrap();
+    assert!(keyring.open(&mut lines[0]).is_err());
+}
+
+#[test]
+fn passphrase_keys_rotate_and_reencrypt_sealed_segments() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.jsonl");
+    let keystore = dir.path().join("audit.keys");
+    let mut keyring = AuditKeyring::load_or_create(&keystore, Some("correct horse")).unwrap();
+    let first_key = keyring.active_key_id().to_string();
+    let store = JsonlStore::open(&path)
+        .unwrap()
+        .with_rotation(AuditRotation {
+            max_bytes: Some(1),
+            compress: true,
+            ..Default::default()
+        });
+    let store: Arc<dyn AuditStore> = Arc::new(store);
+    let log = AuditLog::new(store.clone())
+        .with_node_key(Arc::new(NodeKey::generate()))
+        .with_keyring(Arc::new(keyring.clone()));
+    let policy = PolicyProvenance::default();
+    let ids: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+    let entries: Vec<AuditEntry> = ids
+        .iter()
+        .enumerate()
+        .map(|(i, id)| {
+            log.append(*id, "verified", &secret_dag(i), &policy)
+                .unwrap()
+        })
+        .collect();
+    let input = entries[2].dag[0].id.clone();
+    let redacted = log.redact(ids[2], &input, "erasure request").unwrap();
+    assert!(redacted.dag[0].payload.is_null());
+    assert_eq!(redacted.dag[1].payload["media"], json!(["text"]));
+    assert_eq!(load_seals(&path).unwrap().len(), 2);
+    assert!(AuditKeyring::load_or_create(&keystore, None).is_err());
+    assert!(AuditKeyring::load_or_create(&keystore, Some("wrong"))
+        .map(|wrong| wrong
+            .open(&mut store.get(ids[0]).unwrap().unwrap())
+            .is_err())
+        .unwrap());
+
+    let second_key = keyring.rotate().unwrap();
+    keyring.save().unwrap();
+    let rotated = AuditLog::new(store.clone()).with_keyring(Arc::new(keyring.clone()));
+    // Three requests and two segment headers.
+    assert_eq!(rotated.reencrypt(2).unwrap(), 5);
+    assert_eq!(rotated.reencrypt(2).unwrap(), 0);
+    for (_, entry) in store.scan(0..10).unwrap() {
+        assert_eq!(entry.encrypted.unwrap().key_id, second_key);
+    }
+    assert_eq!(keyring.retire_inactive(), vec![first_key]);
+    keyring.save().unwrap();
+
+    let reloaded = AuditKeyring::load_or_create(&keystore, Some("correct horse")).unwrap();
+    assert_eq!(reloaded.key_ids(), vec![second_key]);
+    let reopened = AuditLog::new(store).with_keyring(Arc::new(reloaded));
+    assert_eq!(
+        reopened.get(ids[0]).unwrap().unwrap().dag[0].payload["axiom_set"],
+        "secret-set-0"
+    );
+    assert!(reopened.get(ids[2]).unwrap().unwrap().dag[0]
+        .payload
+        .is_null());
+    assert!(!stored_text(dir.path()).contains("secret-set"));
+    let report = verify_log(&path, None, None).unwrap();
+    assert!(report.is_intact(), "{:?}", report.issues);
+}
+
+#[test]
+fn existing_plaintext_entries_are_encrypted_in_place() {
+    let dir = tempfile::tempdir().unwrap();
+    let store: Arc<dyn AuditStore> =
+        Arc::new(SledStore::open(dir.path().join("audit.sled")).unwrap());
+    let plain = AuditLog::new(store.clone());
+    let id = Uuid::new_v4();
+    let written = plain
+        .append(id, "verified", &secret_dag(0), &PolicyProvenance::default())
+        .unwrap();
+
+    let keyring = AuditKeyring::load_or_create(dir.path().join("audit.keys"), None).unwrap();
+    let log = AuditLog::new(store.clone()).with_keyring(Arc::new(keyring));
+    assert_eq!(log.reencrypt(16).unwrap(), 1);
+    let stored = store.get(id).unwrap().unwrap();
+    assert!(stored.encrypted.is_some());
+    assert_eq!(stored.hash, written.hash);
+    assert_eq!(
+        log.get(id).unwrap().unwrap().dag[0].payload,
+        written.dag[0].payload
+    );
+    assert!(log.verify(None, None).unwrap().is_intact());
+}
@@ -0,0 +1,198 @@
+use axiomhive::audit::AuditLog;
+use axiomhive::audit_crypt::AuditKeyring;
+use axiomhive::credential::{
+    did_key, did_key_public_key, verify_credential, AuditCredential, CREDENTIALS_CONTEXT,
+};
+use axiomhive::dag::dag::RequestDag;
+use axiomhive::node_key::NodeKey;
+use axiomhive::policy::PolicyProvenance;
+use axiomhive::verification::axiom_checker::{parse_axiom_set, C0Signature};
+use serde_json::json;
+use std::sync::Arc;
+use uuid::Uuid;
+
+fn verified_dag(c0: &C0Signature) -> RequestDag {
+    let mut dag = RequestDag::default();
+    dag.add_node("input", json!({"axiom_set": "demo"}), &[])
+        .unwrap();
+    dag.add_node(
+        "verification",
+        json!({"rules": 1, "free_local": true, "c0_signature": c0}),
+        &[],
+    )
+    .unwrap();
+    dag
+}
+
+#[test]
+fn did_key_round_trips_the_node_key() {
+    let key = NodeKey::generate();
+    let did = did_key(&key.public_key_hex()).unwrap();
+    assert!(did.starts_with("did:key:z6Mk"));
+    assert_eq!(did_key_public_key(&did).unwrap(), key.public_key_hex());
+    assert!(did_key_public_key("did:web:example.com").is_err());
+}
+
+#[test]
+fn exported_credentials_verify_offline_and_detect_tampering() {
+    let dir = tempfile::tempdir().unwrap();
+    let key = Arc::new(NodeKey::generate());
+    let keyring = AuditKeyring::load_or_create(dir.path().join("audit.keys"), None).unwrap();
+    let log = AuditLog::open(dir.path().join("audit.jsonl"))
+        .unwrap()
+        .with_node_key(key.clone())
+        .with_keyring(Arc::new(keyring));
+    let axioms = parse_axiom_set(
+        r#"{"name":"demo","version":"1","rules":[{"id":"r","must_contain":["hello"]}]}"#,
+    )
+    .unwrap();
+    let c0 = C0Signature::new("say hello", &axioms, "hello", 32);
+    let policy = PolicyProvenance::default();
+    let request_id = Uuid::new_v4();
+    log.append(Uuid::new_v4(), "creative", &RequestDag::default(), &policy)
+        .unwrap();
+    log.append(request_id, "verified", &verified_dag(&c0), &policy)
+        .unwrap();
+    log.append(Uuid::new_v4(), "creative", &RequestDag::default(), &policy)
+        .unwrap();
+
+    assert!(AuditCredential::export(&log, Uuid::new_v4(), &key)
+        .unwrap()
+        .is_none());
+    let credential = AuditCredential::export(&log, request_id, &key)
+        .unwrap()
+        .unwrap();
+    let subject = &credential.credential_subject;
+    assert_eq!(credential.context, vec![CREDENTIALS_CONTEXT.to_string()]);
+    assert_eq!(subject.id, format!("urn:uuid:{request_id}"));
+    assert_eq!(subject.inclusion_proof.leaf_index, 1);
+    assert_eq!(subject.tree_head.tree_size, 3);
+    // Exported decrypted, and still verifiable.
+    assert!(subject.audit_entry.encrypted.is_none());
+    assert_eq!(subject.audit_entry.dag[0].payload["axiom_set"], "demo");
+    assert_eq!(
+        subject.c0_signature.as_ref().unwrap().state_trace,
+        c0.state_trace
+    );
+
+    // Round-trips through JSON and verifies with or without pinning the node key.
+    let json = serde_json::to_value(&credential).unwrap();
+    assert_eq!(json["type"][0], "VerifiableCredential");
+    assert_eq!(json["proof"]["cryptosuite"], "eddsa-jcs-2022");
+    let parsed: AuditCredential = serde_json::from_value(json).unwrap();
+    assert_eq!(
+        verify_credential(&parsed, Some(&key.public_key_hex())).unwrap(),
+        key.public_key_hex()
+    );
+    assert!(verify_credential(&parsed, None).is_ok());
+    let other = NodeKey::generate().public_key_hex();
+    assert!(verify_credential(&parsed, Some(&other)).is_err());
+
+    let tampered = |edit: &dyn Fn(&mut AuditCredential)| {
+        let mut copy = credential.clone();
+        edit(&mut copy);
+        verify_credential(&copy, None).is_err()
+    };
+    assert!(tampered(&|vc| vc.valid_from = "2000-01-01T00:00:00Z".into()));
+    assert!(tampered(&|vc| vc.proof = None));
+    assert!(tampered(&|vc| {
+        vc.credential_subject
+            .c0_signature
+            .as_mut()
+            .unwrap()
+            .state_trace = "forged".into()
+    }));
+    // A re-signed credential still needs the entry to match its hash and the tree.
+    let reissue = |edit: &dyn Fn(&mut AuditCredential)| {
+        let mut copy = credential.clone();
+        edit(&mut copy);
+        let subject = copy.credential_subject;
+        let copy = AuditCredential::issue(
+            subject.audit_entry,
+            subject.tree_head,
+            subject.inclusion_proof,
+            &key,
+        )
+        .unwrap();
+        verify_credential(&copy, None).is_err()
+    };
+    assert!(reissue(
+        &|vc| vc.credential_subject.audit_entry.dag[0].payload = json!({"axiom_set": "other"})
+    ));
+    assert!(reissue(
+        &|vc| vc.credential_subject.audit_entry.prev_hash = "0".repeat(64)
+    ));
+    assert!(reissue(&|vc| vc
+        .credential_subject
+        .inclusion_proof
+        .audit_path
+        .clear()));
+    assert!(reissue(
+        &|vc| vc.credential_subject.tree_head.root_hash = "00".repeat(32)
+    ));
+}
+
+#[test]
+fn cli_exported_credentials_pass_cli_verification() {
+    let dir = tempfile::tempdir().unwrap();
+    let key_path = dir.path().join("node.key");
+    let audit_path = dir.path().join("audit.jsonl");
+    let key = Arc::new(NodeKey::load_or_create(&key_path).unwrap());
+    let log = AuditLog::open(&audit_path)
+        .unwrap()
+        .with_node_key(key.clone());
+    let axioms = parse_axiom_set(
+        r#"{"name":"demo","version":"1","rules":[{"id":"r","must_contain":["hello"]}]}"#,
+    )
+    .unwrap();
+    let c0 = C0Signature::new("say hello", &axioms, "hello", 32);
+    let request_id = Uuid::new_v4();
+    log.append(
+        request_id,
+        "verified",
+        &verified_dag(&c0),
+        &PolicyProvenance::default(),
+    )
+    .unwrap();
+    drop(log);
+
+    let cli = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_axiomhive"))
+            .args(args)
+            .current_dir(dir.path())
+            .env("AXIOMHIVE_NODE_KEY_PATH", &key_path)
+            .env("AXIOMHIVE_AUDIT_BACKEND", "jsonl")
+            .env_remove("AXIOMHIVE_AUDIT_PATH")
+            .env_remove("AXIOMHIVE_AUDIT_KEYSTORE")
+            .env_remove("AXIOMHIVE_AUDIT_PASSPHRASE")
+            .output()
+            .unwrap()
+    };
+    let exported = cli(&[
+        "export-credential",
+        "--request-id",
+        &request_id.to_string(),
+        "--path",
+        audit_path.to_str().unwrap(),
+    ]);
+    assert!(
+        exported.status.success(),
+        "{}",
+        String::from_utf8_lossy(&exported.stderr)
+    );
+    let file = dir.path().join("credential.json");
+    std::fs::write(&file, &exported.stdout).unwrap();
+
+    let verified = cli(&[
+        "verify-credential",
+        "--file",
+        file.to_str().unwrap(),
+        "--node-key",
+        &key.public_key_hex(),
+    ]);
+    assert!(
+        verified.status.success(),
+        "{}",
+        String::from_utf8_lossy(&verified.stderr)
+    );
+}
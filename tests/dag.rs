@@ -0,0 +1,487 @@
+use axiomhive::dag::dag::{
+    commitment_root, content_id, DagError, DagNode, DagScheduler, RequestDag,
+};
+use axiomhive::dag::executor::{
+    TaskError, TaskFailure, TaskGraph, TaskInputs, TaskOutput, TaskPolicy,
+};
+use axiomhive::dag::merkletree::{MerkleError, MerkleTree, Side, LEGACY_TREE, TREE_VERSION};
+use serde_json::json;
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+use std::time::Duration;
+use tokio::sync::Barrier;
+
+fn pipeline() -> RequestDag {
+    let mut dag = RequestDag::default();
+    let policy = dag
+        .add_node("policy_check", json!({"ok": true}), &[])
+        .unwrap();
+    let triage = dag
+        .add_node("triage", json!({"outcome": "answer"}), &[&policy.id])
+        .unwrap();
+    let model = dag
+        .add_node("model_run", json!({"run": 1}), &[&policy.id, &triage.id])
+        .unwrap();
+    dag.add_node("verification", json!({"rules": 1}), &[&model.id])
+        .unwrap();
+    dag
+}
+
+#[test]
+fn add_node_records_edges_and_rejects_unknown_parents() {
+    let mut dag = pipeline();
+    let model = dag
+        .nodes
+        .iter()
+        .find(|node| node.node_type == "model_run")
+        .unwrap();
+    assert_eq!(
+        model.parents,
+        vec![dag.nodes[0].id.clone(), dag.nodes[1].id.clone()]
+    );
+    assert_eq!(
+        dag.add_node("output_check", json!({}), &["missing"])
+            .unwrap_err(),
+        DagError::UnknownParent {
+            node: "output_check".into(),
+            parent: "missing".into(),
+        }
+    );
+    assert_eq!(dag.nodes.len(), 4);
+}
+
+#[test]
+fn node_ids_address_content_and_dedupe() {
+    let dag = pipeline();
+    // The same computation gets the same id in another DAG.
+    let again = pipeline();
+    assert_eq!(
+        dag.nodes.iter().map(|node| &node.id).collect::<Vec<_>>(),
+        again.nodes.iter().map(|node| &node.id).collect::<Vec<_>>()
+    );
+    assert!(dag.nodes.iter().all(DagNode::id_is_valid));
+    let model = &dag.nodes[2];
+    assert_eq!(
+        model.id,
+        content_id("model_run", &model.payload_hash(), &model.parents)
+    );
+
+    // Any change to type, payload or parents changes the id.
+    let mut other = RequestDag::default();
+    let policy = other
+        .add_node("policy_check", json!({"ok": false}), &[])
+        .unwrap();
+    assert_ne!(policy.id, dag.nodes[0].id);
+    let unparented = other
+        .add_node("triage", json!({"outcome": "answer"}), &[])
+        .unwrap();
+    assert_ne!(unparented.id, dag.nodes[1].id);
+    let mut tampered = model.clone();
+    tampered.payload = json!({"run": 2});
+    assert!(!tampered.id_is_valid());
+
+    // Adding an equal node again returns the one already recorded.
+    let mut deduped = pipeline();
+    let first = deduped.nodes[0].clone();
+    let repeat = deduped
+        .add_node("policy_check", json!({"ok": true}), &[])
+        .unwrap();
+    assert_eq!(repeat.id, first.id);
+    assert_eq!(deduped.nodes.len(), 4);
+
+    // The commitment alone, without the payload, is enough to check the id.
+    let mut commitment = model.commitment();
+    assert!(commitment.id_is_valid());
+    commitment.parents.pop();
+    assert!(!commitment.id_is_valid());
+}
+
+#[test]
+fn topological_order_follows_edges_and_detects_cycles() {
+    let mut dag = RequestDag::default();
+    let node = |id: &str, parents: &[&str]| DagNode {
+        id: id.into(),
+        node_type: "step".into(),
+        payload: json!(null),
+        parents: parents.iter().map(|p| p.to_string()).collect(),
+        salt: None,
+    };
+    // Stored out of order, as a DAG read back from elsewhere might be.
+    dag.nodes = vec![
+        node("c", &["a", "b"]),
+        node("a", &[]),
+        node("d", &["a"]),
+        node("b", &["a"]),
+    ];
+    let order: Vec<&str> = dag
+        .topological_order()
+        .unwrap()
+        .iter()
+        .map(|node| node.id.as_str())
+        .collect();
+    assert_eq!(order, vec!["a", "d", "b", "c"]);
+
+    dag.nodes[1].parents = vec!["c".into()];
+    assert_eq!(
+        dag.topological_order().unwrap_err(),
+        DagError::Cycle("c".into())
+    );
+    dag.nodes[1].parents = vec!["missing".into()];
+    assert!(matches!(
+        dag.topological_order(),
+        Err(DagError::UnknownParent { .. })
+    ));
+
+    let built = pipeline();
+    let order: Vec<&str> = built
+        .topological_order()
+        .unwrap()
+        .iter()
+        .map(|node| node.node_type.as_str())
+        .collect();
+    assert_eq!(
+        order,
+        vec!["policy_check", "triage", "model_run", "verification"]
+    );
+}
+
+#[test]
+fn merkle_root_commits_to_edges() {
+    let dag = pipeline();
+    let mut rewired = dag.clone();
+    rewired.nodes[2].parents = vec![dag.nodes[0].id.clone()];
+    assert_ne!(dag.merkle_root(), rewired.merkle_root());
+
+    // Nodes without parents commit exactly as before edges existed.
+    let mut flat = RequestDag::default();
+    flat.add_node("policy", json!({"ok": true}), &[]).unwrap();
+    let commitment = flat.nodes[0].commitment();
+    assert_eq!(
+        serde_json::to_value(&commitment).unwrap(),
+        json!({
+            "id": commitment.id,
+            "node_type": "policy",
+            "payload_hash": commitment.payload_hash,
+        })
+    );
+    assert_eq!(flat.merkle_root(), commitment_root(&[commitment]));
+    assert!(!serde_json::to_string(&flat.nodes[0])
+        .unwrap()
+        .contains("parents"));
+}
+
+#[test]
+fn merkle_proofs_place_each_leaf_under_the_root() {
+    for width in 1..=9 {
+        let leaves: Vec<String> = (0..width).map(|i| format!("leaf-{i}")).collect();
+        let tree = MerkleTree::from_leaves(leaves.clone());
+        let root = tree.root().unwrap();
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = tree.proof(index).unwrap();
+            assert!(proof.verify(leaf, &root), "width {width} index {index}");
+            assert!(!proof.verify("other", &root));
+            if let Some(step) = proof.path.first() {
+                let mut moved = proof.clone();
+                moved.leaf_index = (index + 1) % width;
+                assert!(!moved.verify(leaf, &root));
+                let mut flipped = proof.clone();
+                flipped.path[0].side = match step.side {
+                    Side::Left => Side::Right,
+                    Side::Right => Side::Left,
+                };
+                assert!(!flipped.verify(leaf, &root));
+            }
+        }
+        assert_eq!(tree.proof(width), Err(MerkleError::NoLeaf(width)));
+    }
+
+    // The fifth of five leaves pairs with the subtree over the other four.
+    let tree = MerkleTree::from_leaves((0..5).map(|i| i.to_string()).collect());
+    let proof = tree.proof(4).unwrap();
+    assert_eq!(proof.path.len(), 1);
+    assert_eq!(proof.path[0].side, Side::Left);
+}
+
+#[test]
+fn legacy_trees_still_prove_and_verify() {
+    for width in 1..=9 {
+        let leaves: Vec<String> = (0..width).map(|i| format!("leaf-{i}")).collect();
+        let tree = MerkleTree::legacy(leaves.clone());
+        let root = tree.root().unwrap();
+        assert_ne!(
+            Ok(&root),
+            MerkleTree::from_leaves(leaves.clone()).root().as_ref()
+        );
+        for (index, leaf) in leaves.iter().enumerate() {
+            let proof = tree.proof(index).unwrap();
+            assert_eq!(proof.version, LEGACY_TREE);
+            assert!(proof.verify(leaf, &root), "width {width} index {index}");
+            let mut relabeled = proof.clone();
+            relabeled.version = TREE_VERSION;
+            assert!(!relabeled.verify(leaf, &root));
+        }
+    }
+
+    // The legacy fifth of five leaves is promoted twice before it is paired.
+    let tree = MerkleTree::legacy((0..5).map(|i| i.to_string()).collect());
+    assert_eq!(tree.proof(4).unwrap().path.len(), 1);
+    assert_eq!(tree.proof(0).unwrap().path.len(), 3);
+}
+
+#[test]
+fn domain_separation_and_leaf_counts_keep_roots_apart() {
+    let leaves = |items: &[&str]| items.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    let pair = MerkleTree::legacy(leaves(&["a", "b"])).root().unwrap();
+
+    // In the legacy tree a lone leaf equal to a pair's hash has the pair's root, and an
+    // interior hash can stand in for two leaves.
+    assert_eq!(
+        MerkleTree::legacy(vec![pair.clone()]).root(),
+        Ok(pair.clone())
+    );
+    assert_eq!(
+        MerkleTree::legacy(leaves(&["a", "b", "c"])).root(),
+        MerkleTree::legacy(vec![pair.clone(), "c".into()]).root()
+    );
+
+    let pair = MerkleTree::from_leaves(leaves(&["a", "b"])).root().unwrap();
+    assert_ne!(
+        MerkleTree::from_leaves(vec![pair.clone()]).root(),
+        Ok(pair.clone())
+    );
+    assert_ne!(
+        MerkleTree::from_leaves(leaves(&["a", "b", "c"])).root(),
+        MerkleTree::from_leaves(vec![pair, "c".into()]).root()
+    );
+
+    // A proof claiming a different leaf count does not reach the root.
+    let tree = MerkleTree::from_leaves(leaves(&["a", "b", "c", "d"]));
+    let root = tree.root().unwrap();
+    let mut proof = tree.proof(0).unwrap();
+    assert!(proof.verify("a", &root));
+    proof.leaf_count = 5;
+    assert!(!proof.verify("a", &root));
+}
+
+#[test]
+fn unknown_tree_formats_are_rejected() {
+    let leaves: Vec<String> = ["a", "b", "c"].iter().map(|s| s.to_string()).collect();
+    let tree = MerkleTree::from_leaves(leaves.clone());
+    let root = tree.root().unwrap();
+    let mut proof = tree.proof(1).unwrap();
+
+    let future = MerkleTree {
+        version: TREE_VERSION + 1,
+        ..MerkleTree::from_leaves(leaves)
+    };
+    assert_eq!(
+        future.root(),
+        Err(MerkleError::UnknownVersion(TREE_VERSION + 1))
+    );
+    assert!(future.proof(1).is_err());
+    proof.version = TREE_VERSION + 1;
+    assert!(!proof.verify("b", &root));
+    assert_eq!(
+        MerkleTree::from_leaves(Vec::new()).root(),
+        Err(MerkleError::Empty)
+    );
+}
+
+#[test]
+fn node_proofs_verify_against_the_dag_root() {
+    let dag = pipeline();
+    let root = dag.merkle_root().unwrap();
+    for node in &dag.nodes {
+        let proof = dag.merkle_proof(&node.id).unwrap();
+        assert_eq!(proof.commitment, node.commitment());
+        assert!(proof.verify(&root));
+    }
+    let mut forged = dag.merkle_proof(&dag.nodes[2].id).unwrap();
+    forged.commitment.payload_hash = dag.nodes[3].payload_hash();
+    assert!(!forged.verify(&root));
+    assert!(dag.merkle_proof("missing").is_none());
+}
+
+#[derive(Debug, PartialEq)]
+enum Failure {
+    Transient,
+    Fatal(&'static str),
+}
+
+impl TaskError for Failure {
+    fn is_transient(&self) -> bool {
+        *self == Failure::Transient
+    }
+}
+
+fn constant(n: u32) -> impl Fn(TaskInputs) -> std::future::Ready<Result<TaskOutput, Failure>> {
+    move |_| std::future::ready(Ok(TaskOutput::new(json!({ "n": n }), n)))
+}
+
+#[tokio::test]
+async fn independent_tasks_run_concurrently_and_feed_dependents() {
+    // Each side waits for the other, so the run only finishes if both run at once.
+    let barrier = Arc::new(Barrier::new(2));
+    let side = |n: u32| {
+        let barrier = barrier.clone();
+        move |inputs: TaskInputs| {
+            let barrier = barrier.clone();
+            async move {
+                barrier.wait().await;
+                let base = *inputs.get::<u32>("source").unwrap();
+                Ok::<_, Failure>(TaskOutput::new(json!({ "n": base + n }), base + n))
+            }
+        }
+    };
+    let mut graph = TaskGraph::default();
+    let source = graph
+        .add_task("source", &[], TaskPolicy::default(), constant(10))
+        .unwrap();
+    let left = graph
+        .add_task("left", &[source], TaskPolicy::default(), side(1))
+        .unwrap();
+    let right = graph
+        .add_task("right", &[source], TaskPolicy::default(), side(2))
+        .unwrap();
+    graph
+        .add_task(
+            "sum",
+            &[left, right],
+            TaskPolicy::default(),
+            |inputs: TaskInputs| async move {
+                let sum = inputs.get::<u32>("left").unwrap() + inputs.get::<u32>("right").unwrap();
+                Ok::<_, Failure>(TaskOutput::new(json!({ "sum": sum }), sum))
+            },
+        )
+        .unwrap();
+
+    let run = tokio::time::timeout(
+        Duration::from_secs(5),
+        DagScheduler::default().execute(graph),
+    )
+    .await
+    .expect("independent tasks ran concurrently")
+    .unwrap();
+    assert!(run.halted.is_none());
+    assert_eq!(run.value::<u32>("sum"), Some(&23));
+    let sum = &run.dag.nodes[3];
+    assert_eq!(sum.payload, json!({ "sum": 23 }));
+    assert_eq!(
+        sum.parents,
+        vec![run.dag.nodes[1].id.clone(), run.dag.nodes[2].id.clone()]
+    );
+    assert!(run.dag.nodes.iter().all(DagNode::id_is_valid));
+    assert_eq!(run.dag.topological_order().unwrap().len(), 4);
+}
+
+#[tokio::test]
+async fn failed_attempts_are_retried_and_timeouts_stop_the_run() {
+    let calls = Arc::new(AtomicU32::new(0));
+    let flaky = {
+        let calls = calls.clone();
+        move |inputs: TaskInputs| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            async move {
+                match inputs.attempt() {
+                    0 => Err(Failure::Transient),
+                    _ => Ok(TaskOutput::record(json!({ "ok": true }))),
+                }
+            }
+        }
+    };
+    let mut graph = TaskGraph::default();
+    let first = graph
+        .add_task(
+            "flaky",
+            &[],
+            TaskPolicy::default().with_retries(2, Duration::from_millis(1)),
+            flaky,
+        )
+        .unwrap();
+    graph
+        .add_task(
+            "slow",
+            &[first],
+            TaskPolicy::timeout(Duration::from_millis(20)).with_retries(1, Duration::ZERO),
+            |_: TaskInputs| async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok::<_, Failure>(TaskOutput::record(json!({})))
+            },
+        )
+        .unwrap();
+
+    let err = DagScheduler::default().execute(graph).await.unwrap_err();
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    assert_eq!(err.node, "slow");
+    assert_eq!(err.attempts, 2);
+    assert!(
+        matches!(err.failure, TaskFailure::TimedOut(limit) if limit == Duration::from_millis(20))
+    );
+    assert_eq!(err.dag.nodes.len(), 1);
+    assert_eq!(err.dag.nodes[0].payload, json!({ "ok": true }));
+
+    // A deterministic failure is not retried.
+    let mut graph = TaskGraph::default();
+    graph
+        .add_task(
+            "broken",
+            &[],
+            TaskPolicy::default().with_retries(2, Duration::ZERO),
+            |_: TaskInputs| async { Err::<TaskOutput, _>(Failure::Fatal("no")) },
+        )
+        .unwrap();
+    let err = DagScheduler::default().execute(graph).await.unwrap_err();
+    assert!(matches!(
+        err.failure,
+        TaskFailure::Failed(Failure::Fatal("no"))
+    ));
+    assert_eq!(err.attempts, 1);
+
+    // Blocking work awaited through its join handle is still cut off at the timeout.
+    let mut graph = TaskGraph::default();
+    graph
+        .add_task(
+            "blocking",
+            &[],
+            TaskPolicy::timeout(Duration::from_millis(20)),
+            |_: TaskInputs| async {
+                tokio::task::spawn_blocking(|| std::thread::sleep(Duration::from_millis(500)))
+                    .await
+                    .unwrap();
+                Ok::<_, Failure>(TaskOutput::record(json!({})))
+            },
+        )
+        .unwrap();
+    let started = std::time::Instant::now();
+    let err = DagScheduler::default().execute(graph).await.unwrap_err();
+    assert!(matches!(err.failure, TaskFailure::TimedOut(_)));
+    assert!(started.elapsed() < Duration::from_millis(400));
+}
+
+#[tokio::test]
+async fn halting_skips_the_rest_of_the_graph() {
+    let started = Arc::new(AtomicU32::new(0));
+    let mut graph = TaskGraph::default();
+    let gate = graph
+        .add_task("gate", &[], TaskPolicy::default(), |_: TaskInputs| async {
+            Ok::<_, Failure>(TaskOutput::new(json!({ "action": "ask" }), "clarify").halt())
+        })
+        .unwrap();
+    graph
+        .add_task("model_run", &[gate], TaskPolicy::default(), {
+            let started = started.clone();
+            move |_: TaskInputs| {
+                started.fetch_add(1, Ordering::SeqCst);
+                async { Ok::<_, Failure>(TaskOutput::record(json!({}))) }
+            }
+        })
+        .unwrap();
+
+    let run = DagScheduler::default().execute(graph).await.unwrap();
+    assert_eq!(run.halted.as_ref(), Some(&run.dag.nodes[0].id));
+    assert_eq!(run.value::<&str>("gate"), Some(&"clarify"));
+    assert_eq!(run.dag.nodes.len(), 1);
+    assert_eq!(started.load(Ordering::SeqCst), 0);
+}
@@ -0,0 +1,152 @@
+use axiomhive::{
+    audit::{AuditEntry, AuditLog},
+    dag::libp2p_integration::{P2PNetwork, PeerInfo},
+    egress::{EgressError, EgressGate, EgressKind},
+    policy::PolicyProvenance,
+};
+use libp2p::PeerId;
+use serde_json::json;
+use std::{
+    io::{ErrorKind, Read, Write},
+    net::TcpListener,
+    sync::Arc,
+};
+
+fn listener() -> (TcpListener, String) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("bind");
+    listener.set_nonblocking(true).expect("nonblocking");
+    let addr = listener.local_addr().unwrap().to_string();
+    (listener, addr)
+}
+
+fn assert_no_connection(listener: &TcpListener) {
+    match listener.accept() {
+        Err(err) => assert_eq!(err.kind(), ErrorKind::WouldBlock),
+        Ok((_, peer)) => panic!("socket opened from {peer} in offline mode"),
+    }
+}
+
+/// Answers one request on `listener` with `response` verbatim.
+fn respond_once(listener: TcpListener, response: String) {
+    listener.set_nonblocking(false).unwrap();
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0u8; 1024];
+        let _ = stream.read(&mut request);
+        let _ = stream.write_all(response.as_bytes());
+    });
+}
+
+#[tokio::test]
+async fn offline_gate_opens_no_sockets_and_audits_attempts() {
+    let tmp = tempfile::tempdir().unwrap();
+    let audit_path = tmp.path().join("audit.jsonl");
+    let audit = Arc::new(AuditLog::open(&audit_path).unwrap());
+    let gate = EgressGate::offline().with_audit(audit, PolicyProvenance::default());
+    let (listener, addr) = listener();
+    let url = format!("http://{addr}/axioms.json?token=secret");
+
+    let attempts = [
+        gate.get(EgressKind::Http, &url, true).await.err(),
+        gate.get(EgressKind::AxiomFetch, &url, true).await.err(),
+        gate.post_json(EgressKind::Webhook, &url, &json!({"event": "done"}), true)
+            .await
+            .err(),
+        gate.connect(EgressKind::P2pDial, &addr, true).await.err(),
+    ];
+    let mut network = P2PNetwork::default();
+    network.add_peer(PeerInfo {
+        peer_id: PeerId::random(),
+        address: format!("/ip4/127.0.0.1/tcp/{}", addr.rsplit(':').next().unwrap()),
+    });
+    let peer = network.discover_peers().remove(0);
+    let dialed = network.dial(&gate, &peer, true).await.err();
+
+    for err in attempts.into_iter().chain([dialed]) {
+        assert!(matches!(err, Some(EgressError::Denied { .. })), "{err:?}");
+    }
+    assert_no_connection(&listener);
+
+    let entries: Vec<AuditEntry> = std::fs::read_to_string(&audit_path)
+        .unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(entries.len(), 5);
+    assert!(entries.iter().all(|entry| entry.mode == "egress"));
+    assert_eq!(entries[2].dag[0].payload["kind"], "webhook");
+    let target = entries[0].dag[0].payload["target"].as_str().unwrap();
+    assert!(!target.contains("secret"));
+}
+
+#[tokio::test]
+async fn request_flag_off_denies_even_when_node_allows() {
+    let gate = EgressGate::new(true);
+    let (listener, addr) = listener();
+
+    let err = gate.connect(EgressKind::P2pDial, &addr, false).await;
+    assert!(matches!(err, Err(EgressError::Denied { .. })));
+    assert_no_connection(&listener);
+
+    gate.connect(EgressKind::P2pDial, &addr, true)
+        .await
+        .expect("online dial");
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    assert!(listener.accept().is_ok());
+}
+
+#[tokio::test]
+async fn redirects_are_not_followed() {
+    let gate = EgressGate::new(true);
+    let (server, addr) = listener();
+    let (target, target_addr) = listener();
+    respond_once(
+        server,
+        format!(
+            "HTTP/1.1 302 Found\r\nLocation: http://{target_addr}/\r\nContent-Length: 0\r\n\r\n"
+        ),
+    );
+
+    let err = gate
+        .get(EgressKind::Http, &format!("http://{addr}/"), true)
+        .await;
+    assert!(matches!(err, Err(EgressError::Transport(_))), "{err:?}");
+    assert_no_connection(&target);
+}
+
+#[tokio::test]
+async fn response_bodies_are_capped() {
+    let gate = EgressGate::new(true).with_max_body(16);
+    let body = "x".repeat(64);
+
+    let (server, addr) = listener();
+    respond_once(
+        server,
+        format!("HTTP/1.1 200 OK\r\nContent-Length: 64\r\n\r\n{body}"),
+    );
+    let err = gate
+        .get(EgressKind::Http, &format!("http://{addr}/"), true)
+        .await;
+    assert!(matches!(err, Err(EgressError::TooLarge(16))), "{err:?}");
+
+    let (server, addr) = listener();
+    respond_once(
+        server,
+        format!("HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n{body}"),
+    );
+    let err = gate
+        .get(EgressKind::Http, &format!("http://{addr}/"), true)
+        .await;
+    assert!(matches!(err, Err(EgressError::TooLarge(16))), "{err:?}");
+
+    let (server, addr) = listener();
+    respond_once(
+        server,
+        "HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nsmall".to_string(),
+    );
+    let body = gate
+        .get(EgressKind::Http, &format!("http://{addr}/"), true)
+        .await
+        .unwrap();
+    assert_eq!(body, b"small");
+}
@@ -1,4 +1,8 @@
-use axiomhive::policy::{PolicyError, SafetyPolicy};
+use axiomhive::policy::{
+    sign_policy, signature_path, Action, IdentityAction, OutputAction, PiiAction, PiiKind,
+    PolicyError, PolicyLoadOptions, PolicyRequest, RiskLevel, SafetyConfig, SafetyPolicy,
+};
+use ed25519_dalek::SigningKey;
 use std::fs;
 
 #[test]
@@ -37,3 +41,366 @@ fn safe_prompt_passes_policy() {
     let policy = SafetyPolicy::load_from_disk(config_path).unwrap();
     policy.check_prompt("hello world").expect("policy allow");
 }
+
+#[test]
+fn output_terms_are_redacted_when_configured() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("policy.json");
+    fs::write(
+        &config_path,
+        r#"{
+            "allow_network": false,
+            "allow_verified": true,
+            "blocklist": ["malware"],
+            "high_risk_terms": [],
+            "output": {"blocked_action": "redact", "redaction_marker": "***"}
+        }"#,
+    )
+    .unwrap();
+    let policy = SafetyPolicy::load_from_disk(config_path).unwrap();
+    let verdict = policy.check_output("here is some MALWARE and more malware");
+    assert_eq!(verdict.action, OutputAction::Redact);
+    assert_eq!(verdict.redactions, 2);
+    assert_eq!(verdict.output, "here is some *** and more ***");
+    assert!(verdict.clone().release_unaltered().is_err());
+    assert_eq!(verdict.release().unwrap(), "here is some *** and more ***");
+}
+
+#[test]
+fn blocked_output_is_not_released() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("policy.json");
+    fs::write(
+        &config_path,
+        r#"{
+            "allow_network": false,
+            "allow_verified": true,
+            "blocklist": ["malware"],
+            "high_risk_terms": ["medical diagnosis"]
+        }"#,
+    )
+    .unwrap();
+    let policy = SafetyPolicy::load_from_disk(config_path).unwrap();
+    let escalated = policy.check_output("a medical diagnosis follows");
+    assert!(matches!(
+        escalated.release(),
+        Err(PolicyError::EscalationRequired(_))
+    ));
+    let blocked = policy.check_output("malware and a medical diagnosis");
+    assert_eq!(blocked.action, OutputAction::Block);
+    assert!(matches!(
+        blocked.release(),
+        Err(PolicyError::OutputBlocked(_))
+    ));
+    assert_eq!(
+        policy.check_output("hello world").release().unwrap(),
+        "hello world"
+    );
+}
+
+#[test]
+fn configured_rules_match_in_order() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("policy.json");
+    fs::write(
+        &config_path,
+        r#"{
+            "allow_network": false,
+            "allow_verified": true,
+            "blocklist": ["malware"],
+            "high_risk_terms": ["medical diagnosis"],
+            "rules": [
+                {
+                    "id": "clinic-diagnosis",
+                    "when": {"callers": ["clinic"], "modes": ["verified"]},
+                    "action": "allow",
+                    "risk": "medium",
+                    "reason": "clinical team",
+                    "requires_payment": true
+                },
+                {
+                    "id": "no-audio",
+                    "when": {"media": ["audio"]},
+                    "action": "deny",
+                    "risk": "low",
+                    "reason": "audio disabled"
+                }
+            ]
+        }"#,
+    )
+    .unwrap();
+    let policy = SafetyPolicy::load_from_disk(config_path).unwrap();
+
+    let clinic = policy
+        .check_prompt(PolicyRequest {
+            prompt: "medical diagnosis for case 12",
+            mode: "verified",
+            caller: Some("clinic"),
+            ..PolicyRequest::default()
+        })
+        .expect("clinic allowed");
+    assert_eq!(clinic.rule_id.as_deref(), Some("clinic-diagnosis"));
+    assert_eq!(clinic.risk, RiskLevel::Medium);
+    assert!(clinic.requires_payment);
+
+    let other = policy.evaluate(&PolicyRequest {
+        prompt: "medical diagnosis for case 12",
+        mode: "verified",
+        caller: Some("marketing"),
+        ..PolicyRequest::default()
+    });
+    assert_eq!(other.action, Action::Escalate);
+    assert!(other.requires_consent);
+
+    let media = vec!["text".to_string(), "audio".to_string()];
+    let denied = policy.evaluate(&PolicyRequest {
+        prompt: "a song",
+        mode: "creative",
+        media: &media,
+        ..PolicyRequest::default()
+    });
+    assert_eq!(denied.action, Action::Deny);
+    assert_eq!(denied.reason, "audio disabled");
+
+    let blocked = policy.evaluate(&PolicyRequest {
+        prompt: "malware",
+        mode: "verified",
+        caller: Some("clinic"),
+        ..PolicyRequest::default()
+    });
+    assert_eq!(blocked.rule_id.as_deref(), Some("blocklist"));
+    assert_eq!(blocked.risk, RiskLevel::High);
+}
+
+#[test]
+fn strict_loading_refuses_missing_policy() {
+    let dir = tempfile::tempdir().unwrap();
+    let missing = dir.path().join("missing.json");
+    assert!(SafetyPolicy::load_from_disk(&missing).is_ok());
+
+    let strict = PolicyLoadOptions {
+        strict: true,
+        publisher_key: None,
+    };
+    let res = SafetyPolicy::load(&missing, &strict);
+    assert!(matches!(res, Err(PolicyError::ConfigLoad(_))));
+}
+
+#[test]
+fn signed_policy_is_verified_against_publisher_key() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("policy.json");
+    let raw = r#"{
+        "allow_network": false,
+        "allow_verified": true,
+        "blocklist": ["malware"],
+        "high_risk_terms": []
+    }"#;
+    fs::write(&config_path, raw).unwrap();
+
+    let publisher = SigningKey::from_bytes(&[7u8; 32]);
+    let options = PolicyLoadOptions {
+        strict: true,
+        publisher_key: Some(hex::encode(publisher.verifying_key().to_bytes())),
+    };
+    let unsigned = SafetyPolicy::load(&config_path, &options);
+    assert!(matches!(unsigned, Err(PolicyError::SignatureInvalid(_))));
+
+    fs::write(
+        signature_path(&config_path),
+        sign_policy(raw.as_bytes(), &publisher),
+    )
+    .unwrap();
+    let policy = SafetyPolicy::load(&config_path, &options).expect("signed policy");
+    let provenance = policy.provenance();
+    assert_eq!(
+        provenance.signer.as_deref(),
+        options.publisher_key.as_deref()
+    );
+    assert_eq!(
+        provenance.hash,
+        blake3::hash(raw.as_bytes()).to_hex().to_string()
+    );
+
+    fs::write(&config_path, raw.replace("malware", "nothing")).unwrap();
+    let tampered = SafetyPolicy::load(&config_path, &options);
+    assert!(matches!(tampered, Err(PolicyError::SignatureInvalid(_))));
+}
+
+#[test]
+fn pii_is_tokenized_hashed_or_blocked_per_kind() {
+    let mut config = SafetyConfig::default();
+    config.pii.phone = PiiAction::Hash;
+    let policy = SafetyPolicy::from_config(config).unwrap();
+
+    let prompt =
+        "Mail jane.doe@example.com or call +1 415 555 0100 re IBAN GB82 WEST 1234 5698 7654 32";
+    let scrub = policy.scrub_pii(prompt).expect("no blocked kinds");
+    assert!(!scrub.text.contains("jane.doe@example.com"));
+    assert!(!scrub.text.contains("555 0100"));
+    assert!(!scrub.text.contains("GB82"));
+    assert!(scrub.text.contains("[EMAIL_1]"));
+    assert!(scrub.text.contains("[PHONE#"));
+    assert!(scrub.text.contains("[IBAN]"));
+    assert_eq!(scrub.report.detected[&PiiKind::Email], 1);
+    assert_eq!(scrub.report.detected[&PiiKind::Iban], 1);
+
+    let report = serde_json::to_string(&scrub.report).unwrap();
+    assert!(!report.contains("example.com"));
+    assert_eq!(
+        scrub.vault.restore("reply to [EMAIL_1]"),
+        "reply to jane.doe@example.com"
+    );
+
+    let card = policy.scrub_pii("card 4111 1111 1111 1111 exp 2027-01-01");
+    assert!(matches!(card, Err(PolicyError::PiiBlocked(_))));
+    let not_a_card = policy
+        .scrub_pii("invoice 4111 1111 1111 1112 due 2027-01-01")
+        .unwrap();
+    assert!(!not_a_card
+        .report
+        .detected
+        .contains_key(&PiiKind::CardNumber));
+}
+
+#[test]
+fn pii_followed_by_uppercase_words_is_still_found() {
+    let mut config = SafetyConfig::default();
+    config.pii.iban = PiiAction::Block;
+    let policy = SafetyPolicy::from_config(config).unwrap();
+
+    for prompt in [
+        "Wire to DE89370400440532013000 EUR",
+        "Pay GB82WEST12345698765432 NOW please",
+        "Pay GB82 WEST 1234 5698 7654 32 TODAY",
+    ] {
+        let scrub = policy.scrub_pii(prompt);
+        assert!(
+            matches!(&scrub, Err(PolicyError::PiiBlocked(kinds)) if kinds == "iban"),
+            "{prompt}: {scrub:?}"
+        );
+    }
+
+    let policy = SafetyPolicy::from_config(SafetyConfig::default()).unwrap();
+    let scrub = policy
+        .scrub_pii("Wire to DE89370400440532013000 EUR")
+        .unwrap();
+    assert_eq!(scrub.text, "Wire to [IBAN] EUR");
+    assert!(!scrub.report.detected.contains_key(&PiiKind::Phone));
+
+    for prompt in [
+        "card 4111111111111111 VISA",
+        "card 4111 1111 1111 1111 EXP",
+        "card 4111-1111-1111-1111-22 ref",
+    ] {
+        let scrub = policy.scrub_pii(prompt);
+        assert!(
+            matches!(&scrub, Err(PolicyError::PiiBlocked(kinds)) if kinds == "card"),
+            "{prompt}: {scrub:?}"
+        );
+    }
+}
+
+#[test]
+fn api_keys_select_inherited_profiles() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("policy.json");
+    let key_hash = hex::encode(<sha2::Sha256 as sha2::Digest>::digest(b"med-key"));
+    fs::write(
+        &config_path,
+        format!(
+            r#"{{
+            "allow_network": false,
+            "allow_verified": true,
+            "blocklist": ["malware"],
+            "high_risk_terms": ["medical diagnosis", "financial advice"],
+            "profiles": {{
+                "clinical": {{"high_risk_terms": ["financial advice"], "price_multiplier": 2.5}},
+                "medical": {{"inherits": "clinical", "allowed_media": ["text", "pdf"]}}
+            }},
+            "clients": {{"med-team": "medical"}},
+            "api_keys": {{"{key_hash}": {{"client": "med-team"}}}}
+        }}"#
+        ),
+    )
+    .unwrap();
+    let policy = SafetyPolicy::load_from_disk(&config_path).unwrap();
+
+    assert!(matches!(
+        policy.authenticate(Some("wrong-key")),
+        Err(PolicyError::Unauthorized)
+    ));
+    let anonymous = policy.authenticate(None).unwrap();
+    assert!(policy
+        .for_caller(&anonymous)
+        .check_prompt("medical diagnosis")
+        .is_err());
+
+    let caller = policy.authenticate(Some("med-key")).unwrap();
+    assert_eq!(caller.client.as_deref(), Some("med-team"));
+    let medical = policy.for_caller(&caller);
+    medical
+        .check_prompt("medical diagnosis")
+        .expect("medical profile drops the diagnosis escalation");
+    assert!(medical.check_prompt("malware").is_err());
+    assert_eq!(medical.price_multiplier(), 2.5);
+    assert!(medical.ensure_media_allowed(&["pdf".into()]).is_ok());
+    assert!(medical
+        .ensure_media_allowed(&["PDF".into(), "Text".into()])
+        .is_ok());
+    assert!(matches!(
+        medical.ensure_media_allowed(&["audio".into()]),
+        Err(PolicyError::MediaNotAllowed(_))
+    ));
+    let provenance = medical.provenance();
+    assert_eq!(provenance.profile.as_deref(), Some("medical"));
+    assert_eq!(provenance.hash, policy.provenance().hash);
+}
+
+#[test]
+fn profile_inheritance_cycles_are_rejected() {
+    let mut config = SafetyConfig::default();
+    config.profiles.insert(
+        "a".into(),
+        serde_json::from_str(r#"{"inherits": "b"}"#).unwrap(),
+    );
+    config.profiles.insert(
+        "b".into(),
+        serde_json::from_str(r#"{"inherits": "a"}"#).unwrap(),
+    );
+    assert!(matches!(
+        SafetyPolicy::from_config(config),
+        Err(PolicyError::ConfigLoad(_))
+    ));
+}
+
+#[test]
+fn identity_gate_matches_fields_and_phrases() {
+    let policy = SafetyPolicy::from_config(SafetyConfig::default()).unwrap();
+
+    let clean = policy.check_identity("summarize this memo", &["query".to_string()]);
+    assert_eq!(clean.action, IdentityAction::Allow);
+    assert_eq!(clean.enforce(true).unwrap(), None);
+
+    let verdict = policy.check_identity("Who am I?", &["Username".to_string()]);
+    assert_eq!(verdict.action, IdentityAction::Transmute);
+    assert_eq!(verdict.matched_fields, vec!["username"]);
+    assert_eq!(verdict.matched_phrases, vec!["who am i"]);
+    assert_eq!(
+        verdict.enforce(true).unwrap(),
+        Some("Identity processing is structurally barred.")
+    );
+    assert!(matches!(
+        verdict.enforce(false),
+        Err(PolicyError::IdentityBarred(_))
+    ));
+
+    let mut config = SafetyConfig::default();
+    config.identity.action = IdentityAction::Escalate;
+    let policy = SafetyPolicy::from_config(config).unwrap();
+    let verdict = policy.check_identity("profile me please", &[]);
+    assert!(matches!(
+        verdict.enforce(true),
+        Err(PolicyError::EscalationRequired(_))
+    ));
+}
@@ -0,0 +1,99 @@
+use axiomhive::transparency::{
+    consistency_path, inclusion_path, leaf_hash, tree_root, verify_consistency, verify_inclusion,
+    TreeCache, TreeHash,
+};
+
+fn leaves(n: usize) -> Vec<TreeHash> {
+    (0..n).map(|i| leaf_hash(&format!("entry-{i}"))).collect()
+}
+
+#[test]
+fn inclusion_proofs_verify_for_every_leaf_and_size() {
+    for size in 1..=17 {
+        let tree = leaves(size);
+        let root = tree_root(&tree);
+        for index in 0..size {
+            let path = inclusion_path(&tree, index);
+            assert!(verify_inclusion(&tree[index], index, size, &path, &root));
+            let other = (index + 1) % size;
+            if other != index {
+                assert!(!verify_inclusion(&tree[other], index, size, &path, &root));
+            }
+            let stale = tree_root(&leaves(size + 1));
+            assert!(!verify_inclusion(&tree[index], index, size, &path, &stale));
+        }
+    }
+}
+
+#[test]
+fn consistency_proofs_verify_for_every_pair_of_sizes() {
+    let tree = leaves(17);
+    for second in 1..=tree.len() {
+        let second_root = tree_root(&tree[..second]);
+        for first in 1..=second {
+            let first_root = tree_root(&tree[..first]);
+            let proof = consistency_path(&tree[..second], first);
+            assert!(
+                verify_consistency(first, second, &first_root, &second_root, &proof),
+                "{first} -> {second}"
+            );
+            if first < second {
+                let forked = tree_root(&leaves(first + 1)[1..]);
+                assert!(!verify_consistency(
+                    first,
+                    second,
+                    &forked,
+                    &second_root,
+                    &proof
+                ));
+            }
+        }
+    }
+}
+
+#[test]
+fn cached_tree_matches_rebuilt_tree_for_every_prefix() {
+    let tree = leaves(33);
+    let mut cache = TreeCache::default();
+    for leaf in &tree {
+        cache.push(*leaf);
+    }
+
+    for size in 0..=tree.len() {
+        assert_eq!(cache.root(size), Some(tree_root(&tree[..size])));
+        for index in 0..size {
+            assert_eq!(
+                cache.inclusion_path(index, size),
+                Some(inclusion_path(&tree[..size], index))
+            );
+        }
+        for first in 0..=size {
+            assert_eq!(
+                cache.consistency_path(first, size),
+                Some(consistency_path(&tree[..size], first))
+            );
+        }
+    }
+    assert_eq!(cache.root(34), None);
+    assert_eq!(cache.inclusion_path(5, 5), None);
+    assert_eq!(cache.consistency_path(6, 5), None);
+}
+
+#[test]
+fn tampered_proofs_are_rejected() {
+    let tree = leaves(9);
+    let root = tree_root(&tree);
+    let mut path = inclusion_path(&tree, 4);
+    path[1][0] ^= 1;
+    assert!(!verify_inclusion(&tree[4], 4, 9, &path, &root));
+
+    let mut proof = consistency_path(&tree, 5);
+    proof.pop();
+    assert!(!verify_consistency(
+        5,
+        9,
+        &tree_root(&tree[..5]),
+        &root,
+        &proof
+    ));
+}
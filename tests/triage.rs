@@ -0,0 +1,47 @@
+use axiomhive::triage::{triage, TriageConfig, TriageOutcome};
+
+#[test]
+fn clear_prompts_act_and_vague_prompts_ask() {
+    let config = TriageConfig::default();
+
+    let clear = triage("write a short poem about autumn rain", &config);
+    assert_eq!(clear.outcome, TriageOutcome::Act);
+    assert_eq!(clear.confidence, 1.0);
+    assert!(clear.signals.is_empty());
+
+    let vague = triage("fix it", &config);
+    assert_eq!(vague.outcome, TriageOutcome::Ask);
+    assert_eq!(vague.confidence, 0.8);
+    assert_eq!(vague.signals[0].kind, "vague_reference");
+
+    let empty = triage("   ", &config);
+    assert_eq!(empty.confidence, 0.0);
+    assert_eq!(empty.outcome, TriageOutcome::Ask);
+
+    let disabled = TriageConfig {
+        enabled: false,
+        ..TriageConfig::default()
+    };
+    assert_eq!(triage("fix it", &disabled).outcome, TriageOutcome::Act);
+}
+
+#[test]
+fn single_words_are_scored_on_their_content() {
+    let config = TriageConfig::default();
+
+    let topic = triage("photosynthesis", &config);
+    assert_eq!(topic.outcome, TriageOutcome::Act);
+    assert!(topic.signals.is_empty());
+
+    let instruction = triage("Summarize.", &config);
+    assert_eq!(instruction.outcome, TriageOutcome::Ask);
+    assert_eq!(instruction.signals[0].kind, "missing_subject");
+
+    let fragment = triage("ok", &config);
+    assert_eq!(fragment.outcome, TriageOutcome::Ask);
+    assert_eq!(fragment.signals[0].kind, "length");
+
+    let reference = triage("again", &config);
+    assert_eq!(reference.outcome, TriageOutcome::Ask);
+    assert_eq!(reference.signals[0].kind, "vague_reference");
+}